@@ -451,6 +451,8 @@ impl<'a> LiquidateHelper<'a> {
                 asset_token_index,
                 liab_token_index,
                 max_liab_transfer,
+                false,
+                false,
             )
             .await
             .context("sending liq_token_with_token")?;
@@ -1134,6 +1134,8 @@ impl MangoClient {
         asset_token_index: TokenIndex,
         liab_token_index: TokenIndex,
         max_liab_transfer: I80F48,
+        reject_liqor_asset_netting: bool,
+        dust_liqor_asset: bool,
     ) -> anyhow::Result<Signature> {
         let health_remaining_ams = self
             .derive_liquidation_health_check_remaining_account_metas(
@@ -1163,6 +1165,8 @@ impl MangoClient {
                 asset_token_index,
                 liab_token_index,
                 max_liab_transfer,
+                reject_liqor_asset_netting,
+                dust_liqor_asset,
             }),
         };
         self.send_and_confirm_owner_tx(vec![ix]).await
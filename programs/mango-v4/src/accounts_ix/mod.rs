@@ -15,6 +15,7 @@ pub use group_edit::*;
 pub use group_withdraw_insurance_fund::*;
 pub use health_region::*;
 pub use ix_gate_set::*;
+pub use perp_book_defragment::*;
 pub use perp_cancel_all_orders::*;
 pub use perp_cancel_all_orders_by_side::*;
 pub use perp_cancel_order::*;
@@ -28,6 +29,9 @@ pub use perp_force_close_position::*;
 pub use perp_liq_base_or_positive_pnl::*;
 pub use perp_liq_force_cancel_orders::*;
 pub use perp_liq_negative_pnl_or_bankruptcy::*;
+pub use perp_log_spread::*;
+pub use perp_order_snapshots::*;
+pub use perp_orderbook_view::*;
 pub use perp_place_order::*;
 pub use perp_settle_fees::*;
 pub use perp_settle_pnl::*;
@@ -52,8 +56,10 @@ pub use token_edit::*;
 pub use token_force_close_borrows_with_token::*;
 pub use token_liq_bankruptcy::*;
 pub use token_liq_with_token::*;
+pub use token_reconcile_vault::*;
 pub use token_register::*;
 pub use token_register_trustless::*;
+pub use token_sweep_fees::*;
 pub use token_update_index_and_rate::*;
 pub use token_withdraw::*;
 
@@ -74,6 +80,7 @@ mod group_edit;
 mod group_withdraw_insurance_fund;
 mod health_region;
 mod ix_gate_set;
+mod perp_book_defragment;
 mod perp_cancel_all_orders;
 mod perp_cancel_all_orders_by_side;
 mod perp_cancel_order;
@@ -87,6 +94,9 @@ mod perp_force_close_position;
 mod perp_liq_base_or_positive_pnl;
 mod perp_liq_force_cancel_orders;
 mod perp_liq_negative_pnl_or_bankruptcy;
+mod perp_log_spread;
+mod perp_order_snapshots;
+mod perp_orderbook_view;
 mod perp_place_order;
 mod perp_settle_fees;
 mod perp_settle_pnl;
@@ -111,7 +121,9 @@ mod token_edit;
 mod token_force_close_borrows_with_token;
 mod token_liq_bankruptcy;
 mod token_liq_with_token;
+mod token_reconcile_vault;
 mod token_register;
 mod token_register_trustless;
+mod token_sweep_fees;
 mod token_update_index_and_rate;
 mod token_withdraw;
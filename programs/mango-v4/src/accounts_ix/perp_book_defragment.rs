@@ -0,0 +1,22 @@
+use crate::error::MangoError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct PerpBookDefragment<'info> {
+    #[account(
+        constraint = group.load()?.is_ix_enabled(IxGate::PerpBookDefragment) @ MangoError::IxIsDisabled,
+    )]
+    pub group: AccountLoader<'info, Group>,
+
+    #[account(
+        has_one = group,
+        has_one = bids,
+        has_one = asks,
+    )]
+    pub perp_market: AccountLoader<'info, PerpMarket>,
+    #[account(mut)]
+    pub bids: AccountLoader<'info, BookSide>,
+    #[account(mut)]
+    pub asks: AccountLoader<'info, BookSide>,
+}
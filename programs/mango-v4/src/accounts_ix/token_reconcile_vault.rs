@@ -0,0 +1,22 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+/// Adjusts a bank's books to match the actual balance of its vault.
+#[derive(Accounts)]
+pub struct TokenReconcileVault<'info> {
+    #[account(
+        has_one = admin,
+    )]
+    pub group: AccountLoader<'info, Group>,
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = group,
+        has_one = vault,
+    )]
+    pub bank: AccountLoader<'info, Bank>,
+
+    pub vault: Account<'info, TokenAccount>,
+}
@@ -0,0 +1,44 @@
+use crate::error::MangoError;
+use crate::state::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+/// Moves a bank's collected fees out of its vault to the group's fees treasury.
+#[derive(Accounts)]
+pub struct TokenSweepFees<'info> {
+    #[account(
+        constraint = group.load()?.is_ix_enabled(IxGate::TokenSweepFees) @ MangoError::IxIsDisabled,
+    )]
+    pub group: AccountLoader<'info, Group>,
+
+    #[account(
+        mut,
+        has_one = group,
+        has_one = vault,
+    )]
+    pub bank: AccountLoader<'info, Bank>,
+
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = treasury.owner == group.load()?.fees_treasury @ MangoError::FeesTreasuryMismatch,
+        constraint = treasury.mint == bank.load()?.mint,
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> TokenSweepFees<'info> {
+    pub fn transfer_ctx(&self) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
+        let program = self.token_program.to_account_info();
+        let accounts = token::Transfer {
+            from: self.vault.to_account_info(),
+            to: self.treasury.to_account_info(),
+            authority: self.group.to_account_info(),
+        };
+        CpiContext::new(program, accounts)
+    }
+}
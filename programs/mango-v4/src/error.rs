@@ -103,6 +103,44 @@ pub enum MangoError {
     InvalidHealthAccountCount,
     #[msg("would self trade")]
     WouldSelfTrade,
+    #[msg("liquidation transfer is too small")]
+    LiquidationTooSmall,
+    #[msg("liquidation asset and liab token must be different")]
+    LiquidationSameToken,
+    #[msg("liqee asset position must be positive")]
+    LiquidationAssetNotPositive,
+    #[msg("liqee liab position must be negative")]
+    LiquidationLiabNotNegative,
+    #[msg("liquidation fee exceeds the group's maximum")]
+    LiquidationFeeTooHigh,
+    #[msg("perp market order placement is halted by the circuit breaker")]
+    PerpCircuitBreakerHalted,
+    #[msg("account equity is below the market's minimum for placing orders")]
+    PerpOrderEquityTooLow,
+    #[msg("liquidation would repay the liqor's existing asset borrow")]
+    LiquidationLiqorAssetNetting,
+    #[msg("oracle account does not match the expected oracle type")]
+    OracleTypeMismatch,
+    #[msg("the perp market has been used and can't change its settle token")]
+    PerpSettleTokenChangeNotAllowed,
+    #[msg("the perp order has not rested on the book for the minimum time yet")]
+    PerpOrderMinRestTime,
+    #[msg("the perp event queue is too full to place orders, events need to be consumed")]
+    PerpEventQueueCongested,
+    #[msg("the perp order price is outside the market's allowed order price range")]
+    PerpOrderPriceOutOfRange,
+    #[msg("liquidation fee is below the group's minimum")]
+    LiquidationFeeTooLow,
+    #[msg("the perp market edit would bring a sample account below maintenance health")]
+    PerpMarketEditUnsafe,
+    #[msg("the vault discrepancy exceeds the bank's max_vault_discrepancy")]
+    TokenVaultDiscrepancyTooLarge,
+    #[msg("reconciling the vault would make the bank's collected fees negative")]
+    TokenVaultReconcileNegativeFees,
+    #[msg("the token account is not owned by the group's fees treasury")]
+    FeesTreasuryMismatch,
+    #[msg("the bank has no collected fees to sweep")]
+    NoFeesToSweep,
 }
 
 impl MangoError {
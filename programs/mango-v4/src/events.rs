@@ -12,12 +12,30 @@ pub struct MangoAccountData {
     pub equity: Equity,
 }
 
+#[event]
+pub struct MangoAccountLiquidationPriority {
+    pub mango_account: Pubkey,
+    pub init_health: I80F48,
+    /// Accounts with a higher score should be looked at first, zero means
+    /// the account has non-negative init health.
+    pub score: I80F48,
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Debug)]
 pub struct Equity {
     pub tokens: Vec<TokenEquity>,
     pub perps: Vec<PerpEquity>,
 }
 
+impl Equity {
+    /// The sum of all token and perp contributions, in native quote
+    pub fn total(&self) -> I80F48 {
+        let tokens = self.tokens.iter().map(|t| t.value);
+        let perps = self.perps.iter().map(|p| p.value);
+        tokens.chain(perps).sum()
+    }
+}
+
 #[derive(AnchorDeserialize, AnchorSerialize, Debug)]
 pub struct TokenEquity {
     pub token_index: TokenIndex,
@@ -27,5 +45,5 @@ pub struct TokenEquity {
 #[derive(AnchorDeserialize, AnchorSerialize, Debug)]
 pub struct PerpEquity {
     pub perp_market_index: PerpMarketIndex,
-    value: I80F48, // in native quote
+    pub value: I80F48, // in native quote
 }
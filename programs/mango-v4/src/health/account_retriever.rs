@@ -4,7 +4,7 @@ use anchor_lang::ZeroCopy;
 use fixed::types::I80F48;
 use serum_dex::state::OpenOrders;
 
-use std::cell::Ref;
+use std::cell::{Ref, RefCell};
 use std::collections::HashMap;
 
 use crate::accounts_zerocopy::*;
@@ -193,11 +193,39 @@ impl<T: KeyedAccountReader> AccountRetriever for FixedOrderAccountRetriever<T> {
     }
 }
 
+/// Oracle account and the bank settings that affect the price read from it
+type OraclePriceCacheKey = (Pubkey, u8, i128, i64);
+
 pub struct ScannedBanksAndOracles<'a, 'info> {
     banks: Vec<AccountInfoRefMut<'a, 'info>>,
     oracles: Vec<AccountInfoRef<'a, 'info>>,
     index_map: HashMap<TokenIndex, usize>,
     staleness_slot: Option<u64>,
+    /// Oracle prices that were already read, so an oracle shared by several banks
+    /// is only parsed once. Lives as long as the retriever, which is per instruction.
+    oracle_prices: RefCell<HashMap<OraclePriceCacheKey, I80F48>>,
+}
+
+/// Like Bank::oracle_price(), but looks up and stores the price in `cache`
+fn cached_bank_oracle_price(
+    cache: &RefCell<HashMap<OraclePriceCacheKey, I80F48>>,
+    bank: &Bank,
+    oracle: &AccountInfoRef,
+    staleness_slot: Option<u64>,
+) -> Result<I80F48> {
+    require_keys_eq!(bank.oracle, *oracle.key());
+    let key = (
+        bank.oracle,
+        bank.mint_decimals,
+        bank.oracle_config.conf_filter.to_bits(),
+        bank.oracle_config.max_staleness_slots,
+    );
+    if let Some(price) = cache.borrow().get(&key) {
+        return Ok(*price);
+    }
+    let price = bank.oracle_price(oracle, staleness_slot)?;
+    cache.borrow_mut().insert(key, price);
+    Ok(price)
 }
 
 impl<'a, 'info> ScannedBanksAndOracles<'a, 'info> {
@@ -222,7 +250,8 @@ impl<'a, 'info> ScannedBanksAndOracles<'a, 'info> {
             let index = self.bank_index(token_index1)?;
             let bank = self.banks[index].load_mut_fully_unchecked::<Bank>()?;
             let oracle = &self.oracles[index];
-            let price = bank.oracle_price(oracle, self.staleness_slot)?;
+            let price =
+                cached_bank_oracle_price(&self.oracle_prices, bank, oracle, self.staleness_slot)?;
             return Ok((bank, price, None));
         }
         let index1 = self.bank_index(token_index1)?;
@@ -240,8 +269,10 @@ impl<'a, 'info> ScannedBanksAndOracles<'a, 'info> {
         let bank2 = second_bank_part[second - (first + 1)].load_mut_fully_unchecked::<Bank>()?;
         let oracle1 = &self.oracles[first];
         let oracle2 = &self.oracles[second];
-        let price1 = bank1.oracle_price(oracle1, self.staleness_slot)?;
-        let price2 = bank2.oracle_price(oracle2, self.staleness_slot)?;
+        let price1 =
+            cached_bank_oracle_price(&self.oracle_prices, bank1, oracle1, self.staleness_slot)?;
+        let price2 =
+            cached_bank_oracle_price(&self.oracle_prices, bank2, oracle2, self.staleness_slot)?;
         if swap {
             Ok((bank2, price2, Some((bank1, price1))))
         } else {
@@ -254,7 +285,8 @@ impl<'a, 'info> ScannedBanksAndOracles<'a, 'info> {
         // The account was already loaded successfully during construction
         let bank = self.banks[index].load_fully_unchecked::<Bank>()?;
         let oracle = &self.oracles[index];
-        let price = bank.oracle_price(oracle, self.staleness_slot)?;
+        let price =
+            cached_bank_oracle_price(&self.oracle_prices, bank, oracle, self.staleness_slot)?;
         Ok((bank, price))
     }
 }
@@ -273,6 +305,8 @@ pub struct ScanningAccountRetriever<'a, 'info> {
     perp_oracles: Vec<AccountInfoRef<'a, 'info>>,
     serum3_oos: Vec<AccountInfoRef<'a, 'info>>,
     perp_index_map: HashMap<PerpMarketIndex, usize>,
+    /// Perp oracle prices that were already read, by perp market index
+    perp_oracle_prices: RefCell<HashMap<PerpMarketIndex, I80F48>>,
 }
 
 /// Returns None if `ai` doesn't have the owner or discriminator for T.
@@ -290,11 +324,31 @@ fn can_load_as<'a, T: ZeroCopy + Owner>(
     Some((i, load_result))
 }
 
+/// Maximum number of health accounts passed to liquidation instructions.
+///
+/// Two accounts with all 16 token, 8 perp and 8 serum3 positions in use need at most
+/// this many distinct banks, oracles, perp markets and open orders accounts. Anything
+/// beyond is unneeded, but would still be scanned.
+pub const MAX_LIQUIDATION_HEALTH_ACCOUNTS: usize = 2 * (16 * 2 + 8 * 2 + 8);
+
 impl<'a, 'info> ScanningAccountRetriever<'a, 'info> {
     pub fn new(ais: &'a [AccountInfo<'info>], group: &Pubkey) -> Result<Self> {
         Self::new_with_staleness(ais, group, Some(Clock::get()?.slot))
     }
 
+    /// Like new(), but bounds the number of accounts to MAX_LIQUIDATION_HEALTH_ACCOUNTS
+    /// to keep the compute use of liquidation instructions in check.
+    pub fn new_for_liquidation(ais: &'a [AccountInfo<'info>], group: &Pubkey) -> Result<Self> {
+        require_msg_typed!(
+            ais.len() <= MAX_LIQUIDATION_HEALTH_ACCOUNTS,
+            MangoError::InvalidHealthAccountCount,
+            "received {} health accounts, but liquidation allows at most {}: only pass the accounts needed for the liqor and liqee positions",
+            ais.len(),
+            MAX_LIQUIDATION_HEALTH_ACCOUNTS
+        );
+        Self::new(ais, group)
+    }
+
     pub fn new_with_staleness(
         ais: &'a [AccountInfo<'info>],
         group: &Pubkey,
@@ -357,11 +411,13 @@ impl<'a, 'info> ScanningAccountRetriever<'a, 'info> {
                 oracles: AccountInfoRef::borrow_slice(&ais[n_banks..perps_start])?,
                 index_map: token_index_map,
                 staleness_slot,
+                oracle_prices: RefCell::new(HashMap::new()),
             },
             perp_markets: AccountInfoRef::borrow_slice(&ais[perps_start..perp_oracles_start])?,
             perp_oracles: AccountInfoRef::borrow_slice(&ais[perp_oracles_start..serum3_start])?,
             serum3_oos: AccountInfoRef::borrow_slice(&ais[serum3_start..])?,
             perp_index_map,
+            perp_oracle_prices: RefCell::new(HashMap::new()),
         })
     }
 
@@ -394,8 +450,14 @@ impl<'a, 'info> ScanningAccountRetriever<'a, 'info> {
         let index = self.perp_market_index(perp_market_index)?;
         // The account was already loaded successfully during construction
         let perp_market = self.perp_markets[index].load_fully_unchecked::<PerpMarket>()?;
+        if let Some(price) = self.perp_oracle_prices.borrow().get(&perp_market_index) {
+            return Ok((perp_market, *price));
+        }
         let oracle_acc = &self.perp_oracles[index];
         let price = perp_market.oracle_price(oracle_acc, self.banks_and_oracles.staleness_slot)?;
+        self.perp_oracle_prices
+            .borrow_mut()
+            .insert(perp_market_index, price);
         Ok((perp_market, price))
     }
 
@@ -444,6 +506,64 @@ mod tests {
     use serum_dex::state::OpenOrders;
     use std::convert::identity;
 
+    #[test]
+    fn test_scanning_account_retriever_liquidation_bound() {
+        let group = Pubkey::new_unique();
+        let (_, mut oracle) = mock_bank_and_oracle(group, 1, 1.0, 0.2, 0.1);
+        let ais = vec![oracle.as_account_info(); MAX_LIQUIDATION_HEALTH_ACCOUNTS + 1];
+
+        let result = ScanningAccountRetriever::new_for_liquidation(&ais, &group);
+        assert!(result.is_anchor_error_with_code(MangoError::InvalidHealthAccountCount.into()));
+    }
+
+    #[test]
+    fn test_scanning_account_retriever_oracle_cache() {
+        let group = Pubkey::new_unique();
+        let (mut bank1, mut oracle) = mock_bank_and_oracle(group, 1, 5.0, 0.2, 0.1);
+        let (mut bank2, _) = mock_bank_and_oracle(group, 2, 1.0, 0.2, 0.1);
+        let (mut bank3, _) = mock_bank_and_oracle(group, 3, 1.0, 0.2, 0.1);
+        bank2.data().oracle = oracle.pubkey;
+        bank3.data().oracle = oracle.pubkey;
+        // decimals change how the price of real oracles is read
+        bank3.data().mint_decimals = 3;
+
+        let oracle_ai = oracle.as_account_info();
+        let ais = vec![
+            bank1.as_account_info(),
+            bank2.as_account_info(),
+            bank3.as_account_info(),
+            oracle_ai.clone(),
+            oracle_ai.clone(),
+            oracle_ai,
+        ];
+
+        let mut retriever =
+            ScanningAccountRetriever::new_with_staleness(&ais, &group, None).unwrap();
+        let cached = |retriever: &ScanningAccountRetriever| {
+            retriever.banks_and_oracles.oracle_prices.borrow().len()
+        };
+        assert_eq!(cached(&retriever), 0);
+
+        // the shared oracle is parsed once for the first two banks
+        let (_, price1) = retriever.scanned_bank_and_oracle(1).unwrap();
+        assert_eq!(cached(&retriever), 1);
+        let (_, price2) = retriever.scanned_bank_and_oracle(2).unwrap();
+        assert_eq!(cached(&retriever), 1);
+        assert_eq!(price1, I80F48::from(5));
+        assert_eq!(price2, price1);
+        {
+            let (_, p1, opt_b2p2) = retriever.banks_mut_and_oracles(1, 2).unwrap();
+            assert_eq!(p1, price1);
+            assert_eq!(opt_b2p2.unwrap().1, price2);
+        }
+        assert_eq!(cached(&retriever), 1);
+
+        // but a bank with different decimals gets its own entry
+        let (_, price3) = retriever.scanned_bank_and_oracle(3).unwrap();
+        assert_eq!(cached(&retriever), 2);
+        assert_eq!(price3, price1);
+    }
+
     #[test]
     fn test_scanning_account_retriever() {
         let oracle1_price = 1.0;
@@ -555,6 +675,14 @@ mod tests {
         assert_eq!(identity(perp.perp_market_index), 8);
         assert_eq!(oracle_price, oracle1_price);
 
+        // perp oracle prices are read once per perp market
+        assert_eq!(retriever.perp_oracle_prices.borrow().len(), 2);
+        let (_, oracle_price) = retriever
+            .perp_market_and_oracle_price(&group, 0, 9)
+            .unwrap();
+        assert_eq!(oracle_price, oracle2_price);
+        assert_eq!(retriever.perp_oracle_prices.borrow().len(), 2);
+
         assert!(retriever
             .perp_market_and_oracle_price(&group, 1, 5)
             .is_err());
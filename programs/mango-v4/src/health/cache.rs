@@ -225,6 +225,25 @@ impl TokenInfo {
     }
 }
 
+/// Change of a token between two health caches, see HealthCache::diff()
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenInfoDiff {
+    pub token_index: TokenIndex,
+    pub balance_spot_change: I80F48,
+    /// Change of the health contribution of the spot balance
+    pub init_health_contribution_change: I80F48,
+    pub maint_health_contribution_change: I80F48,
+}
+
+/// Differences between two health caches, see HealthCache::diff()
+#[derive(Clone, Debug, PartialEq)]
+pub struct HealthCacheDiff {
+    /// Tokens whose balance or health contribution changed
+    pub tokens: Vec<TokenInfoDiff>,
+    pub init_health_change: I80F48,
+    pub maint_health_change: I80F48,
+}
+
 /// Information about reserved funds on Serum3 open orders accounts.
 ///
 /// Note that all "free" funds on open orders accounts are added directly
@@ -553,6 +572,15 @@ impl HealthCache {
         health
     }
 
+    /// How much weighted collateral, in native quote, would need to be added to bring
+    /// the health of `health_type` up to zero. Zero if the health is not negative.
+    ///
+    /// The result is a health amount: depositing a token with an asset weight below one
+    /// requires a correspondingly larger native amount.
+    pub fn collateral_shortfall(&self, health_type: HealthType) -> I80F48 {
+        (-self.health(health_type)).max(I80F48::ZERO)
+    }
+
     pub fn health_assets_and_liabs_stable_assets(
         &self,
         health_type: HealthType,
@@ -702,6 +730,61 @@ impl HealthCache {
             })
     }
 
+    /// Reports how token balances and health changed from `self` to `other`.
+    ///
+    /// Meant for debugging, for example to check that adjust_token_balance() calls moved
+    /// health as expected. Tokens missing from one of the caches count as zero balance.
+    pub fn diff(&self, other: &HealthCache) -> HealthCacheDiff {
+        let token_diff = |before: Option<&TokenInfo>, after: Option<&TokenInfo>| {
+            // one of the two always exists
+            let info = after.or(before).unwrap();
+            let balance = |ti: Option<&TokenInfo>| ti.map_or(I80F48::ZERO, |ti| ti.balance_spot);
+            let contribution = |ti: Option<&TokenInfo>, health_type| {
+                ti.map_or(I80F48::ZERO, |ti| {
+                    ti.health_contribution(health_type, ti.balance_spot)
+                })
+            };
+            TokenInfoDiff {
+                token_index: info.token_index,
+                balance_spot_change: balance(after) - balance(before),
+                init_health_contribution_change: contribution(after, HealthType::Init)
+                    - contribution(before, HealthType::Init),
+                maint_health_contribution_change: contribution(after, HealthType::Maint)
+                    - contribution(before, HealthType::Maint),
+            }
+        };
+
+        let find = |cache: &HealthCache, token_index| {
+            cache
+                .token_infos
+                .iter()
+                .find(|ti| ti.token_index == token_index)
+        };
+        let mut tokens = self
+            .token_infos
+            .iter()
+            .map(|before| token_diff(Some(before), find(other, before.token_index)))
+            .collect::<Vec<_>>();
+        tokens.extend(
+            other
+                .token_infos
+                .iter()
+                .filter(|after| find(self, after.token_index).is_none())
+                .map(|after| token_diff(None, Some(after))),
+        );
+        tokens.retain(|td| {
+            !td.balance_spot_change.is_zero()
+                || !td.init_health_contribution_change.is_zero()
+                || !td.maint_health_contribution_change.is_zero()
+        });
+
+        HealthCacheDiff {
+            tokens,
+            init_health_change: other.health(HealthType::Init) - self.health(HealthType::Init),
+            maint_health_change: other.health(HealthType::Maint) - self.health(HealthType::Maint),
+        }
+    }
+
     /// Changes the cached user account token balance.
     pub fn adjust_token_balance(&mut self, bank: &Bank, change: I80F48) -> Result<()> {
         let entry_index = self.token_info_index(bank.token_index)?;
@@ -1296,6 +1379,156 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_health_cache_diff() {
+        let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+        let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+
+        let group = Pubkey::new_unique();
+
+        let (mut bank1, mut oracle1) = mock_bank_and_oracle(group, 0, 1.0, 0.2, 0.1);
+        let (mut bank2, mut oracle2) = mock_bank_and_oracle(group, 4, 5.0, 0.5, 0.3);
+        bank1
+            .data()
+            .deposit(
+                account.ensure_token_position(0).unwrap().0,
+                I80F48::from(100),
+                DUMMY_NOW_TS,
+            )
+            .unwrap();
+        bank2
+            .data()
+            .withdraw_without_fee(
+                account.ensure_token_position(4).unwrap().0,
+                I80F48::from(10),
+                DUMMY_NOW_TS,
+            )
+            .unwrap();
+
+        let ais = vec![
+            bank1.as_account_info(),
+            bank2.as_account_info(),
+            oracle1.as_account_info(),
+            oracle2.as_account_info(),
+        ];
+        let retriever = ScanningAccountRetriever::new_with_staleness(&ais, &group, None).unwrap();
+        let before = new_health_cache(&account.borrow(), &retriever).unwrap();
+        assert!(before.diff(&before).tokens.is_empty());
+        drop(retriever);
+        drop(ais);
+
+        // use 20 of token 0 to repay 4 of token 4
+        let mut after = before.clone();
+        after
+            .adjust_token_balance(&bank1.data(), I80F48::from(-20))
+            .unwrap();
+        after
+            .adjust_token_balance(&bank2.data(), I80F48::from(4))
+            .unwrap();
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.tokens.len(), 2);
+        assert_eq!(diff.tokens[0].token_index, 0);
+        assert!(health_eq(diff.tokens[0].balance_spot_change, -20.0));
+        assert!(health_eq(
+            diff.tokens[0].init_health_contribution_change,
+            -20.0 * 0.8
+        ));
+        assert!(health_eq(
+            diff.tokens[0].maint_health_contribution_change,
+            -20.0 * 0.9
+        ));
+        assert_eq!(diff.tokens[1].token_index, 4);
+        assert!(health_eq(diff.tokens[1].balance_spot_change, 4.0));
+        assert!(health_eq(
+            diff.tokens[1].init_health_contribution_change,
+            4.0 * 5.0 * 1.5
+        ));
+        assert!(health_eq(
+            diff.tokens[1].maint_health_contribution_change,
+            4.0 * 5.0 * 1.3
+        ));
+        assert!(health_eq(
+            diff.init_health_change,
+            -20.0 * 0.8 + 4.0 * 5.0 * 1.5
+        ));
+        assert!(health_eq(
+            diff.maint_health_change,
+            -20.0 * 0.9 + 4.0 * 5.0 * 1.3
+        ));
+    }
+
+    #[test]
+    fn test_collateral_shortfall() {
+        let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+        let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+
+        let group = Pubkey::new_unique();
+
+        let (mut bank1, mut oracle1) = mock_bank_and_oracle(group, 0, 1.0, 0.2, 0.1);
+        let (mut bank2, mut oracle2) = mock_bank_and_oracle(group, 4, 5.0, 0.5, 0.3);
+        bank1
+            .data()
+            .deposit(
+                account.ensure_token_position(0).unwrap().0,
+                I80F48::from(50),
+                DUMMY_NOW_TS,
+            )
+            .unwrap();
+        bank2
+            .data()
+            .withdraw_without_fee(
+                account.ensure_token_position(4).unwrap().0,
+                I80F48::from(10),
+                DUMMY_NOW_TS,
+            )
+            .unwrap();
+
+        let ais = vec![
+            bank1.as_account_info(),
+            bank2.as_account_info(),
+            oracle1.as_account_info(),
+            oracle2.as_account_info(),
+        ];
+        let retriever = ScanningAccountRetriever::new_with_staleness(&ais, &group, None).unwrap();
+        let health_cache = new_health_cache(&account.borrow(), &retriever).unwrap();
+
+        // init: 50 * 0.8 - 10 * 5 * 1.5 = -35
+        assert!(health_eq(
+            health_cache.collateral_shortfall(HealthType::Init),
+            35.0
+        ));
+        // maint: 50 * 0.9 - 10 * 5 * 1.3 = -20
+        assert!(health_eq(
+            health_cache.collateral_shortfall(HealthType::Maint),
+            20.0
+        ));
+
+        // no shortfall once health is positive
+        drop(retriever);
+        drop(ais);
+        bank1
+            .data()
+            .deposit(
+                account.token_position_mut(0).unwrap().0,
+                I80F48::from(100),
+                DUMMY_NOW_TS,
+            )
+            .unwrap();
+        let ais = vec![
+            bank1.as_account_info(),
+            bank2.as_account_info(),
+            oracle1.as_account_info(),
+            oracle2.as_account_info(),
+        ];
+        let retriever = ScanningAccountRetriever::new_with_staleness(&ais, &group, None).unwrap();
+        let health_cache = new_health_cache(&account.borrow(), &retriever).unwrap();
+        assert_eq!(
+            health_cache.collateral_shortfall(HealthType::Init),
+            I80F48::ZERO
+        );
+    }
+
     #[derive(Default)]
     struct BankSettings {
         deposits: u64,
@@ -10,9 +10,10 @@ pub fn account_edit(
     name_opt: Option<String>,
     // note: can also be used to unset by using the default pubkey here as a param
     delegate_opt: Option<Pubkey>,
+    liqor_max_liab_quote_opt: Option<u64>,
 ) -> Result<()> {
     require!(
-        name_opt.is_some() || delegate_opt.is_some(),
+        name_opt.is_some() || delegate_opt.is_some() || liqor_max_liab_quote_opt.is_some(),
         MangoError::SomeError
     );
 
@@ -34,6 +35,10 @@ pub fn account_edit(
         account.fixed.delegate = delegate;
     }
 
+    if let Some(liqor_max_liab_quote) = liqor_max_liab_quote_opt {
+        account.fixed.liqor_max_liab_quote = liqor_max_liab_quote;
+    }
+
     // unchanged -
     // tokens
     // serum3
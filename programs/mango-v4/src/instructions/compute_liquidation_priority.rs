@@ -0,0 +1,27 @@
+use crate::accounts_ix::*;
+use crate::{events::MangoAccountLiquidationPriority, health::*};
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+/// Logs a score that liquidators can use to order the accounts they look at.
+///
+/// The score is how far the account's init health is below zero.
+pub fn compute_liquidation_priority(ctx: Context<ComputeAccountData>) -> Result<()> {
+    let group_pk = ctx.accounts.group.key();
+
+    let account = ctx.accounts.account.load_full()?;
+
+    let account_retriever = ScanningAccountRetriever::new(ctx.remaining_accounts, &group_pk)?;
+
+    let health_cache = new_health_cache(&account.borrow(), &account_retriever)?;
+    let init_health = health_cache.health(HealthType::Init);
+    let score = (-init_health).max(I80F48::ZERO);
+
+    emit!(MangoAccountLiquidationPriority {
+        mango_account: ctx.accounts.account.key(),
+        init_health,
+        score,
+    });
+
+    Ok(())
+}
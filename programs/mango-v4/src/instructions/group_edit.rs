@@ -18,6 +18,12 @@ pub fn group_edit(
     buyback_fees_swap_mango_account_opt: Option<Pubkey>,
     mngo_token_index_opt: Option<TokenIndex>,
     buyback_fees_expiry_interval_opt: Option<u64>,
+    fees_treasury_opt: Option<Pubkey>,
+    max_liquidation_fee_opt: Option<f32>,
+    batch_token_balance_logs_opt: Option<bool>,
+    liquidation_fee_emergency_mode_opt: Option<bool>,
+    min_liquidation_fee_quote_opt: Option<f32>,
+    min_liquidation_notional_quote_opt: Option<f32>,
 ) -> Result<()> {
     let mut group = ctx.accounts.group.load_mut()?;
 
@@ -106,5 +112,63 @@ pub fn group_edit(
         group.buyback_fees_expiry_interval = buyback_fees_expiry_interval;
     }
 
+    if let Some(fees_treasury) = fees_treasury_opt {
+        msg!(
+            "Fees treasury old {:?}, new {:?}",
+            group.fees_treasury,
+            fees_treasury
+        );
+        group.fees_treasury = fees_treasury;
+    }
+
+    if let Some(max_liquidation_fee) = max_liquidation_fee_opt {
+        require_gte!(max_liquidation_fee, 0.0);
+        msg!(
+            "Max liquidation fee old {:?}, new {:?}",
+            group.max_liquidation_fee,
+            max_liquidation_fee
+        );
+        group.max_liquidation_fee = max_liquidation_fee;
+    }
+
+    if let Some(batch_token_balance_logs) = batch_token_balance_logs_opt {
+        msg!(
+            "Batch token balance logs old {:?}, new {:?}",
+            group.batch_token_balance_logs,
+            batch_token_balance_logs
+        );
+        group.batch_token_balance_logs = u8::from(batch_token_balance_logs);
+    }
+
+    if let Some(liquidation_fee_emergency_mode) = liquidation_fee_emergency_mode_opt {
+        msg!(
+            "Liquidation fee emergency mode old {:?}, new {:?}, max liquidation fee {:?}",
+            group.liquidation_fee_emergency_mode,
+            liquidation_fee_emergency_mode,
+            group.max_liquidation_fee
+        );
+        group.liquidation_fee_emergency_mode = u8::from(liquidation_fee_emergency_mode);
+    }
+
+    if let Some(min_liquidation_fee_quote) = min_liquidation_fee_quote_opt {
+        require_gte!(min_liquidation_fee_quote, 0.0);
+        msg!(
+            "Min liquidation fee quote old {:?}, new {:?}",
+            group.min_liquidation_fee_quote,
+            min_liquidation_fee_quote
+        );
+        group.min_liquidation_fee_quote = min_liquidation_fee_quote;
+    }
+
+    if let Some(min_liquidation_notional_quote) = min_liquidation_notional_quote_opt {
+        require_gte!(min_liquidation_notional_quote, 0.0);
+        msg!(
+            "Min liquidation notional quote old {:?}, new {:?}",
+            group.min_liquidation_notional_quote,
+            min_liquidation_notional_quote
+        );
+        group.min_liquidation_notional_quote = min_liquidation_notional_quote;
+    }
+
     Ok(())
 }
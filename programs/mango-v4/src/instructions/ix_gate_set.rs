@@ -67,6 +67,8 @@ pub fn ix_gate_set(ctx: Context<IxGateSet>, ix_gate: u128) -> Result<()> {
     log_if_changed(&group, ix_gate, IxGate::TokenForceCloseBorrowsWithToken);
     log_if_changed(&group, ix_gate, IxGate::PerpForceClosePosition);
     log_if_changed(&group, ix_gate, IxGate::GroupWithdrawInsuranceFund);
+    log_if_changed(&group, ix_gate, IxGate::PerpBookDefragment);
+    log_if_changed(&group, ix_gate, IxGate::TokenSweepFees);
 
     group.ix_gate = ix_gate;
 
@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+use crate::accounts_ix::*;
+
+/// Compacts the node storage of a perp market's book sides.
+///
+/// Resting orders, their ids and their priority are unchanged. Calling it on an
+/// already compact book does nothing.
+pub fn perp_book_defragment(ctx: Context<PerpBookDefragment>) -> Result<()> {
+    let moved_bids = ctx.accounts.bids.load_mut()?.defragment()?;
+    let moved_asks = ctx.accounts.asks.load_mut()?.defragment()?;
+    msg!(
+        "defragmented book, moved nodes: bids {}, asks {}",
+        moved_bids,
+        moved_asks
+    );
+    Ok(())
+}
@@ -18,7 +18,15 @@ pub fn perp_cancel_all_orders(ctx: Context<PerpCancelAllOrders>, limit: u8) -> R
         asks: ctx.accounts.asks.load_mut()?,
     };
 
-    book.cancel_all_orders(&mut account.borrow_mut(), &mut perp_market, limit, None)?;
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let rest_check_ts = perp_market.order_rest_check_ts(now_ts);
+    book.cancel_all_orders(
+        &mut account.borrow_mut(),
+        &mut perp_market,
+        limit,
+        None,
+        rest_check_ts,
+    )?;
 
     Ok(())
 }
@@ -22,11 +22,14 @@ pub fn perp_cancel_all_orders_by_side(
         asks: ctx.accounts.asks.load_mut()?,
     };
 
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let rest_check_ts = perp_market.order_rest_check_ts(now_ts);
     book.cancel_all_orders(
         &mut account.borrow_mut(),
         &mut perp_market,
         limit,
         side_option,
+        rest_check_ts,
     )?;
 
     Ok(())
@@ -31,11 +31,13 @@ pub fn perp_cancel_order_by_client_order_id(
     let order_id = oo.id;
     let order_side_and_tree = oo.side_and_tree();
 
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
     book.cancel_order(
         &mut account.borrow_mut(),
         order_id,
         order_side_and_tree,
         Some(ctx.accounts.account.key()),
+        perp_market.order_rest_check_ts(now_ts),
     )?;
 
     Ok(())
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use bytemuck::cast_ref;
+use fixed::types::I80F48;
 
 use crate::error::MangoError;
 use crate::state::*;
@@ -9,8 +10,8 @@ use crate::logs::{emit_perp_balances, FillLogV2};
 
 /// Load a mango account by key from the list of account infos.
 ///
-/// Message and return Ok() if it's missing, to lock in successful processing
-/// of previous events.
+/// Message and stop consuming events if it's missing, to lock in successful
+/// processing of previous events and pay the keeper reward for them.
 ///
 /// Special handling for testing groups, where events for accounts with bad
 /// owners (most likely due to force closure of the account) are being skipped.
@@ -23,7 +24,7 @@ macro_rules! load_mango_account {
                     stringify!($name),
                     $key.to_string()
                 );
-                return Ok(());
+                break;
             }
 
             Some(ai) => {
@@ -55,6 +56,7 @@ pub fn perp_consume_events(ctx: Context<PerpConsumeEvents>, limit: usize) -> Res
     let mut event_queue = ctx.accounts.event_queue.load_mut()?;
     let mango_account_ais = &ctx.remaining_accounts;
 
+    let mut events_consumed: u16 = 0;
     for _ in 0..limit {
         let event = match event_queue.peek_front() {
             None => break,
@@ -132,6 +134,71 @@ pub fn perp_consume_events(ctx: Context<PerpConsumeEvents>, limit: usize) -> Res
 
         // consume this event
         event_queue.pop_front()?;
+        events_consumed += 1;
+    }
+
+    // The keeper that receives the reward is passed as two trailing remaining accounts:
+    // its mango account followed by its owner or delegate as a signer. Callers that don't
+    // pass a signer last get no reward.
+    if let [.., keeper_ai, keeper_authority_ai] = &mango_account_ais[..] {
+        if keeper_authority_ai.is_signer {
+            pay_keeper_reward(
+                keeper_ai,
+                keeper_authority_ai.key,
+                &group_key,
+                &mut perp_market,
+                events_consumed,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn pay_keeper_reward(
+    keeper_ai: &AccountInfo,
+    keeper_authority: &Pubkey,
+    group_key: &Pubkey,
+    perp_market: &mut PerpMarket,
+    events_consumed: u16,
+) -> Result<()> {
+    // The reward can't exceed the fees the market has available
+    let reward = (I80F48::from_num(perp_market.consume_events_reward)
+        * I80F48::from(events_consumed))
+    .min(perp_market.fees_accrued);
+    if !reward.is_positive() {
+        return Ok(());
     }
+
+    let keeper_loader: AccountLoader<MangoAccountFixed> = AccountLoader::try_from(keeper_ai)?;
+    let mut keeper = keeper_loader.load_full_mut()?;
+    require_keys_eq!(keeper.fixed.group, *group_key);
+    require!(
+        keeper.fixed.is_owner_or_delegate(*keeper_authority),
+        MangoError::SomeError
+    );
+    require!(keeper.fixed.is_operational(), MangoError::AccountIsFrozen);
+
+    let perp_market_index = perp_market.perp_market_index;
+    let keeper_perp_position =
+        match keeper.ensure_perp_position(perp_market_index, perp_market.settle_token_index) {
+            Ok((keeper_perp_position, _)) => keeper_perp_position,
+            Err(e) => {
+                // Don't fail the consumed events because of the keeper's account
+                msg!("skipping keeper reward, no perp position: {}", e);
+                return Ok(());
+            }
+        };
+    // A reward works like a negative trading fee: it's paid out of fees_accrued
+    keeper_perp_position.record_trading_fee(-reward);
+    perp_market.fees_accrued -= reward;
+    emit_perp_balances(
+        *group_key,
+        keeper_loader.key(),
+        keeper.perp_position(perp_market_index).unwrap(),
+        perp_market,
+    );
+    msg!("keeper reward: {} for {} events", reward, events_consumed);
+
     Ok(())
 }
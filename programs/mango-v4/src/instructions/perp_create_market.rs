@@ -91,7 +91,33 @@ pub fn perp_create_market(
         maint_overall_asset_weight: I80F48::from_num(maint_overall_asset_weight),
         init_overall_asset_weight: I80F48::from_num(init_overall_asset_weight),
         positive_pnl_liquidation_fee: I80F48::from_num(positive_pnl_liquidation_fee),
-        reserved: [0; 1888],
+        consume_events_reward: 0.0,
+        default_order_expiry_seconds: 0,
+        padding5: Default::default(),
+        circuit_breaker_threshold: 0.0,
+        circuit_breaker_window_seconds: 0,
+        circuit_breaker_cooldown_seconds: 0,
+        padding6: Default::default(),
+        circuit_breaker_window_low_price: I80F48::ZERO,
+        circuit_breaker_window_high_price: I80F48::ZERO,
+        circuit_breaker_window_start_ts: 0,
+        circuit_breaker_halted_until: 0,
+        min_equity_to_place_order: 0,
+        maker_rebate_cap_per_open_interest: 0.0,
+        maker_rebate_epoch_seconds: 0,
+        maker_rebate_epoch_start_ts: 0,
+        maker_rebates_paid_in_epoch: 0.0,
+        taker_fee_price_impact_factor: 0.0,
+        min_order_rest_seconds: 0,
+        padding7: Default::default(),
+        event_queue_halt_fraction: 0.0,
+        padding8: Default::default(),
+        min_order_price_lots: 0,
+        max_order_price_lots: 0,
+        fill_price_cumulative: I80F48::ZERO,
+        last_fill_price_lots: 0,
+        last_fill_ts: 0,
+        reserved: [0; 1720],
     };
 
     let oracle_price =
@@ -1,5 +1,6 @@
+use crate::health::*;
 use crate::util::fill_from_str;
-use crate::{accounts_zerocopy::AccountInfoRef, error::MangoError, state::*};
+use crate::{accounts_zerocopy::*, error::*, state::*};
 use anchor_lang::prelude::*;
 use fixed::types::I80F48;
 
@@ -39,8 +40,33 @@ pub fn perp_edit_market(
     positive_pnl_liquidation_fee_opt: Option<f32>,
     name_opt: Option<String>,
     force_close_opt: Option<bool>,
+    consume_events_reward_opt: Option<f32>,
+    default_order_expiry_seconds_opt: Option<u16>,
+    circuit_breaker_threshold_opt: Option<f32>,
+    circuit_breaker_window_seconds_opt: Option<u32>,
+    circuit_breaker_cooldown_seconds_opt: Option<u32>,
+    min_equity_to_place_order_opt: Option<u64>,
+    oracle_type_opt: Option<OracleType>,
+    maker_rebate_cap_per_open_interest_opt: Option<f32>,
+    maker_rebate_epoch_seconds_opt: Option<u32>,
+    taker_fee_price_impact_factor_opt: Option<f32>,
+    settle_token_index_opt: Option<TokenIndex>,
+    min_order_rest_seconds_opt: Option<u16>,
+    event_queue_halt_fraction_opt: Option<f32>,
+    min_order_price_lots_opt: Option<i64>,
+    max_order_price_lots_opt: Option<i64>,
+    check_sample_accounts_health_opt: Option<bool>,
+    reset_funding_opt: Option<bool>,
 ) -> Result<()> {
     let group = ctx.accounts.group.load()?;
+    let group_pk = ctx.accounts.group.key();
+
+    let check_sample_accounts_health = check_sample_accounts_health_opt.unwrap_or(false);
+    let sample_maint_health_before = if check_sample_accounts_health {
+        sample_accounts_maint_health(ctx.remaining_accounts, &group_pk)?
+    } else {
+        vec![]
+    };
 
     let mut perp_market = ctx.accounts.perp_market.load_mut()?;
 
@@ -62,6 +88,25 @@ pub fn perp_edit_market(
         perp_market.oracle = oracle;
         require_group_admin = true;
     }
+    if let Some(oracle_type) = oracle_type_opt {
+        // The oracle type is inferred from the account whenever a price is read. When
+        // switching sources, make sure the oracle account really is of the intended type
+        // and produces a price with the new config before accepting it.
+        require_keys_eq!(perp_market.oracle, ctx.accounts.oracle.key());
+        let oracle_ai = AccountInfoRef::borrow(ctx.accounts.oracle.as_ref())?;
+        let actual_oracle_type = determine_oracle_type(&oracle_ai)?;
+        require_msg_typed!(
+            actual_oracle_type == oracle_type,
+            MangoError::OracleTypeMismatch,
+            "oracle {} is {:?}, expected {:?}",
+            perp_market.oracle,
+            actual_oracle_type,
+            oracle_type
+        );
+        perp_market.oracle_price(&oracle_ai, None)?;
+        msg!("Oracle type: {:?}", oracle_type);
+        require_group_admin = true;
+    }
     if reset_stable_price {
         msg!("Stable price reset");
         require_keys_eq!(perp_market.oracle, ctx.accounts.oracle.key());
@@ -344,6 +389,201 @@ pub fn perp_edit_market(
         require_group_admin = true;
     };
 
+    if let Some(consume_events_reward) = consume_events_reward_opt {
+        require_gte!(consume_events_reward, 0.0);
+        msg!(
+            "Consume events reward: old - {:?}, new - {:?}",
+            perp_market.consume_events_reward,
+            consume_events_reward
+        );
+        perp_market.consume_events_reward = consume_events_reward;
+        require_group_admin = true;
+    };
+
+    if let Some(default_order_expiry_seconds) = default_order_expiry_seconds_opt {
+        msg!(
+            "Default order expiry seconds: old - {:?}, new - {:?}",
+            perp_market.default_order_expiry_seconds,
+            default_order_expiry_seconds
+        );
+        perp_market.default_order_expiry_seconds = default_order_expiry_seconds;
+        require_group_admin = true;
+    };
+
+    if let Some(circuit_breaker_threshold) = circuit_breaker_threshold_opt {
+        require_gte!(circuit_breaker_threshold, 0.0);
+        msg!(
+            "Circuit breaker threshold: old - {:?}, new - {:?}",
+            perp_market.circuit_breaker_threshold,
+            circuit_breaker_threshold
+        );
+        perp_market.circuit_breaker_threshold = circuit_breaker_threshold;
+        perp_market.reset_circuit_breaker_window();
+        require_group_admin = true;
+    };
+
+    if let Some(circuit_breaker_window_seconds) = circuit_breaker_window_seconds_opt {
+        msg!(
+            "Circuit breaker window seconds: old - {:?}, new - {:?}",
+            perp_market.circuit_breaker_window_seconds,
+            circuit_breaker_window_seconds
+        );
+        perp_market.circuit_breaker_window_seconds = circuit_breaker_window_seconds;
+        require_group_admin = true;
+    };
+
+    if let Some(circuit_breaker_cooldown_seconds) = circuit_breaker_cooldown_seconds_opt {
+        msg!(
+            "Circuit breaker cooldown seconds: old - {:?}, new - {:?}",
+            perp_market.circuit_breaker_cooldown_seconds,
+            circuit_breaker_cooldown_seconds
+        );
+        perp_market.circuit_breaker_cooldown_seconds = circuit_breaker_cooldown_seconds;
+        require_group_admin = true;
+    };
+
+    if let Some(min_equity_to_place_order) = min_equity_to_place_order_opt {
+        msg!(
+            "Min equity to place order: old - {:?}, new - {:?}",
+            perp_market.min_equity_to_place_order,
+            min_equity_to_place_order
+        );
+        perp_market.min_equity_to_place_order = min_equity_to_place_order;
+        require_group_admin = true;
+    };
+    if let Some(maker_rebate_cap_per_open_interest) = maker_rebate_cap_per_open_interest_opt {
+        require_gte!(maker_rebate_cap_per_open_interest, 0.0);
+        msg!(
+            "Maker rebate cap per open interest: old - {:?}, new - {:?}",
+            perp_market.maker_rebate_cap_per_open_interest,
+            maker_rebate_cap_per_open_interest
+        );
+        perp_market.maker_rebate_cap_per_open_interest = maker_rebate_cap_per_open_interest;
+        require_group_admin = true;
+    };
+    if let Some(maker_rebate_epoch_seconds) = maker_rebate_epoch_seconds_opt {
+        msg!(
+            "Maker rebate epoch seconds: old - {:?}, new - {:?}",
+            perp_market.maker_rebate_epoch_seconds,
+            maker_rebate_epoch_seconds
+        );
+        perp_market.maker_rebate_epoch_seconds = maker_rebate_epoch_seconds;
+        perp_market.maker_rebate_epoch_start_ts = 0;
+        perp_market.maker_rebates_paid_in_epoch = 0.0;
+        require_group_admin = true;
+    };
+    if let Some(taker_fee_price_impact_factor) = taker_fee_price_impact_factor_opt {
+        require_gte!(taker_fee_price_impact_factor, 0.0);
+        msg!(
+            "Taker fee price impact factor: old - {:?}, new - {:?}",
+            perp_market.taker_fee_price_impact_factor,
+            taker_fee_price_impact_factor
+        );
+        perp_market.taker_fee_price_impact_factor = taker_fee_price_impact_factor;
+        require_group_admin = true;
+    };
+    if let Some(settle_token_index) = settle_token_index_opt {
+        // Perp positions keep the settle token position of their account in use, and
+        // settlement of existing pnl would switch tokens. Only allow the change while
+        // no order was ever placed, which means no account can have a perp position.
+        require_msg_typed!(
+            perp_market.seq_num == 0,
+            MangoError::PerpSettleTokenChangeNotAllowed,
+            "perp market {} has seq_num {}",
+            perp_market.perp_market_index,
+            perp_market.seq_num
+        );
+        // The settle token's first bank must be passed as a remaining account, to
+        // show that the token exists in the group
+        let settle_bank_passed = ctx.remaining_accounts.iter().any(|ai| {
+            ai.load::<Bank>().map_or(false, |bank| {
+                bank.group == group_pk
+                    && bank.token_index == settle_token_index
+                    && bank.bank_num == 0
+            })
+        });
+        require_msg_typed!(
+            settle_bank_passed,
+            MangoError::InvalidBank,
+            "no bank for settle token index {} was passed",
+            settle_token_index
+        );
+        msg!(
+            "Settle token index: old - {:?}, new - {:?}",
+            perp_market.settle_token_index,
+            settle_token_index
+        );
+        perp_market.settle_token_index = settle_token_index;
+        require_group_admin = true;
+    };
+    if let Some(min_order_rest_seconds) = min_order_rest_seconds_opt {
+        msg!(
+            "Min order rest seconds: old - {:?}, new - {:?}",
+            perp_market.min_order_rest_seconds,
+            min_order_rest_seconds
+        );
+        perp_market.min_order_rest_seconds = min_order_rest_seconds;
+        require_group_admin = true;
+    };
+    if let Some(event_queue_halt_fraction) = event_queue_halt_fraction_opt {
+        msg!(
+            "Event queue halt fraction: old - {:?}, new - {:?}",
+            perp_market.event_queue_halt_fraction,
+            event_queue_halt_fraction
+        );
+        require_gte!(event_queue_halt_fraction, 0.0);
+        require_gte!(1.0, event_queue_halt_fraction);
+        perp_market.event_queue_halt_fraction = event_queue_halt_fraction;
+        require_group_admin = true;
+    };
+    if let Some(min_order_price_lots) = min_order_price_lots_opt {
+        msg!(
+            "Min order price lots: old - {:?}, new - {:?}",
+            perp_market.min_order_price_lots,
+            min_order_price_lots
+        );
+        require_gte!(min_order_price_lots, 0);
+        perp_market.min_order_price_lots = min_order_price_lots;
+        require_group_admin = true;
+    };
+    if let Some(max_order_price_lots) = max_order_price_lots_opt {
+        msg!(
+            "Max order price lots: old - {:?}, new - {:?}",
+            perp_market.max_order_price_lots,
+            max_order_price_lots
+        );
+        require_gte!(max_order_price_lots, 0);
+        perp_market.max_order_price_lots = max_order_price_lots;
+        require_group_admin = true;
+    };
+    if perp_market.min_order_price_lots > 0 && perp_market.max_order_price_lots > 0 {
+        require_gte!(
+            perp_market.max_order_price_lots,
+            perp_market.min_order_price_lots
+        );
+    }
+    if reset_funding_opt == Some(true) {
+        let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+        msg!(
+            "Funding reset: old - long {:?}, short {:?}, last updated {:?}, new - last updated {:?}",
+            perp_market.long_funding,
+            perp_market.short_funding,
+            perp_market.funding_last_updated,
+            now_ts
+        );
+        // Perp positions track funding relative to these accumulators, so they can
+        // only be zeroed while there are no open positions.
+        if perp_market.open_interest == 0 {
+            perp_market.long_funding = I80F48::ZERO;
+            perp_market.short_funding = I80F48::ZERO;
+        } else {
+            msg!("Funding accumulators kept: market has open interest");
+        }
+        // The next funding update only applies funding for the time after the reset
+        perp_market.funding_last_updated = now_ts;
+        require_group_admin = true;
+    }
+
     // account constraint #1
     if require_group_admin {
         require!(
@@ -368,5 +608,60 @@ pub fn perp_edit_market(
         oracle: perp_market.oracle.key(),
     });
 
+    if check_sample_accounts_health {
+        // The perp market is read again through the health accounts
+        drop(perp_market);
+
+        let sample_maint_health_after =
+            sample_accounts_maint_health(ctx.remaining_accounts, &group_pk)?;
+        for ((account_pk, before), (_, after)) in sample_maint_health_before
+            .iter()
+            .zip(sample_maint_health_after.iter())
+        {
+            msg!(
+                "Sample account {}: maint health before {}, after {}",
+                account_pk,
+                before,
+                after
+            );
+            // Accounts that are already liquidatable don't block the edit
+            require_msg_typed!(
+                before.is_negative() || !after.is_negative(),
+                MangoError::PerpMarketEditUnsafe,
+                "account {} would go from maint health {} to {}",
+                account_pk,
+                before,
+                after
+            );
+        }
+    }
+
     Ok(())
 }
+
+/// Computes the maint health of the mango accounts at the start of `ais`.
+///
+/// The accounts following them are the health accounts, like for a
+/// ScanningAccountRetriever, and must cover all the sample accounts' positions.
+fn sample_accounts_maint_health(
+    ais: &[AccountInfo],
+    group_pk: &Pubkey,
+) -> Result<Vec<(Pubkey, I80F48)>> {
+    let num_accounts = ais
+        .iter()
+        .take_while(|ai| AccountLoader::<MangoAccountFixed>::try_from(ai).is_ok())
+        .count();
+    let (account_ais, health_ais) = ais.split_at(num_accounts);
+
+    let retriever = ScanningAccountRetriever::new(health_ais, group_pk)?;
+    account_ais
+        .iter()
+        .map(|ai| {
+            let loader = AccountLoader::<MangoAccountFixed>::try_from(ai)?;
+            let account = loader.load_full()?;
+            require_keys_eq!(account.fixed.group, *group_pk);
+            let health_cache = new_health_cache(&account.borrow(), &retriever)?;
+            Ok((*ai.key, health_cache.health(HealthType::Maint)))
+        })
+        .collect()
+}
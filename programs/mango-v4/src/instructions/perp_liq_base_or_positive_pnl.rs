@@ -49,8 +49,9 @@ pub fn perp_liq_base_or_positive_pnl(
 
     // Initial liqee health check
     let mut liqee_health_cache = {
-        let account_retriever = ScanningAccountRetriever::new(ctx.remaining_accounts, group_pk)
-            .context("create account retriever")?;
+        let account_retriever =
+            ScanningAccountRetriever::new_for_liquidation(ctx.remaining_accounts, group_pk)
+                .context("create account retriever")?;
         new_health_cache(&liqee.borrow(), &account_retriever)
             .context("create liqee health cache")?
     };
@@ -181,8 +182,9 @@ pub fn perp_liq_base_or_positive_pnl(
 
     // Check liqor's health
     if !liqor.fixed.is_in_health_region() {
-        let account_retriever = ScanningAccountRetriever::new(ctx.remaining_accounts, group_pk)
-            .context("create account retriever end")?;
+        let account_retriever =
+            ScanningAccountRetriever::new_for_liquidation(ctx.remaining_accounts, group_pk)
+                .context("create account retriever end")?;
         let liqor_health = compute_health(&liqor.borrow(), HealthType::Init, &account_retriever)
             .context("compute liqor health")?;
         require!(liqor_health >= 0, MangoError::HealthMustBePositive);
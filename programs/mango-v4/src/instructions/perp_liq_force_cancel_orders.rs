@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 use crate::accounts_ix::*;
 use crate::error::*;
 use crate::health::*;
+use crate::logs::PerpForceCancelOrdersLog;
 use crate::state::*;
 
 pub fn perp_liq_force_cancel_orders(
@@ -20,11 +21,13 @@ pub fn perp_liq_force_cancel_orders(
     let mut perp_market = ctx.accounts.perp_market.load_mut()?;
 
     //
-    // Early return if if liquidation is not allowed or if market is not in force close
+    // Early return if if liquidation is not allowed or if market is not in reduce only
+    // or force close: resting orders on such markets can be cleaned up by anyone
     //
     let liquidatable = account.check_liquidatable(&health_cache)?;
     if account.fixed.is_operational()
         && liquidatable != CheckLiquidatable::Liquidatable
+        && !perp_market.is_reduce_only()
         && !perp_market.is_force_close()
     {
         return Ok(());
@@ -39,7 +42,31 @@ pub fn perp_liq_force_cancel_orders(
             asks: ctx.accounts.asks.load_mut()?,
         };
 
-        book.cancel_all_orders(&mut account.borrow_mut(), &mut perp_market, limit, None)?;
+        let perp_market_index = perp_market.perp_market_index;
+        let active_orders = |account: &MangoAccountRef| {
+            account
+                .all_perp_orders()
+                .filter(|oo| oo.is_active_for_market(perp_market_index))
+                .count()
+        };
+        let orders_before = active_orders(&account.borrow());
+        // liquidation may always cancel, regardless of order rest times
+        book.cancel_all_orders(
+            &mut account.borrow_mut(),
+            &mut perp_market,
+            limit,
+            None,
+            None,
+        )?;
+        let orders_cancelled = orders_before - active_orders(&account.borrow());
+        if orders_cancelled > 0 {
+            emit!(PerpForceCancelOrdersLog {
+                mango_group: ctx.accounts.group.key(),
+                mango_account: ctx.accounts.account.key(),
+                perp_market_index,
+                orders_cancelled: orders_cancelled as u8,
+            });
+        }
 
         let perp_position = account.perp_position(perp_market.perp_market_index)?;
         health_cache.recompute_perp_info(perp_position, &perp_market)?;
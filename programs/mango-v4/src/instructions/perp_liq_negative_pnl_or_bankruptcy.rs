@@ -69,8 +69,9 @@ pub fn perp_liq_negative_pnl_or_bankruptcy(
         "liqor account"
     );
 
-    let retriever = ScanningAccountRetriever::new(ctx.remaining_accounts, &mango_group)
-        .context("create account retriever")?;
+    let retriever =
+        ScanningAccountRetriever::new_for_liquidation(ctx.remaining_accounts, &mango_group)
+            .context("create account retriever")?;
     let mut liqee_health_cache = new_health_cache(&liqee.borrow(), &retriever)?;
     drop(retriever);
     let liqee_liq_end_health = liqee_health_cache.health(HealthType::LiquidationEnd);
@@ -196,7 +197,7 @@ pub fn perp_liq_negative_pnl_or_bankruptcy(
     // Check liqor's health
     if !liqor.fixed.is_in_health_region() {
         let account_retriever =
-            ScanningAccountRetriever::new(ctx.remaining_accounts, &mango_group)?;
+            ScanningAccountRetriever::new_for_liquidation(ctx.remaining_accounts, &mango_group)?;
         let liqor_health = compute_health(&liqor.borrow(), HealthType::Init, &account_retriever)
             .context("compute liqor health")?;
         require!(liqor_health >= 0, MangoError::HealthMustBePositive);
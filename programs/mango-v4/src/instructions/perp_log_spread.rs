@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+use crate::accounts_ix::*;
+use crate::accounts_zerocopy::*;
+use crate::logs::PerpSpreadLog;
+
+/// Logs the best bid, best ask, mid and spread of a perp market.
+///
+/// Both book sides and the oracle are read in the same instruction, so oracle
+/// pegged orders on both sides resolve against the same oracle price.
+pub fn perp_log_spread(ctx: Context<PerpLogSpread>) -> Result<()> {
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let now_slot = Clock::get()?.slot;
+
+    let perp_market = ctx.accounts.perp_market.load()?;
+    let bids = ctx.accounts.bids.load()?;
+    let asks = ctx.accounts.asks.load()?;
+
+    let oracle_price = perp_market.oracle_price(
+        &AccountInfoRef::borrow(ctx.accounts.oracle.as_ref())?,
+        Some(now_slot),
+    )?;
+    let oracle_price_lots = perp_market.native_price_to_lot(oracle_price);
+
+    let best_bid_lots = bids.best_price(now_ts, oracle_price_lots);
+    let best_ask_lots = asks.best_price(now_ts, oracle_price_lots);
+    let (mid_price, spread_lots) = match (best_bid_lots, best_ask_lots) {
+        (Some(bid), Some(ask)) => {
+            let mid_lots = (I80F48::from(bid) + I80F48::from(ask)) / 2;
+            let mid_price = mid_lots * perp_market.lot_to_native_price(1);
+            (Some(mid_price.to_bits()), Some(ask - bid))
+        }
+        _ => (None, None),
+    };
+
+    emit!(PerpSpreadLog {
+        mango_group: ctx.accounts.group.key(),
+        perp_market_index: perp_market.perp_market_index,
+        oracle_price: oracle_price.to_bits(),
+        best_bid_lots,
+        best_ask_lots,
+        mid_price,
+        spread_lots,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::accounts_ix::*;
+use crate::accounts_zerocopy::*;
+use crate::state::{OrderSnapshot, Side, ORDER_SNAPSHOTS_PER_PAGE};
+
+/// Returns up to ORDER_SNAPSHOTS_PER_PAGE orders of one side of a perp market's book,
+/// starting at `start_index` in matching priority order.
+///
+/// Invalid orders are included, so the same book, oracle price and time always give
+/// the same pages. Clients page through a side until a call returns fewer orders than
+/// a full page.
+pub fn perp_order_snapshots(
+    ctx: Context<PerpOrderSnapshots>,
+    side: Side,
+    start_index: u32,
+) -> Result<Vec<OrderSnapshot>> {
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let now_slot = Clock::get()?.slot;
+
+    let perp_market = ctx.accounts.perp_market.load()?;
+    let book_side = match side {
+        Side::Bid => ctx.accounts.bids.load()?,
+        Side::Ask => ctx.accounts.asks.load()?,
+    };
+
+    let oracle_price = perp_market.oracle_price(
+        &AccountInfoRef::borrow(ctx.accounts.oracle.as_ref())?,
+        Some(now_slot),
+    )?;
+    let oracle_price_lots = perp_market.native_price_to_lot(oracle_price);
+
+    Ok(book_side
+        .iter_order_snapshots(now_ts, oracle_price_lots)
+        .skip(start_index as usize)
+        .take(ORDER_SNAPSHOTS_PER_PAGE)
+        .collect())
+}
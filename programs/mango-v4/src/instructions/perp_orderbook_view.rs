@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::accounts_ix::*;
+use crate::accounts_zerocopy::*;
+use crate::logs::PerpOrderbookViewLog;
+use crate::state::BookSide;
+
+/// Logs the top of the book and the impact prices of a perp market.
+///
+/// The impact prices are the average fill prices for the market's impact_quantity
+/// on each side. Invalid orders, like ones with an expired time in force, are skipped.
+/// Values that can't be computed because a side is too thin are logged as None.
+pub fn perp_orderbook_view(ctx: Context<PerpOrderbookView>) -> Result<()> {
+    let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let now_slot = Clock::get()?.slot;
+
+    let perp_market = ctx.accounts.perp_market.load()?;
+    let bids = ctx.accounts.bids.load()?;
+    let asks = ctx.accounts.asks.load()?;
+
+    let oracle_price = perp_market.oracle_price(
+        &AccountInfoRef::borrow(ctx.accounts.oracle.as_ref())?,
+        Some(now_slot),
+    )?;
+    let oracle_price_lots = perp_market.native_price_to_lot(oracle_price);
+
+    let impact_quantity = perp_market.impact_quantity;
+    let impact_price = |side: &BookSide| {
+        side.impact_average_price(impact_quantity, now_ts, oracle_price_lots)
+            .map(|price| price.to_bits())
+    };
+
+    emit!(PerpOrderbookViewLog {
+        mango_group: ctx.accounts.group.key(),
+        perp_market_index: perp_market.perp_market_index,
+        oracle_price: oracle_price.to_bits(),
+        best_bid_lots: bids.best_price(now_ts, oracle_price_lots),
+        best_ask_lots: asks.best_price(now_ts, oracle_price_lots),
+        impact_quantity,
+        bid_impact_price_lots: impact_price(&bids),
+        ask_impact_price_lots: impact_price(&asks),
+    });
+
+    Ok(())
+}
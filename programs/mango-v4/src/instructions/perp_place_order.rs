@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use fixed::types::I80F48;
 
 use crate::accounts_ix::*;
 use crate::accounts_zerocopy::*;
@@ -16,6 +17,13 @@ pub fn perp_place_order(
     require_gte!(order.max_base_lots, 0);
     require_gte!(order.max_quote_lots, 0);
 
+    let mut account = ctx.accounts.account.load_full_mut()?;
+    // account constraint #1
+    require!(
+        account.fixed.is_owner_or_delegate(ctx.accounts.owner.key()),
+        MangoError::SomeError
+    );
+
     let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
     let oracle_price;
 
@@ -37,22 +45,27 @@ pub fn perp_place_order(
         )?;
 
         perp_market.update_funding_and_stable_price(&book, oracle_price, oracle_state, now_ts)?;
-    }
 
-    let mut account = ctx.accounts.account.load_full_mut()?;
-    // account constraint #1
-    require!(
-        account.fixed.is_owner_or_delegate(ctx.accounts.owner.key()),
-        MangoError::SomeError
-    );
+        // When this call trips the breaker, return without an error so the halt
+        // is persisted. An error would revert the tripped state. The order is not placed.
+        if perp_market.update_circuit_breaker(oracle_price, now_ts) {
+            msg!("circuit breaker tripped, order not placed");
+            return Ok(None);
+        }
+        require!(
+            !perp_market.is_circuit_breaker_halted(now_ts),
+            MangoError::PerpCircuitBreakerHalted
+        );
+    }
 
     let account_pk = ctx.accounts.account.key();
 
-    let (perp_market_index, settle_token_index) = {
+    let (perp_market_index, settle_token_index, min_equity_to_place_order) = {
         let perp_market = ctx.accounts.perp_market.load()?;
         (
             perp_market.perp_market_index,
             perp_market.settle_token_index,
+            perp_market.min_equity_to_place_order,
         )
     };
 
@@ -75,6 +88,20 @@ pub fn perp_place_order(
         None
     };
 
+    // Unlike the health check, this also applies inside health regions
+    if min_equity_to_place_order > 0 {
+        let retriever =
+            new_fixed_order_account_retriever(ctx.remaining_accounts, &account.borrow())?;
+        let equity = compute_equity(&account.borrow(), &retriever)?.total();
+        require_msg_typed!(
+            equity >= I80F48::from(min_equity_to_place_order),
+            MangoError::PerpOrderEquityTooLow,
+            "equity {} is below the minimum {}",
+            equity,
+            min_equity_to_place_order
+        );
+    }
+
     let mut perp_market = ctx.accounts.perp_market.load_mut()?;
     let mut book = Orderbook {
         bids: ctx.accounts.bids.load_mut()?,
@@ -82,6 +109,13 @@ pub fn perp_place_order(
     };
 
     let mut event_queue = ctx.accounts.event_queue.load_mut()?;
+    require_msg_typed!(
+        !perp_market.is_event_queue_congested(event_queue.len()),
+        MangoError::PerpEventQueueCongested,
+        "event queue holds {} of {} events",
+        event_queue.len(),
+        MAX_NUM_EVENTS
+    );
     let group = ctx.accounts.group.load()?;
 
     let now_ts: u64 = Clock::get()?.unix_timestamp.try_into().unwrap();
@@ -104,6 +138,11 @@ pub fn perp_place_order(
     };
     order.max_base_lots = max_base_lots;
 
+    // Orders without an expiry timestamp get the market's default time in force
+    if order.time_in_force == 0 {
+        order.time_in_force = perp_market.default_order_expiry_seconds;
+    }
+
     let order_id_opt = book.new_order(
         order,
         &mut perp_market,
@@ -20,6 +20,7 @@ pub fn perp_update_funding(ctx: Context<PerpUpdateFunding>) -> Result<()> {
     )?;
 
     perp_market.update_funding_and_stable_price(&book, oracle_price, oracle_state, now_ts)?;
+    perp_market.update_circuit_breaker(oracle_price, now_ts);
 
     Ok(())
 }
@@ -4,7 +4,7 @@ use fixed::types::I80F48;
 
 use crate::accounts_zerocopy::{AccountInfoRef, LoadMutZeroCopyRef};
 
-use crate::error::MangoError;
+use crate::error::*;
 use crate::state::*;
 
 use crate::accounts_ix::*;
@@ -39,6 +39,9 @@ pub fn token_edit(
     reduce_only_opt: Option<u8>,
     name_opt: Option<String>,
     force_close_opt: Option<bool>,
+    liquidation_fee_mode_opt: Option<u8>,
+    flat_liquidation_fee_quote_opt: Option<f32>,
+    max_vault_discrepancy_opt: Option<u64>,
 ) -> Result<()> {
     let group = ctx.accounts.group.load()?;
 
@@ -180,6 +183,20 @@ pub fn token_edit(
             require_group_admin = true;
         }
         if let Some(liquidation_fee) = liquidation_fee_opt {
+            if let Some(max_liquidation_fee) = group.max_liquidation_fee() {
+                require_msg_typed!(
+                    I80F48::from_num(liquidation_fee) <= max_liquidation_fee,
+                    MangoError::LiquidationFeeTooHigh,
+                    "liquidation fee {} exceeds the group maximum {}",
+                    liquidation_fee,
+                    max_liquidation_fee
+                );
+            } else if group.liquidation_fee_emergency_mode() {
+                msg!(
+                    "Liquidation fee emergency mode, max liquidation fee {:?} not enforced",
+                    group.max_liquidation_fee
+                );
+            }
             msg!(
                 "Liquidation fee: old - {:?}, new - {:?}",
                 bank.liquidation_fee,
@@ -304,6 +321,54 @@ pub fn token_edit(
             bank.force_close = u8::from(force_close);
             require_group_admin = true;
         };
+
+        if let Some(liquidation_fee_mode) = liquidation_fee_mode_opt {
+            require_gte!(1, liquidation_fee_mode);
+            msg!(
+                "Liquidation fee mode: old - {:?}, new - {:?}",
+                bank.liquidation_fee_mode,
+                liquidation_fee_mode
+            );
+            bank.liquidation_fee_mode = liquidation_fee_mode;
+            require_group_admin = true;
+        };
+
+        if let Some(flat_liquidation_fee_quote) = flat_liquidation_fee_quote_opt {
+            require_gte!(flat_liquidation_fee_quote, 0.0);
+            msg!(
+                "Flat liquidation fee quote: old - {:?}, new - {:?}",
+                bank.flat_liquidation_fee_quote,
+                flat_liquidation_fee_quote
+            );
+            bank.flat_liquidation_fee_quote = flat_liquidation_fee_quote;
+            require_group_admin = true;
+        };
+
+        if let Some(max_vault_discrepancy) = max_vault_discrepancy_opt {
+            msg!(
+                "Max vault discrepancy: old - {:?}, new - {:?}",
+                bank.max_vault_discrepancy,
+                max_vault_discrepancy
+            );
+            bank.max_vault_discrepancy = max_vault_discrepancy;
+            require_group_admin = true;
+        };
+
+        if liquidation_fee_opt.is_some()
+            || liquidation_fee_mode_opt.is_some()
+            || flat_liquidation_fee_quote_opt.is_some()
+        {
+            require_msg_typed!(
+                !group.is_liquidation_fee_below_floor(&bank),
+                MangoError::LiquidationFeeTooLow,
+                "liquidation fee {} (mode {}, flat {}) pays less than {} on a liquidation of {}",
+                bank.liquidation_fee,
+                bank.liquidation_fee_mode,
+                bank.flat_liquidation_fee_quote,
+                group.min_liquidation_fee_quote,
+                group.min_liquidation_notional_quote
+            );
+        }
     }
 
     // account constraint #1
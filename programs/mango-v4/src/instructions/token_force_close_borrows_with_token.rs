@@ -15,6 +15,7 @@ pub fn token_force_close_borrows_with_token(
     max_liab_transfer: u64,
 ) -> Result<()> {
     let group_pk = &ctx.accounts.group.key();
+    let max_liquidation_fee = ctx.accounts.group.load()?.max_liquidation_fee();
 
     require_neq!(asset_token_index, liab_token_index, MangoError::SomeError);
 
@@ -56,12 +57,21 @@ pub fn token_force_close_borrows_with_token(
         // account constraint #2
         require!(liab_bank.is_force_close(), MangoError::TokenInForceClose);
 
+        // The fee is clamped to the group maximum like in token_liq_with_token. Banks in the
+        // flat quote fee mode only charge their fee in token_liq_with_token, force closing
+        // their borrows pays no fee.
+        let liquidation_fee = if liab_bank.is_flat_quote_liquidation_fee() {
+            I80F48::ZERO
+        } else {
+            liab_bank.capped_liquidation_fee(max_liquidation_fee)
+        };
+
         // account constraint #3
         // only allow combination of asset and liab token,
         // where liqee's health would be guaranteed to not decrease
         require_gte!(
             liab_bank.init_liab_weight,
-            asset_bank.init_liab_weight * (I80F48::ONE + liab_bank.liquidation_fee),
+            asset_bank.init_liab_weight * (I80F48::ONE + liquidation_fee),
             MangoError::SomeError
         );
 
@@ -89,7 +99,7 @@ pub fn token_force_close_borrows_with_token(
             .max(I80F48::ZERO);
 
         // The amount of asset native tokens we will give up for them
-        let fee_factor = I80F48::ONE + liab_bank.liquidation_fee;
+        let fee_factor = I80F48::ONE + liquidation_fee;
         let liab_oracle_price_adjusted = liab_oracle_price * fee_factor;
         let asset_transfer = liab_transfer * liab_oracle_price_adjusted / asset_oracle_price;
 
@@ -42,7 +42,8 @@ pub fn token_liq_bankruptcy(
         "liqor account"
     );
 
-    let mut account_retriever = ScanningAccountRetriever::new(health_ais, group_pk)?;
+    let mut account_retriever =
+        ScanningAccountRetriever::new_for_liquidation(health_ais, group_pk)?;
 
     let mut liqee = ctx.accounts.liqee.load_full_mut()?;
     let mut liqee_health_cache = new_health_cache(&liqee.borrow(), &account_retriever)
@@ -75,9 +76,18 @@ pub fn token_liq_bankruptcy(
 
     // We pay for the liab token in quote. Example: SOL is at $20 and USDC is at $2, then for a liab
     // of 3 SOL, we'd pay 3 * 20 / 2 * (1+fee) = 30 * (1+fee) USDC.
+    //
+    // The fee is clamped to the group maximum like in token_liq_with_token. Banks in the
+    // flat quote fee mode charge their fee once per liquidation in token_liq_with_token,
+    // so bankruptcy pays them no fee.
+    let liquidation_fee = if liab_bank.is_flat_quote_liquidation_fee() {
+        I80F48::ZERO
+    } else {
+        liab_bank.capped_liquidation_fee(group.max_liquidation_fee())
+    };
     let liab_to_quote_with_fee =
         if let Some((_quote_bank, quote_price)) = opt_quote_bank_and_price.as_ref() {
-            liab_oracle_price * (I80F48::ONE + liab_bank.liquidation_fee) / quote_price
+            liab_oracle_price * (I80F48::ONE + liquidation_fee) / quote_price
         } else {
             I80F48::ONE
         };
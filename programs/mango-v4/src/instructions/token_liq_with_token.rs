@@ -5,7 +5,7 @@ use crate::accounts_ix::*;
 use crate::error::*;
 use crate::health::*;
 use crate::logs::{
-    LoanOriginationFeeInstruction, TokenBalanceLog, TokenLiqWithTokenLog,
+    emit_token_balances, LoanOriginationFeeInstruction, TokenBalanceDetail, TokenLiqWithTokenLog,
     WithdrawLoanOriginationFeeLog,
 };
 use crate::state::*;
@@ -15,12 +15,14 @@ pub fn token_liq_with_token(
     asset_token_index: TokenIndex,
     liab_token_index: TokenIndex,
     max_liab_transfer: I80F48,
+    reject_liqor_asset_netting: bool,
+    dust_liqor_asset: bool,
 ) -> Result<()> {
     let group_pk = &ctx.accounts.group.key();
 
-    require!(asset_token_index != liab_token_index, MangoError::SomeError);
-    let mut account_retriever = ScanningAccountRetriever::new(ctx.remaining_accounts, group_pk)
-        .context("create account retriever")?;
+    let mut account_retriever =
+        ScanningAccountRetriever::new_for_liquidation(ctx.remaining_accounts, group_pk)
+            .context("create account retriever")?;
 
     require_keys_neq!(ctx.accounts.liqor.key(), ctx.accounts.liqee.key());
     let mut liqor = ctx.accounts.liqor.load_full_mut()?;
@@ -54,6 +56,19 @@ pub fn token_liq_with_token(
     // transfer some asset_token from liqee to liqor.
     //
     let now_ts = Clock::get()?.unix_timestamp.try_into().unwrap();
+    let (max_liquidation_fee, batch_token_balance_logs) = {
+        let group = ctx.accounts.group.load()?;
+        if group.liquidation_fee_emergency_mode() {
+            msg!(
+                "liquidation fee emergency mode, max liquidation fee {} not enforced",
+                group.max_liquidation_fee
+            );
+        }
+        (
+            group.max_liquidation_fee(),
+            group.batch_token_balance_logs(),
+        )
+    };
     liquidation_action(
         &mut account_retriever,
         liab_token_index,
@@ -66,6 +81,10 @@ pub fn token_liq_with_token(
         liqee_liq_end_health,
         now_ts,
         max_liab_transfer,
+        max_liquidation_fee,
+        reject_liqor_asset_netting,
+        dust_liqor_asset,
+        batch_token_balance_logs,
     )?;
 
     // Check liqor's health
@@ -90,9 +109,20 @@ pub(crate) fn liquidation_action(
     liqee_liq_end_health: I80F48,
     now_ts: u64,
     max_liab_transfer: I80F48,
+    max_liquidation_fee: Option<I80F48>,
+    reject_liqor_asset_netting: bool,
+    dust_liqor_asset: bool,
+    batch_token_balance_logs: bool,
 ) -> Result<()> {
     let liq_end_type = HealthType::LiquidationEnd;
 
+    require_msg_typed!(
+        asset_token_index != liab_token_index,
+        MangoError::LiquidationSameToken,
+        "token index {}",
+        asset_token_index
+    );
+
     // Get the mut banks and oracle prices
     //
     // This must happen _after_ the health computation, since immutable borrows of
@@ -101,17 +131,27 @@ pub(crate) fn liquidation_action(
         account_retriever.banks_mut_and_oracles(asset_token_index, liab_token_index)?;
     let (liab_bank, liab_oracle_price) = opt_liab_bank_and_price.unwrap();
 
-    // The main complication here is that we can't keep the liqee_asset_position and liqee_liab_position
-    // borrows alive at the same time. Possibly adding get_mut_pair() would be helpful.
     let (liqee_asset_position, liqee_asset_raw_index) =
         liqee.token_position_and_raw_index(asset_token_index)?;
     let liqee_asset_native = liqee_asset_position.native(asset_bank);
-    require_gt!(liqee_asset_native, 0);
+    require_msg_typed!(
+        liqee_asset_native.is_positive(),
+        MangoError::LiquidationAssetNotPositive,
+        "asset token index {}, native position {}",
+        asset_token_index,
+        liqee_asset_native
+    );
 
     let (liqee_liab_position, liqee_liab_raw_index) =
         liqee.token_position_and_raw_index(liab_token_index)?;
     let liqee_liab_native = liqee_liab_position.native(liab_bank);
-    require_gt!(0, liqee_liab_native);
+    require_msg_typed!(
+        liqee_liab_native.is_negative(),
+        MangoError::LiquidationLiabNotNegative,
+        "liab token index {}, native position {}",
+        liab_token_index,
+        liqee_liab_native
+    );
 
     // Liquidation fees work by giving the liqor more assets than the oracle price would
     // indicate. Specifically we choose
@@ -122,8 +162,27 @@ pub(crate) fn liquidation_action(
     //   assets = liabs * liab_oracle_price / asset_oracle_price * fee_factor
     //   assets = liabs * liab_oracle_price_adjusted / asset_oracle_price
     //          = liabs * lopa / aop
-    let fee_factor = I80F48::ONE + liab_bank.liquidation_fee;
-    let liab_oracle_price_adjusted = liab_oracle_price * fee_factor;
+    //
+    // The fee is clamped to the group's maximum in case a bank is misconfigured.
+    //
+    // Banks in the flat quote fee mode instead use
+    //   assets = (liabs * liab_oracle_price + flat_fee) / asset_oracle_price
+    // where flat_fee is a fixed amount of native quote per liquidation. It is only
+    // paid once until the liqee stops being liquidated: otherwise a liqor could
+    // split one liquidation into many small ones and collect the fee each time.
+    let flat_fee_mode = liab_bank.is_flat_quote_liquidation_fee();
+    let (liab_oracle_price_adjusted, flat_fee) = if flat_fee_mode {
+        let flat_fee = if liqee.fixed.flat_liquidation_fee_paid() {
+            I80F48::ZERO
+        } else {
+            I80F48::from_num(liab_bank.flat_liquidation_fee_quote)
+        };
+        (liab_oracle_price, flat_fee)
+    } else {
+        let liquidation_fee = liab_bank.capped_liquidation_fee(max_liquidation_fee);
+        let fee_factor = I80F48::ONE + liquidation_fee;
+        (liab_oracle_price * fee_factor, I80F48::ZERO)
+    };
 
     let init_asset_weight = asset_bank.init_asset_weight;
     let init_liab_weight = liab_bank.init_liab_weight;
@@ -154,7 +213,15 @@ pub(crate) fn liquidation_action(
     let liqee_liab_health_balance = liqee_health_token_balances
         [liqee_health_cache.token_info_index(liab_token_index)?]
     .spot_and_perp;
+    // The liqor may have capped how much liab it takes on per liquidation
+    let liqor_max_liab_quote = liqor.fixed.liqor_max_liab_quote;
+    let liqor_max_liab_transfer = if liqor_max_liab_quote > 0 {
+        I80F48::from(liqor_max_liab_quote) / liab_oracle_price
+    } else {
+        I80F48::MAX
+    };
     let max_liab_liquidation = max_liab_transfer
+        .min(liqor_max_liab_transfer)
         .min(-liqee_liab_native)
         .min(-liqee_liab_health_balance)
         .max(I80F48::ZERO);
@@ -172,74 +239,117 @@ pub(crate) fn liquidation_action(
     require_gt!(max_liab_liquidation, 0);
     require_gt!(max_asset_transfer, 0);
 
-    // How much asset would need to be exchanged to liab in order to bring health to 0?
-    //
-    // That means: what is x (unit: native liab tokens) such that
-    //   init_health
-    //     + x * ilw * llep     health gain from reducing liabs
-    //     - y * iaw * alep     health loss from paying asset
-    //     = 0
-    // where
-    //   ilw = init_liab_weight,
-    //   llep = liab_liq_end_price,
-    //   lopa = liab_oracle_price_adjusted, (see above)
-    //   iaw = init_asset_weight,
-    //   alep = asset_liq_end_price,
-    //   aop = asset_oracle_price
-    //   ff = fee_factor
-    // and the asset cost of getting x native units of liab is:
-    //   y = x * lopa / aop   (native asset tokens, see above)
-    //
-    // Result: x = -init_health / (ilw * llep - iaw * lopa * alep / aop)
-    //
-    // Simplified for alep == aop:
+    // An asset position that can't cover the flat fee could never be liquidated with it,
+    // so it is liquidated without a fee instead
+    let flat_fee = if flat_fee > max_asset_transfer * asset_oracle_price {
+        msg!(
+            "asset position {} can't pay the flat liquidation fee {}, waiving it",
+            max_asset_transfer,
+            flat_fee
+        );
+        I80F48::ZERO
+    } else {
+        flat_fee
+    };
+
     assert!(asset_liq_end_price == asset_oracle_price);
-    let liab_needed = -liqee_liq_end_health
-        / (liab_liq_end_price * init_liab_weight - liab_oracle_price_adjusted * init_asset_weight);
+    let (liab_transfer, mut asset_transfer) = liquidation_transfer_amounts(
+        liqee_liq_end_health,
+        liab_liq_end_price,
+        init_liab_weight,
+        liab_oracle_price_adjusted,
+        flat_fee,
+        init_asset_weight,
+        asset_oracle_price,
+        max_liab_liquidation,
+        max_asset_transfer,
+    );
 
-    // How much liab can we get at most for the asset balance?
-    let liab_possible = max_asset_transfer * asset_oracle_price / liab_oracle_price_adjusted;
+    // A flat fee can't be clamped up front, since its relative size depends on the
+    // liquidated amount. Cap it at the group maximum relative to the transferred liab value.
+    if flat_fee_mode {
+        if let Some(max) = max_liquidation_fee {
+            let max_asset_transfer_with_fee =
+                liab_transfer * liab_oracle_price * (I80F48::ONE + max) / asset_oracle_price;
+            asset_transfer = asset_transfer.min(max_asset_transfer_with_fee);
+        }
+    }
 
-    // The amount of liab native tokens we will transfer
-    let liab_transfer = liab_needed
-        .min(liab_possible)
-        .min(max_liab_liquidation)
-        .max(I80F48::ZERO);
+    // Don't spend compute on liquidations that don't move a single native token. An
+    // expensive liab can move less than one native token while the asset side doesn't.
+    require_msg_typed!(
+        liab_transfer.floor() > 0 || asset_transfer.floor() > 0,
+        MangoError::LiquidationTooSmall,
+        "liab transfer {} and asset transfer {} are below one native token",
+        liab_transfer,
+        asset_transfer
+    );
 
-    // The amount of asset native tokens we will give up for them
-    let asset_transfer = liab_transfer * liab_oracle_price_adjusted / asset_oracle_price;
+    if flat_fee.is_positive() {
+        liqee.fixed.set_flat_liquidation_fee_paid(true);
+    }
 
     // During liquidation, we mustn't leave small positive balances in the liqee. Those
     // could break bankruptcy-detection. Thus we dust them even if the token position
     // is nominally in-use.
 
     // Apply the balance changes to the liqor and liqee accounts
-    let liqee_liab_position = liqee.token_position_mut_by_raw_index(liqee_liab_raw_index);
+    let (liqee_asset_position, liqee_liab_position) =
+        liqee.token_position_pair_mut(liqee_asset_raw_index, liqee_liab_raw_index)?;
     let liqee_liab_active =
         liab_bank.deposit_with_dusting(liqee_liab_position, liab_transfer, now_ts)?;
     let liqee_liab_indexed_position = liqee_liab_position.indexed_position;
+    let liqee_liab_native_after = liqee_liab_position.native(liab_bank);
+
+    let liqee_asset_active = asset_bank.withdraw_without_fee_with_dusting(
+        liqee_asset_position,
+        asset_transfer,
+        now_ts,
+    )?;
+    let liqee_asset_indexed_position = liqee_asset_position.indexed_position;
+    let liqee_assets_native_after = liqee_asset_position.native(asset_bank);
 
     let (liqor_liab_position, liqor_liab_raw_index, _) =
         liqor.ensure_token_position(liab_token_index)?;
     let (liqor_liab_active, loan_origination_fee) =
         liab_bank.withdraw_with_fee(liqor_liab_position, liab_transfer, now_ts)?;
     let liqor_liab_indexed_position = liqor_liab_position.indexed_position;
-    let liqee_liab_native_after = liqee_liab_position.native(liab_bank);
 
+    // The liqor's positions are changed like regular deposits and withdrawals, so they
+    // net against whatever the liqor already holds: the liab withdrawal first uses up an
+    // existing liab deposit before borrowing, and the asset deposit first repays an
+    // existing asset borrow before accumulating.
+    //
+    // Liqors that want the asset to be purely accumulated can ask for the liquidation
+    // to be rejected if it would repay an asset borrow.
     let (liqor_asset_position, liqor_asset_raw_index, _) =
         liqor.ensure_token_position(asset_token_index)?;
-    let liqor_asset_active = asset_bank.deposit(liqor_asset_position, asset_transfer, now_ts)?;
+    if reject_liqor_asset_netting {
+        let liqor_asset_native = liqor_asset_position.native(asset_bank);
+        require_msg_typed!(
+            !liqor_asset_native.is_negative(),
+            MangoError::LiquidationLiqorAssetNetting,
+            "asset token index {}, liqor native position {}",
+            asset_token_index,
+            liqor_asset_native
+        );
+    }
+    let mut liqor_asset_active =
+        asset_bank.deposit(liqor_asset_position, asset_transfer, now_ts)?;
+
+    // A liqor that had no asset position may end up with less than one native token,
+    // which it can't withdraw. Optionally dust it, deactivating the position unless
+    // it's in use.
+    let liqor_asset_native_after = liqor_asset_position.native(asset_bank);
+    if dust_liqor_asset
+        && liqor_asset_native_after.is_positive()
+        && liqor_asset_native_after < I80F48::ONE
+    {
+        liqor_asset_active =
+            asset_bank.withdraw_without_fee(liqor_asset_position, I80F48::ZERO, now_ts)?;
+    }
     let liqor_asset_indexed_position = liqor_asset_position.indexed_position;
 
-    let liqee_asset_position = liqee.token_position_mut_by_raw_index(liqee_asset_raw_index);
-    let liqee_asset_active = asset_bank.withdraw_without_fee_with_dusting(
-        liqee_asset_position,
-        asset_transfer,
-        now_ts,
-    )?;
-    let liqee_asset_indexed_position = liqee_asset_position.indexed_position;
-    let liqee_assets_native_after = liqee_asset_position.native(asset_bank);
-
     // Update the health cache
     liqee_health_cache
         .adjust_token_balance(liab_bank, liqee_liab_native_after - liqee_liab_native)?;
@@ -252,42 +362,23 @@ pub(crate) fn liquidation_action(
         asset_transfer
     );
 
-    // liqee asset
-    emit!(TokenBalanceLog {
-        mango_group: liqee.fixed.group,
-        mango_account: liqee_key,
-        token_index: asset_token_index,
-        indexed_position: liqee_asset_indexed_position.to_bits(),
-        deposit_index: asset_bank.deposit_index.to_bits(),
-        borrow_index: asset_bank.borrow_index.to_bits(),
-    });
-    // liqee liab
-    emit!(TokenBalanceLog {
-        mango_group: liqee.fixed.group,
-        mango_account: liqee_key,
-        token_index: liab_token_index,
-        indexed_position: liqee_liab_indexed_position.to_bits(),
-        deposit_index: liab_bank.deposit_index.to_bits(),
-        borrow_index: liab_bank.borrow_index.to_bits(),
-    });
-    // liqor asset
-    emit!(TokenBalanceLog {
-        mango_group: liqee.fixed.group,
-        mango_account: liqor_key,
-        token_index: asset_token_index,
-        indexed_position: liqor_asset_indexed_position.to_bits(),
-        deposit_index: asset_bank.deposit_index.to_bits(),
-        borrow_index: asset_bank.borrow_index.to_bits(),
-    });
-    // liqor liab
-    emit!(TokenBalanceLog {
-        mango_group: liqee.fixed.group,
-        mango_account: liqor_key,
-        token_index: liab_token_index,
-        indexed_position: liqor_liab_indexed_position.to_bits(),
-        deposit_index: liab_bank.deposit_index.to_bits(),
-        borrow_index: liab_bank.borrow_index.to_bits(),
-    });
+    let balance = |mango_account, bank: &Bank, indexed_position: I80F48| TokenBalanceDetail {
+        mango_account,
+        token_index: bank.token_index,
+        indexed_position: indexed_position.to_bits(),
+        deposit_index: bank.deposit_index.to_bits(),
+        borrow_index: bank.borrow_index.to_bits(),
+    };
+    emit_token_balances(
+        liqee.fixed.group,
+        vec![
+            balance(liqee_key, asset_bank, liqee_asset_indexed_position),
+            balance(liqee_key, liab_bank, liqee_liab_indexed_position),
+            balance(liqor_key, asset_bank, liqor_asset_indexed_position),
+            balance(liqor_key, liab_bank, liqor_liab_indexed_position),
+        ],
+        batch_token_balance_logs,
+    );
 
     if loan_origination_fee.is_positive() {
         emit!(WithdrawLoanOriginationFeeLog {
@@ -336,6 +427,78 @@ pub(crate) fn liquidation_action(
     Ok(())
 }
 
+/// Returns the (liab, asset) native token amounts exchanged in a token liquidation.
+///
+/// The liab transfer is the amount needed to bring the liqee's liquidation end health
+/// to zero, limited by max_liab_liquidation and by how much liab max_asset_transfer
+/// can buy. Both results are non-negative.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn liquidation_transfer_amounts(
+    liqee_liq_end_health: I80F48,
+    liab_liq_end_price: I80F48,
+    init_liab_weight: I80F48,
+    liab_oracle_price_adjusted: I80F48,
+    flat_fee: I80F48,
+    init_asset_weight: I80F48,
+    asset_oracle_price: I80F48,
+    max_liab_liquidation: I80F48,
+    max_asset_transfer: I80F48,
+) -> (I80F48, I80F48) {
+    // How much asset would need to be exchanged to liab in order to bring health to 0?
+    //
+    // That means: what is x (unit: native liab tokens) such that
+    //   init_health
+    //     + x * ilw * llep     health gain from reducing liabs
+    //     - y * iaw * alep     health loss from paying asset
+    //     = 0
+    // where
+    //   ilw = init_liab_weight,
+    //   llep = liab_liq_end_price,
+    //   lopa = liab_oracle_price_adjusted, (see liquidation_action)
+    //   f = flat_fee, (native quote, zero unless the liab bank uses a flat fee)
+    //   iaw = init_asset_weight,
+    //   alep = asset_liq_end_price,
+    //   aop = asset_oracle_price
+    // and the asset cost of getting x native units of liab is:
+    //   y = (x * lopa + f) / aop   (native asset tokens)
+    //
+    // Result: x = (-init_health + iaw * f * alep / aop) / (ilw * llep - iaw * lopa * alep / aop)
+    //
+    // Simplified for alep == aop, which the caller guarantees:
+    let liab_needed = (-liqee_liq_end_health + flat_fee * init_asset_weight)
+        / (liab_liq_end_price * init_liab_weight - liab_oracle_price_adjusted * init_asset_weight);
+
+    // How much liab can we get at most for the asset balance?
+    let liab_possible = (max_asset_transfer * asset_oracle_price - flat_fee).max(I80F48::ZERO)
+        / liab_oracle_price_adjusted;
+
+    // The amount of liab native tokens we will transfer
+    let mut liab_transfer = liab_needed
+        .min(liab_possible)
+        .min(max_liab_liquidation)
+        .max(I80F48::ZERO);
+
+    // The amount of asset native tokens we will give up for them
+    let mut asset_transfer = if liab_transfer.is_positive() {
+        (liab_transfer * liab_oracle_price_adjusted + flat_fee) / asset_oracle_price
+    } else {
+        I80F48::ZERO
+    };
+
+    // Converting liab_possible back to assets must not end up above the available
+    // asset amount due to rounding. Clamp and derive the liab amount from the clamped
+    // asset amount so the two stay consistent.
+    if asset_transfer > max_asset_transfer {
+        asset_transfer = max_asset_transfer;
+        liab_transfer = ((asset_transfer * asset_oracle_price - flat_fee)
+            / liab_oracle_price_adjusted)
+            .min(liab_transfer)
+            .max(I80F48::ZERO);
+    }
+
+    (liab_transfer, asset_transfer)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,6 +519,9 @@ mod tests {
         perp_oracle_liab: TestAccount<StubOracle>,
         liqee: MangoAccountValue,
         liqor: MangoAccountValue,
+        max_liquidation_fee: Option<I80F48>,
+        reject_liqor_asset_netting: bool,
+        dust_liqor_asset: bool,
     }
 
     impl TestSetup {
@@ -421,6 +587,9 @@ mod tests {
                 perp_oracle_liab,
                 liqee,
                 liqor,
+                max_liquidation_fee: None,
+                reject_liqor_asset_netting: false,
+                dust_liqor_asset: false,
             }
         }
 
@@ -446,6 +615,15 @@ mod tests {
         }
 
         fn run(&self, max_liab_transfer: I80F48) -> Result<Self> {
+            self.run_with_tokens(1, 0, max_liab_transfer)
+        }
+
+        fn run_with_tokens(
+            &self,
+            liab_token_index: TokenIndex,
+            asset_token_index: TokenIndex,
+            max_liab_transfer: I80F48,
+        ) -> Result<Self> {
             let mut setup = self.clone();
 
             let ais = vec![
@@ -469,8 +647,8 @@ mod tests {
 
             liquidation_action(
                 &mut retriever,
-                1,
-                0,
+                liab_token_index,
+                asset_token_index,
                 &mut setup.liqor.borrow_mut(),
                 Pubkey::new_unique(),
                 &mut setup.liqee.borrow_mut(),
@@ -479,6 +657,10 @@ mod tests {
                 liqee_liq_end_health,
                 0,
                 max_liab_transfer,
+                setup.max_liquidation_fee,
+                setup.reject_liqor_asset_netting,
+                setup.dust_liqor_asset,
+                false,
             )?;
 
             drop(retriever);
@@ -568,6 +750,331 @@ mod tests {
         assert_eq_f!(hc.health(HealthType::LiquidationEnd), 0.0, 0.01);
     }
 
+    #[test]
+    fn test_liq_with_token_max_liquidation_fee() {
+        let mut setup = TestSetup::new();
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank.init_asset_weight = I80F48::from_num(0.8);
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank.init_liab_weight = I80F48::from_num(1.2);
+            liab_bank.liquidation_fee = I80F48::from_num(0.1);
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-8.0), 0)
+                .unwrap();
+        }
+
+        let mut expected = setup.run(I80F48::from(100)).unwrap();
+
+        // an excessive fee is clamped to the maximum and gives the same result
+        setup.liab_bank.data().liquidation_fee = I80F48::from_num(0.3);
+        setup.max_liquidation_fee = Some(I80F48::from_num(0.1));
+        let mut result = setup.run(I80F48::from(100)).unwrap();
+
+        let asset_bank = result.asset_bank.data().clone();
+        let liab_bank = result.liab_bank.data().clone();
+        assert_eq!(
+            asset_p(&mut result.liqee).native(&asset_bank),
+            asset_p(&mut expected.liqee).native(&asset_bank)
+        );
+        assert_eq!(
+            liab_p(&mut result.liqee).native(&liab_bank),
+            liab_p(&mut expected.liqee).native(&liab_bank)
+        );
+        assert_eq!(
+            asset_p(&mut result.liqor).native(&asset_bank),
+            asset_p(&mut expected.liqor).native(&asset_bank)
+        );
+
+        // without the maximum the liqor takes all of the asset
+        setup.max_liquidation_fee = None;
+        let mut unclamped = setup.run(I80F48::from(100)).unwrap();
+        assert!(
+            asset_p(&mut unclamped.liqee).native(&asset_bank)
+                < asset_p(&mut expected.liqee).native(&asset_bank)
+        );
+    }
+
+    #[test]
+    fn test_liq_with_token_flat_quote_fee() {
+        let mut setup = TestSetup::new();
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank.init_asset_weight = I80F48::from_num(0.8);
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank.init_liab_weight = I80F48::from_num(1.2);
+            liab_bank.liquidation_fee = I80F48::from_num(0.1);
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-8.0), 0)
+                .unwrap();
+        }
+
+        let mut flat_setup = setup.clone();
+        {
+            let liab_bank = flat_setup.liab_bank.data();
+            liab_bank.liquidation_fee_mode = 1;
+            liab_bank.flat_liquidation_fee_quote = 0.5;
+        }
+
+        // health is -1.6: with a 10% fee the liqor takes 5 liab for 5.5 asset, and a flat
+        // fee of 0.5 happens to lead to the same result
+        for s in [&setup, &flat_setup] {
+            let mut result = s.run(I80F48::from(100)).unwrap();
+            let asset_bank = result.asset_bank.data().clone();
+            let liab_bank = result.liab_bank.data().clone();
+            assert_eq_f!(asset_p(&mut result.liqee).native(&asset_bank), 4.5, 0.01);
+            assert_eq_f!(liab_p(&mut result.liqee).native(&liab_bank), -3.0, 0.01);
+            assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 5.5, 0.01);
+
+            let hc = result.liqee_health_cache();
+            assert_eq_f!(hc.health(HealthType::LiquidationEnd), 0.0, 0.01);
+        }
+
+        // for smaller liquidations the factor fee shrinks while the flat fee stays the same
+        let mut result = setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.2, 0.01);
+
+        let mut result = flat_setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.5, 0.01);
+
+        // the flat fee is only paid once: splitting the liquidation doesn't earn it again
+        assert!(result.liqee.fixed.flat_liquidation_fee_paid());
+        let mut result = result.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 4.5, 0.01);
+
+        // an asset position that can't pay the flat fee is liquidated without it
+        let mut small_asset_setup = flat_setup.clone();
+        small_asset_setup
+            .liab_bank
+            .data()
+            .flat_liquidation_fee_quote = 20.0;
+        let mut result = small_asset_setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.0, 0.01);
+        assert!(!result.liqee.fixed.flat_liquidation_fee_paid());
+
+        // the flat fee is still capped by the group maximum
+        flat_setup.max_liquidation_fee = Some(I80F48::from_num(0.1));
+        let mut result = flat_setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.2, 0.01);
+    }
+
+    #[test]
+    fn test_liq_with_token_liqor_max_liab_quote() {
+        let mut setup = TestSetup::new();
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank.init_asset_weight = I80F48::from_num(0.8);
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank.init_liab_weight = I80F48::from_num(1.2);
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-8.0), 0)
+                .unwrap();
+        }
+        setup.liab_oracle.data().price = I80F48::from_num(2.0);
+
+        // the liqor's cap of 6 quote binds before max_liab_transfer: 3 liab at price 2
+        setup.liqor.fixed.liqor_max_liab_quote = 6;
+        let mut result = setup.run(I80F48::from(100)).unwrap();
+        let liab_bank = result.liab_bank.data().clone();
+        assert_eq_f!(liab_p(&mut result.liqee).native(&liab_bank), -5.0, 0.01);
+        assert_eq_f!(liab_p(&mut result.liqor).native(&liab_bank), 997.0, 0.01);
+
+        // max_liab_transfer still applies when it is lower
+        let mut result = setup.run(I80F48::from(2)).unwrap();
+        assert_eq_f!(liab_p(&mut result.liqee).native(&liab_bank), -6.0, 0.01);
+
+        // without the cap, only the health target limits the transfer
+        setup.liqor.fixed.liqor_max_liab_quote = 0;
+        let mut result = setup.run(I80F48::from(100)).unwrap();
+        assert!(liab_p(&mut result.liqee).native(&liab_bank) > -5.0);
+    }
+
+    #[test]
+    fn test_liq_with_token_liqor_netting() {
+        let mut setup = TestSetup::new();
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank.init_asset_weight = I80F48::from_num(0.8);
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqor), I80F48::from_num(5.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank.init_liab_weight = I80F48::from_num(1.2);
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-8.0), 0)
+                .unwrap();
+        }
+
+        // 10 * 0.8 - 8 * 1.2 = -1.6, needs 1.6 / (1.2 - 0.8) = 4 liab for 4 asset
+        //
+        // a liqor that already holds the asset accumulates it, the liab comes out of
+        // its existing deposit
+        setup.reject_liqor_asset_netting = true;
+        let mut result = setup.run(I80F48::from(100)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        let liab_bank = result.liab_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 9.0, 0.01);
+        assert_eq_f!(liab_p(&mut result.liqor).native(&liab_bank), 996.0, 0.01);
+        assert_eq_f!(asset_p(&mut result.liqee).native(&asset_bank), 6.0, 0.01);
+        assert_eq_f!(liab_p(&mut result.liqee).native(&liab_bank), -4.0, 0.01);
+
+        // a liqor with an asset borrow gets it repaid
+        setup
+            .asset_bank
+            .data()
+            .change_without_fee(asset_p(&mut setup.liqor), I80F48::from_num(-7.0), 0)
+            .unwrap();
+        setup.reject_liqor_asset_netting = false;
+        let mut result = setup.run(I80F48::from(100)).unwrap();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.0, 0.01);
+
+        // unless it asks for the liquidation to be rejected
+        setup.reject_liqor_asset_netting = true;
+        assert!(setup
+            .run(I80F48::from(100))
+            .is_anchor_error_with_code(MangoError::LiquidationLiqorAssetNetting.into()));
+    }
+
+    #[test]
+    fn test_liq_with_token_dust_liqor_asset() {
+        let mut setup = TestSetup::new();
+        setup.asset_oracle.data().price = I80F48::from(10);
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank.stable_price_model.reset_to_price(10.0, 0);
+            asset_bank.init_asset_weight = I80F48::from_num(0.8);
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank.init_liab_weight = I80F48::from_num(1.2);
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-75.0), 0)
+                .unwrap();
+        }
+
+        // 10 * 10 * 0.8 - 75 * 1.2 = -10, but the liqor only takes 2 liab for 0.2 asset
+        let mut result = setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 0.2, 0.001);
+        assert_eq_f!(asset_bank.dust, 0.0, 0.001);
+
+        // when asked, the sub-dust asset is dusted and the position deactivated
+        setup.dust_liqor_asset = true;
+        let mut result = setup.run(I80F48::from(2)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert!(result.liqor.token_position(0).is_err());
+        assert_eq_f!(asset_bank.dust, 0.2, 0.001);
+        assert_eq_f!(asset_p(&mut result.liqee).native(&asset_bank), 9.8, 0.001);
+
+        // larger acquired positions are kept
+        let mut result = setup.run(I80F48::from(20)).unwrap();
+        let asset_bank = result.asset_bank.data().clone();
+        assert_eq_f!(asset_p(&mut result.liqor).native(&asset_bank), 2.0, 0.001);
+    }
+
+    #[test]
+    fn test_liq_with_token_invalid_positions() {
+        let mut setup = TestSetup::new();
+
+        assert!(setup
+            .run_with_tokens(0, 0, I80F48::from(100))
+            .is_anchor_error_with_code(MangoError::LiquidationSameToken.into()));
+
+        // no asset deposit
+        assert!(setup
+            .run(I80F48::from(100))
+            .is_anchor_error_with_code(MangoError::LiquidationAssetNotPositive.into()));
+
+        // asset deposit, but no liab borrow
+        setup
+            .asset_bank
+            .data()
+            .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+            .unwrap();
+        assert!(setup
+            .run(I80F48::from(100))
+            .is_anchor_error_with_code(MangoError::LiquidationLiabNotNegative.into()));
+    }
+
+    #[test]
+    fn test_liq_with_token_too_small() {
+        let mut setup = TestSetup::new();
+        {
+            let ab = setup.asset_bank.data();
+            ab.init_asset_weight = I80F48::from_num(0.8);
+            let lb = setup.liab_bank.data();
+            lb.init_liab_weight = I80F48::from_num(1.2);
+        }
+        {
+            let asset_bank = setup.asset_bank.data();
+            asset_bank
+                .change_without_fee(asset_p(&mut setup.liqee), I80F48::from_num(10.0), 0)
+                .unwrap();
+
+            let liab_bank = setup.liab_bank.data();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqor), I80F48::from_num(1000.0), 0)
+                .unwrap();
+            liab_bank
+                .change_without_fee(liab_p(&mut setup.liqee), I80F48::from_num(-6.7), 0)
+                .unwrap();
+        }
+
+        // 10 * 0.8 - 6.7 * 1.2 = -0.04, needs only 0.04 / (1.2 - 0.8) = 0.1 liab
+        let hc = setup.liqee_health_cache();
+        assert_eq_f!(hc.health(HealthType::LiquidationEnd), -0.04, 0.001);
+        assert!(setup
+            .run(I80F48::from(100))
+            .is_anchor_error_with_code(MangoError::LiquidationTooSmall.into()));
+
+        // 10 * 0.8 - 6.7 * 1.4 = -1.38, but the liqor limits the transfer
+        setup.liab_bank.data().init_liab_weight = I80F48::from_num(1.4);
+        assert!(setup
+            .run(I80F48::from_num(0.5))
+            .is_anchor_error_with_code(MangoError::LiquidationTooSmall.into()));
+
+        // larger liquidations still work
+        assert!(setup.run(I80F48::from(100)).is_ok());
+    }
+
     #[test]
     fn test_liq_with_token_while_perp() {
         let test_cases = vec![
@@ -768,4 +1275,146 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_liquidation_break_even_asset_price() {
+        let liab_price = I80F48::from_num(2.0);
+        let asset_price = I80F48::from_num(5.0);
+        let fee_factor = I80F48::from_num(1.1);
+        let (liab_transfer, asset_transfer) = liquidation_transfer_amounts(
+            I80F48::from(-100),
+            liab_price,
+            I80F48::from_num(1.2),
+            liab_price * fee_factor,
+            I80F48::ZERO,
+            I80F48::from_num(0.8),
+            asset_price,
+            I80F48::from(20),
+            I80F48::from(1000),
+        );
+        assert_eq!(liab_transfer, I80F48::from(20));
+
+        // with a 10% fee the asset can fall to 5 / 1.1 before the liqor loses money
+        let break_even =
+            liquidation_break_even_asset_price(liab_transfer, liab_price, asset_transfer).unwrap();
+        assert!((break_even.to_num::<f64>() - 5.0 / 1.1).abs() < 1e-9);
+        assert!(
+            (asset_transfer * break_even - liab_transfer * liab_price)
+                .abs()
+                .to_num::<f64>()
+                < 1e-9
+        );
+
+        // a flat fee of 4 quote on a 40 quote liquidation is also a 10% margin
+        let (liab_transfer, asset_transfer) = liquidation_transfer_amounts(
+            I80F48::from(-100),
+            liab_price,
+            I80F48::from_num(1.2),
+            liab_price,
+            I80F48::from(4),
+            I80F48::from_num(0.8),
+            asset_price,
+            I80F48::from(20),
+            I80F48::from(1000),
+        );
+        let flat_break_even =
+            liquidation_break_even_asset_price(liab_transfer, liab_price, asset_transfer).unwrap();
+        assert!((flat_break_even - break_even).abs().to_num::<f64>() < 1e-9);
+
+        assert_eq!(
+            liquidation_break_even_asset_price(liab_transfer, liab_price, I80F48::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_liquidation_transfer_amounts_asset_limit() {
+        // The liqee's health is low enough that the available asset is the binding limit:
+        // the liqor must get exactly all of it, never more, with a matching liab amount.
+        let prices = [0.3, 0.7, 1.1, 1.3, 1.7, 2.9, 3.3, 7.1, 1234.567];
+        for liab_price in prices {
+            for asset_price in prices {
+                for max_asset in [1.0, 3.0, 7.0, 97.0, 12345.0, 0.001] {
+                    let liab_price = I80F48::from_num(liab_price);
+                    let asset_price = I80F48::from_num(asset_price);
+                    let max_asset_transfer = I80F48::from_num(max_asset);
+                    let lopa = liab_price * I80F48::from_num(1.1);
+                    let (liab_transfer, asset_transfer) = liquidation_transfer_amounts(
+                        I80F48::from(-1_000_000_000_000_000i64),
+                        liab_price,
+                        I80F48::from_num(1.2),
+                        lopa,
+                        I80F48::ZERO,
+                        I80F48::from_num(0.8),
+                        asset_price,
+                        I80F48::MAX,
+                        max_asset_transfer,
+                    );
+
+                    let ctx = format!("prices {liab_price} {asset_price}, max {max_asset}");
+                    assert!(asset_transfer <= max_asset_transfer, "{ctx}");
+                    assert!(
+                        (max_asset_transfer - asset_transfer) < I80F48::from_num(1e-9),
+                        "{ctx}"
+                    );
+                    let asset_for_liab = liab_transfer * lopa / asset_price;
+                    assert!(
+                        (asset_for_liab - asset_transfer).abs() < I80F48::from_num(1e-9),
+                        "{ctx}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_liquidation_transfer_amounts_invariants() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut rand_scale = |min_exp: f64, max_exp: f64| {
+            I80F48::from_num(10f64.powf(rng.gen_range(min_exp..max_exp)))
+        };
+
+        for _ in 0..10000 {
+            let health = -rand_scale(-3.0, 6.0);
+            let liab_price = rand_scale(-6.0, 6.0);
+            let asset_price = rand_scale(-6.0, 6.0);
+            let fee_factor = I80F48::ONE + rand_scale(-4.0, -1.0);
+            let init_liab_weight = I80F48::ONE + rand_scale(-2.0, 0.0);
+            let init_asset_weight = rand_scale(-2.0, 0.0);
+            if init_liab_weight - init_asset_weight * fee_factor < 0.01 {
+                continue;
+            }
+            let max_asset_transfer = rand_scale(-3.0, 9.0);
+            let max_liab_liquidation = rand_scale(-3.0, 9.0);
+            let larger_max_liab_liquidation = max_liab_liquidation + rand_scale(-3.0, 9.0);
+
+            let amounts = |max_liab| {
+                liquidation_transfer_amounts(
+                    health,
+                    liab_price,
+                    init_liab_weight,
+                    liab_price * fee_factor,
+                    I80F48::ZERO,
+                    init_asset_weight,
+                    asset_price,
+                    max_liab,
+                    max_asset_transfer,
+                )
+            };
+            let (liab_transfer, asset_transfer) = amounts(max_liab_liquidation);
+            let (larger_liab_transfer, _) = amounts(larger_max_liab_liquidation);
+
+            let ctx = format!(
+                "health {health}, liab price {liab_price}, asset price {asset_price}, \
+                fee factor {fee_factor}, weights {init_liab_weight} {init_asset_weight}, \
+                max asset {max_asset_transfer}, max liab {max_liab_liquidation}"
+            );
+            assert!(liab_transfer >= 0, "{ctx}");
+            assert!(asset_transfer >= 0, "{ctx}");
+            assert!(liab_transfer <= max_liab_liquidation, "{ctx}");
+            assert!(asset_transfer <= max_asset_transfer, "{ctx}");
+            assert!(larger_liab_transfer >= liab_transfer, "{ctx}");
+        }
+    }
 }
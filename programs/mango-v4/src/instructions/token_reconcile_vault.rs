@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use fixed::types::I80F48;
+
+use crate::accounts_ix::*;
+use crate::error::*;
+use crate::logs::TokenReconcileVaultLog;
+use crate::state::*;
+
+/// Books the difference between the vault balance and the bank's expected balance
+/// as collected fees.
+///
+/// The bank expects its vault to hold deposits - borrows + unswept fees + dust. External
+/// transfers into the vault (or rounding) can make these drift apart. The admin can
+/// fix that as long as the discrepancy is at most the bank's max_vault_discrepancy.
+pub fn token_reconcile_vault(ctx: Context<TokenReconcileVault>) -> Result<()> {
+    let mut bank = ctx.accounts.bank.load_mut()?;
+    let vault_amount = ctx.accounts.vault.amount;
+
+    let discrepancy = reconcile_vault(&mut bank, vault_amount)?;
+
+    emit!(TokenReconcileVaultLog {
+        mango_group: ctx.accounts.group.key(),
+        token_index: bank.token_index,
+        vault_amount,
+        discrepancy: discrepancy.to_bits(),
+        collected_fees: bank.collected_fees_native.to_bits(),
+    });
+
+    Ok(())
+}
+
+/// Moves the vault discrepancy into the bank's collected fees and returns it.
+fn reconcile_vault(bank: &mut Bank, vault_amount: u64) -> Result<I80F48> {
+    let discrepancy = I80F48::from(vault_amount) - bank.expected_vault_amount();
+    require_msg_typed!(
+        discrepancy.abs() <= I80F48::from(bank.max_vault_discrepancy),
+        MangoError::TokenVaultDiscrepancyTooLarge,
+        "vault discrepancy {} exceeds the allowed {}",
+        discrepancy,
+        bank.max_vault_discrepancy
+    );
+
+    let collected_fees = bank.collected_fees_native + discrepancy;
+    require_msg_typed!(
+        collected_fees >= I80F48::from(bank.fees_withdrawn),
+        MangoError::TokenVaultReconcileNegativeFees,
+        "vault discrepancy {} exceeds the unswept collected fees {}",
+        discrepancy,
+        bank.unswept_fees_native()
+    );
+    bank.collected_fees_native = collected_fees;
+
+    Ok(discrepancy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_reconcile_vault() {
+        let mut bank = Bank::zeroed();
+        bank.deposit_index = I80F48::ONE;
+        bank.borrow_index = I80F48::ONE;
+        bank.indexed_deposits = I80F48::from(1000);
+        bank.indexed_borrows = I80F48::from(200);
+        bank.collected_fees_native = I80F48::from(10);
+        bank.dust = I80F48::from_num(0.5);
+        bank.max_vault_discrepancy = 20;
+
+        // in sync, dust included
+        let mut b = bank;
+        assert_eq!(
+            reconcile_vault(&mut b, 810).unwrap(),
+            I80F48::from_num(-0.5)
+        );
+        assert_eq!(b.collected_fees_native, I80F48::from_num(9.5));
+
+        // surplus is booked as fees
+        let mut b = bank;
+        assert_eq!(
+            reconcile_vault(&mut b, 825).unwrap(),
+            I80F48::from_num(14.5)
+        );
+        assert_eq!(b.collected_fees_native, I80F48::from_num(24.5));
+
+        // discrepancy beyond the configured bound
+        let mut b = bank;
+        assert!(reconcile_vault(&mut b, 831).is_err());
+        assert!(reconcile_vault(&mut b, 790).is_err());
+
+        // a shortfall can't make collected fees negative
+        let mut b = bank;
+        assert!(reconcile_vault(&mut b, 795).is_err());
+        assert_eq!(b.collected_fees_native, I80F48::from(10));
+
+        let mut b = bank;
+        assert_eq!(
+            reconcile_vault(&mut b, 801).unwrap(),
+            I80F48::from_num(-9.5)
+        );
+        assert_eq!(b.collected_fees_native, I80F48::from_num(0.5));
+    }
+}
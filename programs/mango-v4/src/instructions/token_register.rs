@@ -89,7 +89,12 @@ pub fn token_register(
         deposit_weight_scale_start_quote: f64::MAX,
         reduce_only: 0,
         force_close: 0,
-        reserved: [0; 2118],
+        liquidation_fee_mode: 0,
+        padding: Default::default(),
+        flat_liquidation_fee_quote: 0.0,
+        max_vault_discrepancy: 0,
+        fees_withdrawn: 0,
+        reserved: [0; 2096],
     };
     require_gt!(bank.max_rate, MINIMUM_MAX_RATE);
 
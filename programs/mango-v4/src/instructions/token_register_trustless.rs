@@ -75,7 +75,12 @@ pub fn token_register_trustless(
         deposit_weight_scale_start_quote: 5_000_000_000.0, // $5k
         reduce_only: 2,                                   // deposit-only
         force_close: 0,
-        reserved: [0; 2118],
+        liquidation_fee_mode: 0,
+        padding: Default::default(),
+        flat_liquidation_fee_quote: 0.0,
+        max_vault_discrepancy: 0,
+        fees_withdrawn: 0,
+        reserved: [0; 2096],
     };
     require_gt!(bank.max_rate, MINIMUM_MAX_RATE);
 
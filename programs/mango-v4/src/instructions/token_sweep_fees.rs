@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+use fixed::types::I80F48;
+
+use crate::accounts_ix::*;
+use crate::error::*;
+use crate::group_seeds;
+use crate::logs::TokenSweepFeesLog;
+
+/// Transfers the whole native tokens of a bank's unswept collected fees to the group's
+/// fees treasury.
+///
+/// collected_fees_native keeps growing, the swept amount is tracked in fees_withdrawn.
+pub fn token_sweep_fees(ctx: Context<TokenSweepFees>) -> Result<()> {
+    let group = ctx.accounts.group.load()?;
+    require_keys_neq!(group.fees_treasury, Pubkey::default());
+
+    let mut bank = ctx.accounts.bank.load_mut()?;
+    let amount = bank
+        .unswept_fees_native()
+        .floor()
+        .max(I80F48::ZERO)
+        .to_num::<u64>()
+        .min(ctx.accounts.vault.amount);
+    require_msg_typed!(
+        amount > 0,
+        MangoError::NoFeesToSweep,
+        "no fees to sweep, collected fees {}, already swept {}",
+        bank.collected_fees_native,
+        bank.fees_withdrawn
+    );
+
+    bank.fees_withdrawn += amount;
+
+    let group_seeds = group_seeds!(group);
+    token::transfer(
+        ctx.accounts.transfer_ctx().with_signer(&[group_seeds]),
+        amount,
+    )?;
+
+    // Sweeping must not drain the vault below what's reserved for depositors
+    ctx.accounts.vault.reload()?;
+    bank.enforce_min_vault_to_deposits_ratio(ctx.accounts.vault.as_ref())?;
+
+    emit!(TokenSweepFeesLog {
+        mango_group: ctx.accounts.group.key(),
+        token_index: bank.token_index,
+        treasury: ctx.accounts.treasury.key(),
+        amount,
+        collected_fees: bank.collected_fees_native.to_bits(),
+        fees_withdrawn: bank.fees_withdrawn,
+    });
+
+    Ok(())
+}
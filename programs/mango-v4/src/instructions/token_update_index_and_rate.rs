@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 use crate::accounts_ix::*;
 use crate::error::MangoError;
 use crate::logs::{UpdateIndexLog, UpdateRateLog};
-use crate::state::HOUR;
+use crate::state::{HOUR, MAX_INTEREST_TIMESTEP};
 use crate::{
     accounts_zerocopy::{AccountInfoRef, LoadMutZeroCopyRef, LoadZeroCopyRef},
     state::Bank,
@@ -74,9 +74,8 @@ pub fn token_update_index_and_rate(ctx: Context<TokenUpdateIndexAndRate>) -> Res
         // Limit the maximal time interval that interest is applied for. This means we won't use
         // a fixed interest rate for a very long time period in exceptional circumstances, like
         // when there is a solana downtime or the security council disables this instruction.
-        let max_interest_timestep = 3600; // hour
         let diff_ts =
-            I80F48::from_num((now_ts - some_bank.index_last_updated).min(max_interest_timestep));
+            I80F48::from_num((now_ts - some_bank.index_last_updated).min(MAX_INTEREST_TIMESTEP));
 
         let (deposit_index, borrow_index, borrow_fees, borrow_rate, deposit_rate) =
             some_bank.compute_index(indexed_total_deposits, indexed_total_borrows, diff_ts)?;
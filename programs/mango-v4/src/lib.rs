@@ -31,8 +31,8 @@ pub mod instructions;
 compile_error!("compiling the program entrypoint without 'enable-gpl' makes no sense, enable it or use the 'cpi' or 'client' features");
 
 use state::{
-    OracleConfigParams, PerpMarketIndex, PlaceOrderType, SelfTradeBehavior, Serum3MarketIndex,
-    Side, TokenIndex,
+    OracleConfigParams, OrderSnapshot, PerpMarketIndex, PlaceOrderType, SelfTradeBehavior,
+    Serum3MarketIndex, Side, TokenIndex,
 };
 
 declare_id!("4MangoMjqJ2firMokCjjGgoK8d4MXcrgL7XJaL3w6fVg");
@@ -68,6 +68,12 @@ pub mod mango_v4 {
         buyback_fees_swap_mango_account_opt: Option<Pubkey>,
         mngo_token_index_opt: Option<TokenIndex>,
         buyback_fees_expiry_interval_opt: Option<u64>,
+        fees_treasury_opt: Option<Pubkey>,
+        max_liquidation_fee_opt: Option<f32>,
+        batch_token_balance_logs_opt: Option<bool>,
+        liquidation_fee_emergency_mode_opt: Option<bool>,
+        min_liquidation_fee_quote_opt: Option<f32>,
+        min_liquidation_notional_quote_opt: Option<f32>,
     ) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::group_edit(
@@ -83,6 +89,12 @@ pub mod mango_v4 {
             buyback_fees_swap_mango_account_opt,
             mngo_token_index_opt,
             buyback_fees_expiry_interval_opt,
+            fees_treasury_opt,
+            max_liquidation_fee_opt,
+            batch_token_balance_logs_opt,
+            liquidation_fee_emergency_mode_opt,
+            min_liquidation_fee_quote_opt,
+            min_liquidation_notional_quote_opt,
         )?;
         Ok(())
     }
@@ -184,6 +196,9 @@ pub mod mango_v4 {
         reduce_only_opt: Option<u8>,
         name_opt: Option<String>,
         force_close_opt: Option<bool>,
+        liquidation_fee_mode_opt: Option<u8>,
+        flat_liquidation_fee_quote_opt: Option<f32>,
+        max_vault_discrepancy_opt: Option<u64>,
     ) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::token_edit(
@@ -212,6 +227,9 @@ pub mod mango_v4 {
             reduce_only_opt,
             name_opt,
             force_close_opt,
+            liquidation_fee_mode_opt,
+            flat_liquidation_fee_quote_opt,
+            max_vault_discrepancy_opt,
         )?;
         Ok(())
     }
@@ -241,6 +259,18 @@ pub mod mango_v4 {
         Ok(())
     }
 
+    pub fn token_reconcile_vault(ctx: Context<TokenReconcileVault>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::token_reconcile_vault(ctx)?;
+        Ok(())
+    }
+
+    pub fn token_sweep_fees(ctx: Context<TokenSweepFees>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::token_sweep_fees(ctx)?;
+        Ok(())
+    }
+
     pub fn account_create(
         ctx: Context<AccountCreate>,
         account_num: u32,
@@ -279,9 +309,10 @@ pub mod mango_v4 {
         ctx: Context<AccountEdit>,
         name_opt: Option<String>,
         delegate_opt: Option<Pubkey>,
+        liqor_max_liab_quote_opt: Option<u64>,
     ) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
-        instructions::account_edit(ctx, name_opt, delegate_opt)?;
+        instructions::account_edit(ctx, name_opt, delegate_opt, liqor_max_liab_quote_opt)?;
         Ok(())
     }
 
@@ -533,6 +564,8 @@ pub mod mango_v4 {
             asset_token_index,
             liab_token_index,
             max_liab_transfer,
+            false,
+            false,
         )?;
         Ok(())
     }
@@ -552,6 +585,8 @@ pub mod mango_v4 {
         asset_token_index: TokenIndex,
         liab_token_index: TokenIndex,
         max_liab_transfer: I80F48,
+        reject_liqor_asset_netting: bool,
+        dust_liqor_asset: bool,
     ) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::token_liq_with_token(
@@ -559,6 +594,8 @@ pub mod mango_v4 {
             asset_token_index,
             liab_token_index,
             max_liab_transfer,
+            reject_liqor_asset_netting,
+            dust_liqor_asset,
         )?;
         Ok(())
     }
@@ -690,6 +727,23 @@ pub mod mango_v4 {
         positive_pnl_liquidation_fee_opt: Option<f32>,
         name_opt: Option<String>,
         force_close_opt: Option<bool>,
+        consume_events_reward_opt: Option<f32>,
+        default_order_expiry_seconds_opt: Option<u16>,
+        circuit_breaker_threshold_opt: Option<f32>,
+        circuit_breaker_window_seconds_opt: Option<u32>,
+        circuit_breaker_cooldown_seconds_opt: Option<u32>,
+        min_equity_to_place_order_opt: Option<u64>,
+        oracle_type_opt: Option<OracleType>,
+        maker_rebate_cap_per_open_interest_opt: Option<f32>,
+        maker_rebate_epoch_seconds_opt: Option<u32>,
+        taker_fee_price_impact_factor_opt: Option<f32>,
+        settle_token_index_opt: Option<TokenIndex>,
+        min_order_rest_seconds_opt: Option<u16>,
+        event_queue_halt_fraction_opt: Option<f32>,
+        min_order_price_lots_opt: Option<i64>,
+        max_order_price_lots_opt: Option<i64>,
+        check_sample_accounts_health_opt: Option<bool>,
+        reset_funding_opt: Option<bool>,
     ) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::perp_edit_market(
@@ -724,6 +778,23 @@ pub mod mango_v4 {
             positive_pnl_liquidation_fee_opt,
             name_opt,
             force_close_opt,
+            consume_events_reward_opt,
+            default_order_expiry_seconds_opt,
+            circuit_breaker_threshold_opt,
+            circuit_breaker_window_seconds_opt,
+            circuit_breaker_cooldown_seconds_opt,
+            min_equity_to_place_order_opt,
+            oracle_type_opt,
+            maker_rebate_cap_per_open_interest_opt,
+            maker_rebate_epoch_seconds_opt,
+            taker_fee_price_impact_factor_opt,
+            settle_token_index_opt,
+            min_order_rest_seconds_opt,
+            event_queue_halt_fraction_opt,
+            min_order_price_lots_opt,
+            max_order_price_lots_opt,
+            check_sample_accounts_health_opt,
+            reset_funding_opt,
         )?;
         Ok(())
     }
@@ -1051,12 +1122,42 @@ pub mod mango_v4 {
         Ok(())
     }
 
+    pub fn perp_book_defragment(ctx: Context<PerpBookDefragment>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::perp_book_defragment(ctx)?;
+        Ok(())
+    }
+
     pub fn perp_update_funding(ctx: Context<PerpUpdateFunding>) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::perp_update_funding(ctx)?;
         Ok(())
     }
 
+    pub fn perp_log_spread(ctx: Context<PerpLogSpread>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::perp_log_spread(ctx)?;
+        Ok(())
+    }
+
+    pub fn perp_orderbook_view(ctx: Context<PerpOrderbookView>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::perp_orderbook_view(ctx)?;
+        Ok(())
+    }
+
+    pub fn perp_order_snapshots(
+        ctx: Context<PerpOrderSnapshots>,
+        side: Side,
+        start_index: u32,
+    ) -> Result<Vec<OrderSnapshot>> {
+        #[cfg(feature = "enable-gpl")]
+        return instructions::perp_order_snapshots(ctx, side, start_index);
+
+        #[cfg(not(feature = "enable-gpl"))]
+        Ok(vec![])
+    }
+
     pub fn perp_settle_pnl(ctx: Context<PerpSettlePnl>) -> Result<()> {
         #[cfg(feature = "enable-gpl")]
         instructions::perp_settle_pnl(ctx)?;
@@ -1134,6 +1235,12 @@ pub mod mango_v4 {
         Ok(())
     }
 
+    pub fn compute_liquidation_priority(ctx: Context<ComputeAccountData>) -> Result<()> {
+        #[cfg(feature = "enable-gpl")]
+        instructions::compute_liquidation_priority(ctx)?;
+        Ok(())
+    }
+
     ///
     /// benchmark
     ///
@@ -24,6 +24,27 @@ pub fn emit_perp_balances(
     });
 }
 
+/// Emits one TokenBalanceLog per balance, or a single TokenBalanceBatchLog if `batch` is set
+pub fn emit_token_balances(mango_group: Pubkey, balances: Vec<TokenBalanceDetail>, batch: bool) {
+    if batch {
+        emit!(TokenBalanceBatchLog {
+            mango_group,
+            balances,
+        });
+        return;
+    }
+    for balance in balances {
+        emit!(TokenBalanceLog {
+            mango_group,
+            mango_account: balance.mango_account,
+            token_index: balance.token_index,
+            indexed_position: balance.indexed_position,
+            deposit_index: balance.deposit_index,
+            borrow_index: balance.borrow_index,
+        });
+    }
+}
+
 #[event]
 pub struct PerpBalanceLog {
     pub mango_group: Pubkey,
@@ -435,3 +456,70 @@ pub struct TokenForceCloseBorrowsWithTokenLog {
     pub liab_price: i128,
     pub fee_factor: i128,
 }
+
+#[event]
+pub struct TokenReconcileVaultLog {
+    pub mango_group: Pubkey,
+    pub token_index: u16,
+    pub vault_amount: u64,
+    pub discrepancy: i128,
+    pub collected_fees: i128,
+}
+
+#[event]
+pub struct PerpForceCancelOrdersLog {
+    pub mango_group: Pubkey,
+    pub mango_account: Pubkey,
+    pub perp_market_index: u16,
+    pub orders_cancelled: u8,
+}
+
+#[event]
+pub struct TokenSweepFeesLog {
+    pub mango_group: Pubkey,
+    pub token_index: u16,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub collected_fees: i128,
+    pub fees_withdrawn: u64,
+}
+
+#[event]
+pub struct PerpSpreadLog {
+    pub mango_group: Pubkey,
+    pub perp_market_index: u16,
+    pub oracle_price: i128,
+    pub best_bid_lots: Option<i64>,
+    pub best_ask_lots: Option<i64>,
+    pub mid_price: Option<i128>,
+    pub spread_lots: Option<i64>,
+}
+
+#[event]
+pub struct PerpOrderbookViewLog {
+    pub mango_group: Pubkey,
+    pub perp_market_index: u16,
+    pub oracle_price: i128,
+    pub best_bid_lots: Option<i64>,
+    pub best_ask_lots: Option<i64>,
+    pub impact_quantity: i64,
+    /// Average price in lots of selling impact_quantity into the bids
+    pub bid_impact_price_lots: Option<i128>,
+    /// Average price in lots of buying impact_quantity from the asks
+    pub ask_impact_price_lots: Option<i128>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct TokenBalanceDetail {
+    pub mango_account: Pubkey,
+    pub token_index: u16,
+    pub indexed_position: i128, // I80F48
+    pub deposit_index: i128,    // I80F48
+    pub borrow_index: i128,     // I80F48
+}
+
+#[event]
+pub struct TokenBalanceBatchLog {
+    pub mango_group: Pubkey,
+    pub balances: Vec<TokenBalanceDetail>,
+}
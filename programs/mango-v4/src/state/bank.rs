@@ -18,6 +18,8 @@ pub const DAY: i64 = 86400;
 pub const DAY_I80F48: I80F48 = I80F48::from_bits(86_400 * I80F48::ONE.to_bits());
 pub const YEAR_I80F48: I80F48 = I80F48::from_bits(31_536_000 * I80F48::ONE.to_bits());
 pub const MINIMUM_MAX_RATE: I80F48 = I80F48::from_bits(I80F48::ONE.to_bits() / 2);
+/// Maximal time interval in seconds that a single index update applies interest for
+pub const MAX_INTEREST_TIMESTEP: u64 = 3600;
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -135,8 +137,28 @@ pub struct Bank {
     pub reduce_only: u8,
     pub force_close: u8,
 
+    /// How liquidation fees are charged when this token is the liability
+    /// 0 - Factor: the liqor receives (1 + liquidation_fee) times the liab value in assets
+    /// 1 - FlatQuote: the liqor receives the liab value plus flat_liquidation_fee_quote in assets,
+    ///     once per liquidation of an account; force closes and bankruptcies pay no fee
+    pub liquidation_fee_mode: u8,
+
     #[derivative(Debug = "ignore")]
-    pub reserved: [u8; 2118],
+    pub padding: [u8; 1],
+
+    /// Fee in native quote charged per liquidation when liquidation_fee_mode is FlatQuote
+    pub flat_liquidation_fee_quote: f32,
+
+    /// Largest vault discrepancy, in native tokens, that token_reconcile_vault may book
+    pub max_vault_discrepancy: u64,
+
+    /// Collected fees that were swept to the fees treasury, in native tokens
+    ///
+    /// The fees that can still be swept are collected_fees_native - fees_withdrawn.
+    pub fees_withdrawn: u64,
+
+    #[derivative(Debug = "ignore")]
+    pub reserved: [u8; 2096],
 }
 const_assert_eq!(
     size_of::<Bank>(),
@@ -165,7 +187,12 @@ const_assert_eq!(
         + 8
         + 1
         + 1
-        + 2118
+        + 1
+        + 1
+        + 4
+        + 8
+        + 8
+        + 2096
 );
 const_assert_eq!(size_of::<Bank>(), 3064);
 const_assert_eq!(size_of::<Bank>() % 8, 0);
@@ -227,7 +254,12 @@ impl Bank {
             deposit_weight_scale_start_quote: f64::MAX,
             reduce_only: 0,
             force_close: 0,
-            reserved: [0; 2118],
+            liquidation_fee_mode: existing_bank.liquidation_fee_mode,
+            padding: Default::default(),
+            flat_liquidation_fee_quote: existing_bank.flat_liquidation_fee_quote,
+            max_vault_discrepancy: existing_bank.max_vault_discrepancy,
+            fees_withdrawn: 0,
+            reserved: [0; 2096],
         }
     }
 
@@ -249,6 +281,16 @@ impl Bank {
         self.force_close == 1
     }
 
+    pub fn is_flat_quote_liquidation_fee(&self) -> bool {
+        self.liquidation_fee_mode == 1
+    }
+
+    /// The factor liquidation fee, clamped to the group's maximum in case the bank
+    /// is misconfigured
+    pub fn capped_liquidation_fee(&self, max_liquidation_fee: Option<I80F48>) -> I80F48 {
+        max_liquidation_fee.map_or(self.liquidation_fee, |max| self.liquidation_fee.min(max))
+    }
+
     #[inline(always)]
     pub fn native_borrows(&self) -> I80F48 {
         self.borrow_index * self.indexed_borrows
@@ -259,6 +301,18 @@ impl Bank {
         self.deposit_index * self.indexed_deposits
     }
 
+    /// The amount of native tokens the vault should hold
+    ///
+    /// Dusted fractions of token positions stay in the vault, so they are included.
+    pub fn expected_vault_amount(&self) -> I80F48 {
+        self.native_deposits() - self.native_borrows() + self.unswept_fees_native() + self.dust
+    }
+
+    /// Collected fees that are still in the vault
+    pub fn unswept_fees_native(&self) -> I80F48 {
+        self.collected_fees_native - I80F48::from(self.fees_withdrawn)
+    }
+
     /// Prevent borrowing away the full bank vault.
     /// Keep some in reserve to satisfy non-borrow withdraws.
     pub fn enforce_min_vault_to_deposits_ratio(&self, vault_ai: &AccountInfo) -> Result<()> {
@@ -683,6 +737,22 @@ impl Bank {
         ))
     }
 
+    /// Native deposit interest this bank's depositors have accrued since `index_last_updated`
+    /// that hasn't been applied to the deposit index yet.
+    ///
+    /// Mirrors the index update but only uses this bank's indexed totals, which
+    /// matches the actual update for tokens with a single bank.
+    pub fn pending_interest(&self, now_ts: u64) -> Result<I80F48> {
+        let diff_ts = I80F48::from_num(
+            now_ts
+                .saturating_sub(self.index_last_updated)
+                .min(MAX_INTEREST_TIMESTEP),
+        );
+        let (deposit_index, _, _, _, _) =
+            self.compute_index(self.indexed_deposits, self.indexed_borrows, diff_ts)?;
+        Ok((deposit_index - self.deposit_index) * self.indexed_deposits)
+    }
+
     /// returns the current interest rate in APR
     #[inline(always)]
     pub fn compute_interest_rate(&self, utilization: I80F48) -> I80F48 {
@@ -845,6 +915,25 @@ impl Bank {
     }
 }
 
+/// The asset price at which a token liquidation just breaks even for the liqor.
+///
+/// The liqor pays liab_transfer native liab tokens and receives asset_transfer native
+/// asset tokens. If the asset price falls to the returned value, the received assets are
+/// worth exactly the liab paid at its oracle price. Below it, the liquidation loses money.
+/// This holds for both factor and flat quote liquidation fees.
+///
+/// Lives outside the instructions module so liquidators can use it via the client feature.
+pub fn liquidation_break_even_asset_price(
+    liab_transfer: I80F48,
+    liab_oracle_price: I80F48,
+    asset_transfer: I80F48,
+) -> Option<I80F48> {
+    if !asset_transfer.is_positive() {
+        return None;
+    }
+    Some(liab_transfer * liab_oracle_price / asset_transfer)
+}
+
 #[macro_export]
 macro_rules! bank_seeds {
     ( $bank:expr ) => {
@@ -1012,6 +1101,48 @@ mod tests {
         assert_eq!(bank.avg_utilization, I80F48::ONE);
     }
 
+    #[test]
+    fn test_pending_interest() -> Result<()> {
+        let mut bank = Bank::zeroed();
+        bank.deposit_index = I80F48::from_num(1.0);
+        bank.borrow_index = I80F48::from_num(1.0);
+        bank.util0 = I80F48::from_num(0.5);
+        bank.rate0 = I80F48::from_num(0.1);
+        bank.util1 = I80F48::from_num(0.8);
+        bank.rate1 = I80F48::from_num(0.5);
+        bank.max_rate = I80F48::from_num(2.0);
+        bank.index_last_updated = 1000;
+        bank.indexed_deposits = I80F48::from(1_000_000);
+        bank.indexed_borrows = I80F48::from(600_000);
+
+        assert_eq!(bank.pending_interest(1000)?, I80F48::ZERO);
+
+        let now_ts = 1000 + 600;
+        let pending = bank.pending_interest(now_ts)?;
+        assert!(pending > 0);
+
+        // apply the interest like token_update_index_and_rate does
+        let deposits_before = bank.native_deposits();
+        let (deposit_index, borrow_index, _, _, _) = bank.compute_index(
+            bank.indexed_deposits,
+            bank.indexed_borrows,
+            I80F48::from_num(now_ts - bank.index_last_updated),
+        )?;
+        bank.deposit_index = deposit_index;
+        bank.borrow_index = borrow_index;
+        bank.index_last_updated = now_ts;
+        assert_eq!(bank.native_deposits() - deposits_before, pending);
+        assert_eq!(bank.pending_interest(now_ts)?, I80F48::ZERO);
+
+        // long gaps are capped like the index update
+        assert_eq!(
+            bank.pending_interest(now_ts + 10 * MAX_INTEREST_TIMESTEP)?,
+            bank.pending_interest(now_ts + MAX_INTEREST_TIMESTEP)?
+        );
+
+        Ok(())
+    }
+
     #[test]
     pub fn test_net_borrows() -> Result<()> {
         let mut bank = Bank::zeroed();
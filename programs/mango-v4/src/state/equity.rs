@@ -4,33 +4,36 @@ use anchor_lang::prelude::*;
 
 use fixed::types::I80F48;
 
-use crate::events::{Equity, TokenEquity};
-use crate::health::ScanningAccountRetriever;
+use crate::error::*;
+use crate::events::{Equity, PerpEquity, TokenEquity};
+use crate::health::AccountRetriever;
 
-use super::MangoAccountRef;
+use super::{MangoAccountRef, TokenIndex};
 
 pub fn compute_equity(
     account: &MangoAccountRef,
-    retriever: &ScanningAccountRetriever,
+    retriever: &impl AccountRetriever,
 ) -> Result<Equity> {
+    let group = &account.fixed.group;
     let mut token_equity_map = HashMap::new();
+    let mut token_price_map = HashMap::new();
 
     // token contributions
-    for (_i, position) in account.active_token_positions().enumerate() {
-        let (bank, oracle_price) = retriever.scanned_bank_and_oracle(position.token_index)?;
+    for (i, position) in account.active_token_positions().enumerate() {
+        let (bank, oracle_price) = retriever.bank_and_oracle(group, i, position.token_index)?;
         // converts the token value to the basis token value for health computations
         // TODO: health basis token == USDC?
         let native = position.native(bank);
         token_equity_map.insert(bank.token_index, native * oracle_price);
+        token_price_map.insert(bank.token_index, oracle_price);
     }
 
     // token contributions from Serum3
-    for (_i, serum_account) in account.active_serum3_orders().enumerate() {
-        let oo = retriever.scanned_serum_oo(&serum_account.open_orders)?;
+    for (i, serum_account) in account.active_serum3_orders().enumerate() {
+        let oo = retriever.serum_oo(i, &serum_account.open_orders)?;
 
         // note base token value
-        let (_bank, oracle_price) =
-            retriever.scanned_bank_and_oracle(serum_account.base_token_index)?;
+        let oracle_price = token_price(&token_price_map, serum_account.base_token_index)?;
         let accumulated_equity = token_equity_map
             .get(&serum_account.base_token_index)
             .unwrap_or(&I80F48::ZERO);
@@ -39,8 +42,7 @@ pub fn compute_equity(
         token_equity_map.insert(serum_account.base_token_index, new_equity);
 
         // note quote token value
-        let (_bank, oracle_price) =
-            retriever.scanned_bank_and_oracle(serum_account.quote_token_index)?;
+        let oracle_price = token_price(&token_price_map, serum_account.quote_token_index)?;
         let accumulated_equity = token_equity_map
             .get(&serum_account.quote_token_index)
             .unwrap_or(&I80F48::ZERO);
@@ -57,8 +59,31 @@ pub fn compute_equity(
         })
         .collect::<Vec<TokenEquity>>();
 
-    // TODO: perp contributions
-    let perps = Vec::new();
+    // perp contributions: base position at the oracle price plus the quote position,
+    // converted with the settle token price. Open orders are ignored.
+    let mut perps = Vec::new();
+    for (i, position) in account.active_perp_positions().enumerate() {
+        let (perp_market, oracle_price) =
+            retriever.perp_market_and_oracle_price(group, i, position.market_index)?;
+        let settle_price = token_price(&token_price_map, perp_market.settle_token_index)?;
+        let value = (position.base_position_native(perp_market) * oracle_price
+            + position.quote_position_native())
+            * settle_price;
+        perps.push(PerpEquity {
+            perp_market_index: position.market_index,
+            value,
+        });
+    }
 
     Ok(Equity { tokens, perps })
 }
+
+fn token_price(
+    token_price_map: &HashMap<TokenIndex, I80F48>,
+    token_index: TokenIndex,
+) -> Result<I80F48> {
+    token_price_map
+        .get(&token_index)
+        .copied()
+        .ok_or_else(|| error_msg!("no active token position for token index {}", token_index))
+}
@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
+use fixed::types::I80F48;
 use static_assertions::const_assert_eq;
 use std::mem::size_of;
 
+use super::Bank;
+
 // TODO: Assuming we allow up to 65536 different tokens
 pub type TokenIndex = u16;
 
@@ -88,11 +91,59 @@ pub struct Group {
     /// When set to 0, there's no expiry of buyback fees.
     pub buyback_fees_expiry_interval: u64,
 
-    pub reserved: [u8; 1824],
+    /// Owner of the token accounts that TokenSweepFees sends collected bank fees to.
+    ///
+    /// Sweeping is disabled while this is the default pubkey.
+    pub fees_treasury: Pubkey,
+
+    /// Upper bound for bank liquidation fees, enforced in token_edit and when liquidating.
+    ///
+    /// Set to 0 to disable the bound.
+    pub max_liquidation_fee: f32,
+
+    /// If 1, instructions that change several token balances at once emit a single
+    /// TokenBalanceBatchLog instead of one TokenBalanceLog per balance.
+    pub batch_token_balance_logs: u8,
+
+    /// If 1, max_liquidation_fee is not enforced, allowing banks to pay liqors a
+    /// higher fee in emergencies.
+    pub liquidation_fee_emergency_mode: u8,
+    pub padding2: [u8; 2],
+
+    /// Minimum fee in native quote that bank liquidation fees must pay the liqor on a
+    /// liquidation of min_liquidation_notional_quote, so that liquidations stay worth
+    /// the transaction costs. Enforced in token_edit when liquidation fees change.
+    ///
+    /// Set to 0 to disable the floor.
+    pub min_liquidation_fee_quote: f32,
+
+    /// Liquidation size in native quote that min_liquidation_fee_quote applies to.
+    pub min_liquidation_notional_quote: f32,
+
+    pub reserved: [u8; 1776],
 }
 const_assert_eq!(
     size_of::<Group>(),
-    32 + 4 + 32 * 2 + 4 + 32 * 2 + 4 + 4 + 20 * 32 + 32 + 8 + 16 + 32 + 8 + 1824
+    32 + 4
+        + 32 * 2
+        + 4
+        + 32 * 2
+        + 4
+        + 4
+        + 20 * 32
+        + 32
+        + 8
+        + 16
+        + 32
+        + 8
+        + 32
+        + 4
+        + 1
+        + 1
+        + 2
+        + 4
+        + 4
+        + 1776
 );
 const_assert_eq!(size_of::<Group>(), 2736);
 const_assert_eq!(size_of::<Group>() % 8, 0);
@@ -102,6 +153,34 @@ impl Group {
         self.buyback_fees == 1
     }
 
+    pub fn batch_token_balance_logs(&self) -> bool {
+        self.batch_token_balance_logs == 1
+    }
+
+    pub fn liquidation_fee_emergency_mode(&self) -> bool {
+        self.liquidation_fee_emergency_mode == 1
+    }
+
+    /// The cap on bank liquidation fees, if one is configured and enforced
+    pub fn max_liquidation_fee(&self) -> Option<I80F48> {
+        (self.max_liquidation_fee > 0.0 && !self.liquidation_fee_emergency_mode())
+            .then(|| I80F48::from_num(self.max_liquidation_fee))
+    }
+
+    /// Does the bank pay less than min_liquidation_fee_quote when its token is the liab
+    /// in a liquidation of min_liquidation_notional_quote?
+    pub fn is_liquidation_fee_below_floor(&self, bank: &Bank) -> bool {
+        if self.min_liquidation_fee_quote <= 0.0 {
+            return false;
+        }
+        let fee_quote = if bank.is_flat_quote_liquidation_fee() {
+            bank.flat_liquidation_fee_quote
+        } else {
+            bank.liquidation_fee.to_num::<f32>() * self.min_liquidation_notional_quote
+        };
+        fee_quote < self.min_liquidation_fee_quote
+    }
+
     pub fn is_testing(&self) -> bool {
         self.testing == 1
     }
@@ -190,6 +269,8 @@ pub enum IxGate {
     TokenForceCloseBorrowsWithToken = 49,
     PerpForceClosePosition = 50,
     GroupWithdrawInsuranceFund = 51,
+    PerpBookDefragment = 52,
+    TokenSweepFees = 53,
     // NOTE: Adding new variants requires matching changes in ts and the ix_gate_set instruction.
 }
 
@@ -207,3 +288,46 @@ macro_rules! group_seeds {
 }
 
 pub use group_seeds;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_max_liquidation_fee() {
+        let mut group = Group::zeroed();
+        assert_eq!(group.max_liquidation_fee(), None);
+
+        // normal mode clamps fees to the maximum
+        group.max_liquidation_fee = 0.05;
+        assert_eq!(group.max_liquidation_fee(), Some(I80F48::from_num(0.05f32)));
+
+        // emergency mode allows higher fees
+        group.liquidation_fee_emergency_mode = 1;
+        assert_eq!(group.max_liquidation_fee(), None);
+    }
+
+    #[test]
+    fn test_liquidation_fee_floor() {
+        let mut group = Group::zeroed();
+        let mut bank = Bank::zeroed();
+        bank.liquidation_fee = I80F48::from_num(0.001);
+        assert!(!group.is_liquidation_fee_below_floor(&bank));
+
+        // 0.1% on a 1000 quote liquidation pays 1 quote, below the floor of 5
+        group.min_liquidation_fee_quote = 5.0;
+        group.min_liquidation_notional_quote = 1000.0;
+        assert!(group.is_liquidation_fee_below_floor(&bank));
+
+        bank.liquidation_fee = I80F48::from_num(0.01);
+        assert!(!group.is_liquidation_fee_below_floor(&bank));
+
+        // flat fees are compared directly
+        bank.liquidation_fee_mode = 1;
+        bank.flat_liquidation_fee_quote = 2.0;
+        assert!(group.is_liquidation_fee_below_floor(&bank));
+        bank.flat_liquidation_fee_quote = 5.0;
+        assert!(!group.is_liquidation_fee_below_floor(&bank));
+    }
+}
@@ -80,7 +80,10 @@ pub struct MangoAccount {
 
     pub bump: u8,
 
-    pub padding: [u8; 1],
+    /// Set once a flat quote liquidation fee was paid during the current liquidation.
+    ///
+    /// Reset when the account stops being liquidated.
+    pub flat_liquidation_fee_paid: u8,
 
     // (Display only)
     // Cumulative (deposits - withdraws)
@@ -104,7 +107,13 @@ pub struct MangoAccount {
     /// End timestamp of the current expiry interval of the buyback fees amount.
     pub buyback_fees_expiry_timestamp: u64,
 
-    pub reserved: [u8; 208],
+    /// When acting as a liqor, take on at most this much liab per token liquidation,
+    /// in native quote. Applies on top of the max_liab_transfer argument.
+    ///
+    /// Set to 0 to disable the cap.
+    pub liqor_max_liab_quote: u64,
+
+    pub reserved: [u8; 200],
 
     // dynamic
     pub header_version: u8,
@@ -135,14 +144,15 @@ impl MangoAccount {
             in_health_region: 0,
             account_num: 0,
             bump: 0,
-            padding: Default::default(),
+            flat_liquidation_fee_paid: 0,
             net_deposits: 0,
             health_region_begin_init_health: 0,
             frozen_until: 0,
             buyback_fees_accrued_current: 0,
             buyback_fees_accrued_previous: 0,
             buyback_fees_expiry_timestamp: 0,
-            reserved: [0; 208],
+            liqor_max_liab_quote: 0,
+            reserved: [0; 200],
             header_version: DEFAULT_MANGO_ACCOUNT_VERSION,
             padding3: Default::default(),
             padding4: Default::default(),
@@ -219,7 +229,7 @@ pub struct MangoAccountFixed {
     being_liquidated: u8,
     in_health_region: u8,
     pub bump: u8,
-    pub padding: [u8; 1],
+    flat_liquidation_fee_paid: u8,
     pub net_deposits: i64,
     pub perp_spot_transfers: i64,
     pub health_region_begin_init_health: i64,
@@ -227,9 +237,10 @@ pub struct MangoAccountFixed {
     pub buyback_fees_accrued_current: u64,
     pub buyback_fees_accrued_previous: u64,
     pub buyback_fees_expiry_timestamp: u64,
-    pub reserved: [u8; 208],
+    pub liqor_max_liab_quote: u64,
+    pub reserved: [u8; 200],
 }
-const_assert_eq!(size_of::<MangoAccountFixed>(), 32 * 4 + 8 + 7 * 8 + 208);
+const_assert_eq!(size_of::<MangoAccountFixed>(), 32 * 4 + 8 + 8 * 8 + 200);
 const_assert_eq!(size_of::<MangoAccountFixed>(), 400);
 const_assert_eq!(size_of::<MangoAccountFixed>() % 8, 0);
 
@@ -261,6 +272,14 @@ impl MangoAccountFixed {
         self.being_liquidated = u8::from(b);
     }
 
+    pub fn flat_liquidation_fee_paid(&self) -> bool {
+        self.flat_liquidation_fee_paid == 1
+    }
+
+    pub fn set_flat_liquidation_fee_paid(&mut self, b: bool) {
+        self.flat_liquidation_fee_paid = u8::from(b);
+    }
+
     pub fn is_in_health_region(&self) -> bool {
         self.in_health_region == 1
     }
@@ -274,6 +293,7 @@ impl MangoAccountFixed {
         let one_native_usdc = I80F48::ONE;
         if self.being_liquidated() && liq_end_health > -one_native_usdc {
             self.set_being_liquidated(false);
+            self.set_flat_liquidation_fee_paid(false);
             true
         } else {
             false
@@ -684,6 +704,29 @@ impl<
         get_helper_mut(self.dynamic_mut(), offset)
     }
 
+    /// Get mut TokenPositions at two different raw indexes at the same time
+    pub fn token_position_pair_mut(
+        &mut self,
+        raw_index_a: usize,
+        raw_index_b: usize,
+    ) -> Result<(&mut TokenPosition, &mut TokenPosition)> {
+        require_neq!(raw_index_a, raw_index_b);
+        let token_count = self.header().token_count();
+        require_gt!(token_count, raw_index_a);
+        require_gt!(token_count, raw_index_b);
+
+        let offset_a = self.header().token_offset(raw_index_a);
+        let offset_b = self.header().token_offset(raw_index_b);
+        let dynamic = self.dynamic_mut();
+        if offset_a < offset_b {
+            let (low, high) = dynamic.split_at_mut(offset_b);
+            Ok((get_helper_mut(low, offset_a), get_helper_mut(high, 0)))
+        } else {
+            let (low, high) = dynamic.split_at_mut(offset_a);
+            Ok((get_helper_mut(high, 0), get_helper_mut(low, offset_b)))
+        }
+    }
+
     /// Creates or retrieves a TokenPosition for the token_index.
     /// Returns:
     /// - the position
@@ -1287,6 +1330,7 @@ mod tests {
         account.buyback_fees_accrued_current = 10;
         account.buyback_fees_accrued_previous = 11;
         account.buyback_fees_expiry_timestamp = 12;
+        account.liqor_max_liab_quote = 13;
         account.tokens.resize(8, TokenPosition::default());
         account.tokens[0].token_index = 8;
         account.serum3.resize(8, Serum3Orders::default());
@@ -1330,6 +1374,10 @@ mod tests {
             account.buyback_fees_expiry_timestamp,
             account2.fixed.buyback_fees_expiry_timestamp
         );
+        assert_eq!(
+            account.liqor_max_liab_quote,
+            account2.fixed.liqor_max_liab_quote
+        );
         assert_eq!(
             account.tokens[0].token_index,
             account2.token_position_by_raw_index(0).token_index
@@ -1411,6 +1459,21 @@ mod tests {
             assert_eq!(pos.token_index, 8);
             assert_eq!(raw, 1);
         }
+
+        {
+            let (a, b) = account.token_position_pair_mut(2, 1).unwrap();
+            assert_eq!(a.token_index, 42);
+            assert_eq!(b.token_index, 8);
+            a.indexed_position = I80F48::from(3);
+            b.indexed_position = I80F48::from(-5);
+        }
+        {
+            let (a, b) = account.token_position_pair_mut(1, 2).unwrap();
+            assert_eq!(a.indexed_position, I80F48::from(-5));
+            assert_eq!(b.indexed_position, I80F48::from(3));
+        }
+        assert!(account.token_position_pair_mut(1, 1).is_err());
+        assert!(account.token_position_pair_mut(1, 100).is_err());
     }
 
     #[test]
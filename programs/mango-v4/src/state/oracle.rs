@@ -83,7 +83,7 @@ impl OracleConfigParams {
     }
 }
 
-#[derive(PartialEq, AnchorSerialize, AnchorDeserialize)]
+#[derive(PartialEq, Clone, Copy, Debug, AnchorSerialize, AnchorDeserialize)]
 pub enum OracleType {
     Pyth,
     Stub,
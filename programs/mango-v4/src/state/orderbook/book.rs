@@ -40,6 +40,22 @@ impl<'a> Orderbook<'a> {
         }
     }
 
+    /// Returns true if an order on `side` at `price_lots` would match against the best
+    /// valid order on the opposing side, considering both fixed and oracle pegged orders.
+    pub fn is_crossing(
+        &self,
+        side: Side,
+        price_lots: i64,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> bool {
+        self.bookside(side.invert_side())
+            .best_price(now_ts, oracle_price_lots)
+            .map_or(false, |best_opposing_price| {
+                side.is_price_within_limit(best_opposing_price, price_lots)
+            })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn new_order(
         &mut self,
@@ -60,6 +76,19 @@ impl<'a> Orderbook<'a> {
         let mut post_target = order.post_target();
         let (price_lots, price_data) = order.price(now_ts, oracle_price_lots, self)?;
 
+        // Only orders that may rest on the book are restricted, market and IOC orders
+        // use extreme limit prices and only fill against resting orders.
+        if post_target.is_some() {
+            require_msg_typed!(
+                market.is_order_price_allowed(price_lots),
+                MangoError::PerpOrderPriceOutOfRange,
+                "order price {} lots, allowed range {}..={} (zero is unbounded)",
+                price_lots,
+                market.min_order_price_lots,
+                market.max_order_price_lots
+            );
+        }
+
         // generate new order id
         let order_id = market.gen_order_id(side, price_data);
 
@@ -80,6 +109,7 @@ impl<'a> Orderbook<'a> {
         let mut remaining_quote_lots = order.max_quote_lots;
         let mut decremented_base_lots = 0i64;
         let mut decremented_quote_lots = 0i64;
+        let mut maker_fees = I80F48::ZERO;
         let mut orders_to_change: Vec<(BookSideOrderHandle, i64)> = vec![];
         let mut orders_to_delete: Vec<(BookSideOrderTree, u128)> = vec![];
         let mut number_of_dropped_expired_orders = 0;
@@ -167,6 +197,8 @@ impl<'a> Orderbook<'a> {
             remaining_quote_lots -= match_quote_lots;
             assert!(remaining_quote_lots >= 0);
 
+            market.record_fill_price(best_opposing_price, now_ts);
+
             let new_best_opposing_quantity = best_opposing.node.quantity - match_base_lots;
             let maker_out = new_best_opposing_quantity == 0;
             if maker_out {
@@ -176,6 +208,14 @@ impl<'a> Orderbook<'a> {
             }
 
             // order_would_self_trade is only true in the DecrementTake case, in which we don't charge fees
+            let maker_fee = if order_would_self_trade {
+                I80F48::ZERO
+            } else {
+                let match_quote_native = I80F48::from(market.quote_lot_size * match_quote_lots);
+                let maker_fee = market.maker_fee_for_fill(match_quote_native, now_ts);
+                maker_fees += match_quote_native * maker_fee;
+                maker_fee
+            };
             let seq_num = event_queue.header.seq_num;
             let fill = FillEvent::new(
                 side,
@@ -185,11 +225,7 @@ impl<'a> Orderbook<'a> {
                 seq_num,
                 best_opposing.node.owner,
                 best_opposing.node.client_order_id,
-                if order_would_self_trade {
-                    I80F48::ZERO
-                } else {
-                    market.maker_fee
-                },
+                maker_fee,
                 best_opposing.node.timestamp,
                 *mango_account_pk,
                 order.client_order_id,
@@ -220,11 +256,22 @@ impl<'a> Orderbook<'a> {
         // realized when the fill event gets executed
         if total_quote_lots_taken > 0 || total_base_lots_taken > 0 {
             perp_position.add_taker_trade(side, total_base_lots_taken, total_quote_lots_taken);
+            let price_impact_fee =
+                if market.taker_fee_price_impact_factor > 0.0 && total_base_lots_taken > 0 {
+                    // volume weighted average price of the fills made above
+                    let fill_price_lots =
+                        I80F48::from(total_quote_lots_taken) / I80F48::from(total_base_lots_taken);
+                    market.price_impact_taker_fee(fill_price_lots, oracle_price_lots)
+                } else {
+                    I80F48::ZERO
+                };
             // reduce fees to apply by decrement take volume
             let taker_fees_paid = apply_fees(
                 market,
                 mango_account,
                 total_quote_lots_taken - decremented_quote_lots,
+                maker_fees,
+                price_impact_fee,
             )?;
             emit!(PerpTakerTradeLog {
                 mango_group: market.group.key(),
@@ -307,7 +354,7 @@ impl<'a> Orderbook<'a> {
             }
 
             let owner_slot = mango_account.perp_next_order_slot()?;
-            let new_order = LeafNode::new(
+            let mut new_order = LeafNode::new(
                 owner_slot as u8,
                 order_id,
                 *mango_account_pk,
@@ -318,6 +365,7 @@ impl<'a> Orderbook<'a> {
                 order.peg_limit(),
                 order.client_order_id,
             );
+            new_order.min_rest_seconds = market.min_order_rest_seconds;
             let _result = bookside.insert_leaf(order_tree_target, &new_order)?;
 
             // TODO OPT remove if PlacePerpOrder needs more compute
@@ -339,6 +387,15 @@ impl<'a> Orderbook<'a> {
                 &new_order,
                 order.client_order_id,
             )?;
+
+            // Matching must have consumed every opposing order the new one crosses,
+            // no matter which order tree it's in.
+            debug_assert!(
+                !self.is_crossing(side, price_lots, now_ts, oracle_price_lots),
+                "order_id={} price={} left the book crossed",
+                order_id,
+                price_lots
+            );
         }
 
         if post_target.is_some() {
@@ -351,12 +408,15 @@ impl<'a> Orderbook<'a> {
     /// Cancels up to `limit` orders that are listed on the mango account for the given perp market.
     /// Optionally filters by `side_to_cancel_option`.
     /// The orders are removed from the book and from the mango account open order list.
+    ///
+    /// See cancel_order() for `rest_check_ts`. Orders that can't be cancelled yet are skipped.
     pub fn cancel_all_orders(
         &mut self,
         mango_account: &mut MangoAccountRefMut,
         perp_market: &mut PerpMarket,
         mut limit: u8,
         side_to_cancel_option: Option<Side>,
+        rest_check_ts: Option<u64>,
     ) -> Result<()> {
         for i in 0..mango_account.header.perp_oo_count() {
             let oo = mango_account.perp_order_by_raw_index(i);
@@ -373,16 +433,29 @@ impl<'a> Orderbook<'a> {
 
             let order_id = oo.id;
 
-            let cancel_result =
-                self.cancel_order(mango_account, order_id, order_side_and_tree, None);
+            let cancel_result = self.cancel_order(
+                mango_account,
+                order_id,
+                order_side_and_tree,
+                None,
+                rest_check_ts,
+            );
             if cancel_result.is_anchor_error_with_code(MangoError::PerpOrderIdNotFound.into()) {
                 // It's possible for the order to be filled or expired already.
                 // There will be an event on the queue, the perp order slot is freed once
                 // it is processed.
+                let id = OrderId::from_key(order_side_and_tree.side(), order_id);
                 msg!(
-                    "order {} was not found on orderbook, expired or filled already",
-                    order_id
+                    "order {} (seq num {}) was not found on orderbook, expired or filled already",
+                    order_id,
+                    id.seq_num()
                 );
+            } else if cancel_result
+                .is_anchor_error_with_code(MangoError::PerpOrderMinRestTime.into())
+            {
+                // Orders that didn't rest long enough stay on the book, the others can
+                // still be cancelled.
+                msg!("order {} did not rest long enough yet, skipping", order_id);
             } else {
                 cancel_result?;
             }
@@ -397,23 +470,38 @@ impl<'a> Orderbook<'a> {
     }
 
     /// Cancels an order on a side, removing it from the book and the mango account orders list
+    ///
+    /// If `rest_check_ts` is set, orders that have not rested for their minimum rest time
+    /// at that timestamp can't be cancelled.
     pub fn cancel_order(
         &mut self,
         mango_account: &mut MangoAccountRefMut,
         order_id: u128,
         side_and_tree: SideAndOrderTree,
         expected_owner: Option<Pubkey>,
+        rest_check_ts: Option<u64>,
     ) -> Result<LeafNode> {
         let side = side_and_tree.side();
         let book_component = side_and_tree.order_tree();
+        let id = OrderId::from_key(side, order_id);
         let leaf_node = self.bookside_mut(side).
         remove_by_key(book_component, order_id).ok_or_else(|| {
             // possibly already filled or expired?
-            error_msg_typed!(MangoError::PerpOrderIdNotFound, "no perp order with id {order_id}, side {side:?}, component {book_component:?} found on the orderbook")
+            error_msg_typed!(MangoError::PerpOrderIdNotFound, "no perp order with id {order_id} (price data {}, seq num {}), side {side:?}, component {book_component:?} found on the orderbook", id.price_data(), id.seq_num())
         })?;
         if let Some(owner) = expected_owner {
             require_keys_eq!(leaf_node.owner, owner);
         }
+        if let Some(now_ts) = rest_check_ts {
+            require_msg_typed!(
+                leaf_node.is_cancel_allowed(now_ts),
+                MangoError::PerpOrderMinRestTime,
+                "order {order_id} (seq num {}) placed at {} can't be cancelled before {}s of rest",
+                id.seq_num(),
+                leaf_node.timestamp,
+                leaf_node.min_rest_seconds
+            );
+        }
         mango_account.remove_perp_order(leaf_node.owner_slot as usize, leaf_node.quantity)?;
         Ok(leaf_node)
     }
@@ -421,18 +509,23 @@ impl<'a> Orderbook<'a> {
 
 /// Apply taker fees to the taker account and update the markets' fees_accrued for
 /// both the maker and taker fees.
+///
+/// `maker_fees` is the sum of the maker fees of all fills, which can differ from the
+/// market's maker fee when the maker rebate cap applies. The maker fees apply to the
+/// maker's account only when the fill event is consumed.
+///
+/// `price_impact_fee` is charged to the taker on top of the market's taker fee.
 fn apply_fees(
     market: &mut PerpMarket,
     account: &mut MangoAccountRefMut,
     quote_lots: i64,
+    maker_fees: I80F48,
+    price_impact_fee: I80F48,
 ) -> Result<I80F48> {
     assert!(quote_lots >= 0);
     let quote_native = I80F48::from_num(market.quote_lot_size * quote_lots);
 
-    // The maker fees apply to the maker's account only when the fill event is consumed.
-    let maker_fees = quote_native * market.maker_fee;
-
-    let taker_fees = quote_native * market.taker_fee;
+    let taker_fees = quote_native * (market.taker_fee + price_impact_fee);
 
     // taker fees should never be negative
     require_gte!(taker_fees, 0);
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use fixed::types::I80F48;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use static_assertions::const_assert_eq;
 
@@ -21,6 +22,33 @@ pub enum BookSideOrderTree {
     OraclePegged = 1,
 }
 
+/// A resting order with everything needed to replay matching against it off-chain
+///
+/// Returned by the perp_order_snapshots instruction, one page of a book side at a
+/// time, or built by BookSide::order_snapshots() from the BookSide accounts directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, AnchorSerialize, AnchorDeserialize)]
+pub struct OrderSnapshot {
+    pub side: Side,
+    pub order_tree: BookSideOrderTree,
+    /// False for expired orders and oracle pegged orders beyond their peg limit
+    pub is_valid: bool,
+    pub key: u128,
+    pub owner: Pubkey,
+    pub owner_slot: u8,
+    /// Price at the oracle price the snapshot was taken at
+    pub price_lots: i64,
+    pub quantity: i64,
+    pub timestamp: u64,
+    pub time_in_force: u16,
+    pub peg_limit: i64,
+    pub client_order_id: u64,
+}
+
+/// Number of order snapshots returned per perp_order_snapshots call
+///
+/// A borsh encoded Vec of this many snapshots fits in the 1024 bytes of return data.
+pub const ORDER_SNAPSHOTS_PER_PAGE: usize = 10;
+
 /// Reference to a node in a book side component
 pub struct BookSideOrderHandle {
     pub node: NodeHandle,
@@ -137,6 +165,13 @@ impl BookSide {
         self.nodes.remove_by_key(root, search_key)
     }
 
+    /// Compacts the node storage of both order trees, see OrderTreeNodes::defragment().
+    ///
+    /// All orders and their priority stay unchanged. Returns the number of moved nodes.
+    pub fn defragment(&mut self) -> Result<u32> {
+        self.nodes.defragment(&mut self.roots)
+    }
+
     pub fn side(&self) -> Side {
         self.nodes.order_tree_type().side()
     }
@@ -180,6 +215,138 @@ impl BookSide {
         }
         None
     }
+
+    /// Find the oldest valid order of `owner` at exactly `price_lots`
+    ///
+    /// Considers both fixed and oracle pegged orders. If the owner has several orders
+    /// at that price, the one with the lowest seq_num is returned.
+    pub fn find_order(
+        &self,
+        owner: &Pubkey,
+        price_lots: i64,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> Option<OrderId> {
+        let side = self.side();
+        let mut best: Option<OrderId> = None;
+        for item in self.iter_valid(now_ts, oracle_price_lots) {
+            if side.is_price_better(price_lots, item.price_lots) {
+                break;
+            }
+            if item.price_lots != price_lots || item.node.owner != *owner {
+                continue;
+            }
+            let id = OrderId::from_key(side, item.node.key);
+            if best.map_or(true, |b| id.seq_num() < b.seq_num()) {
+                best = Some(id);
+            }
+        }
+        best
+    }
+
+    /// Average price in lots of filling `quantity` base lots against this side.
+    ///
+    /// Returns None if the valid orders don't add up to `quantity`.
+    pub fn impact_average_price(
+        &self,
+        quantity: i64,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> Option<I80F48> {
+        if quantity <= 0 {
+            return None;
+        }
+        let mut remaining = quantity;
+        let mut quote_lots = I80F48::ZERO;
+        for order in self.iter_valid(now_ts, oracle_price_lots) {
+            let fill = remaining.min(order.node.quantity);
+            quote_lots += I80F48::from(fill) * I80F48::from(order.price_lots);
+            remaining -= fill;
+            if remaining == 0 {
+                return Some(quote_lots / I80F48::from(quantity));
+            }
+        }
+        None
+    }
+
+    /// Sum of the quantity of all valid orders of `owner` on this side
+    pub fn account_resting_quantity(
+        &self,
+        owner: &Pubkey,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> i64 {
+        self.iter_valid(now_ts, oracle_price_lots)
+            .filter(|item| item.node.owner == *owner)
+            .map(|item| item.node.quantity)
+            .sum()
+    }
+
+    /// Walk the book until `target_quote_lots` of quote notional is reached.
+    ///
+    /// Returns the base lots that would be filled and their volume weighted average price
+    /// in price lots. The last order may be filled partially. If the book does not have
+    /// enough depth the result covers all valid orders.
+    pub fn depth_to_notional(
+        &self,
+        target_quote_lots: I80F48,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> (I80F48, I80F48) {
+        let mut filled_base = I80F48::ZERO;
+        let mut filled_quote = I80F48::ZERO;
+        for order in self.iter_valid(now_ts, oracle_price_lots) {
+            let remaining_quote = target_quote_lots - filled_quote;
+            if remaining_quote <= I80F48::ZERO {
+                break;
+            }
+            let price = I80F48::from(order.price_lots);
+            let order_quote = price * I80F48::from(order.node.quantity);
+            if order_quote >= remaining_quote {
+                filled_base += remaining_quote / price;
+                filled_quote = target_quote_lots;
+                break;
+            }
+            filled_base += I80F48::from(order.node.quantity);
+            filled_quote += order_quote;
+        }
+        let avg_price = if filled_base.is_zero() {
+            I80F48::ZERO
+        } else {
+            filled_quote / filled_base
+        };
+        (filled_base, avg_price)
+    }
+
+    /// All orders on this side, including invalid ones, in matching priority order
+    pub fn order_snapshots(&self, now_ts: u64, oracle_price_lots: i64) -> Vec<OrderSnapshot> {
+        self.iter_order_snapshots(now_ts, oracle_price_lots)
+            .collect()
+    }
+
+    /// Like order_snapshots(), but lazy so callers can page through the side
+    pub fn iter_order_snapshots(
+        &self,
+        now_ts: u64,
+        oracle_price_lots: i64,
+    ) -> impl Iterator<Item = OrderSnapshot> + '_ {
+        let side = self.side();
+        self.iter_all_including_invalid(now_ts, oracle_price_lots)
+            .map(move |item| OrderSnapshot {
+                side,
+                order_tree: item.handle.order_tree,
+                is_valid: item.is_valid(),
+                key: item.node.key,
+                owner: item.node.owner,
+                owner_slot: item.node.owner_slot,
+                price_lots: item.price_lots,
+                quantity: item.node.quantity,
+                timestamp: item.node.timestamp,
+                time_in_force: item.node.time_in_force,
+                peg_limit: item.node.peg_limit,
+                client_order_id: item.node.client_order_id,
+            })
+    }
 }
 
 #[cfg(test)]
@@ -367,6 +534,31 @@ mod tests {
         assert_eq!(order_prices(1010, 2015), vec![2000, 100]);
     }
 
+    #[test]
+    fn bookside_best_price_skips_expired() {
+        let bookside = bookside_setup();
+
+        // the fixed order at 120 expires at 1005
+        assert_eq!(bookside.best_price(1004, 100), Some(120));
+        assert_eq!(bookside.best_price(1005, 100), Some(100));
+
+        // expired orders are still in the tree, just flagged as invalid
+        let all = bookside
+            .iter_all_including_invalid(1005, 100)
+            .map(|it| (it.price_lots, it.is_valid()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            all,
+            vec![
+                (120, false),
+                (100, true),
+                (90, true),
+                (85, true),
+                (80, true)
+            ]
+        );
+    }
+
     #[test]
     fn bookside_remove_worst() {
         use std::cell::RefCell;
@@ -406,4 +598,260 @@ mod tests {
         assert_eq!(p, 120);
         assert_eq!(order_prices(0, 100), Vec::<i64>::new());
     }
+
+    #[test]
+    fn bookside_defragment() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        let side = Side::Ask;
+        let mut bookside = BookSide {
+            roots: [OrderTreeRoot::zeroed(); 2],
+            reserved_roots: [OrderTreeRoot::zeroed(); 4],
+            reserved: [0; 256],
+            nodes: new_order_tree(OrderTreeType::Asks),
+        };
+        let new_leaf = |key: u128, quantity: i64| {
+            LeafNode::new(
+                0,
+                key,
+                Pubkey::new_unique(),
+                quantity,
+                1000,
+                PostOrderType::Limit,
+                0,
+                -1,
+                0,
+            )
+        };
+
+        // fill both trees, then remove a random half of the orders to fragment the nodes
+        let mut keys = vec![];
+        for seq_num in 0..200u64 {
+            let (component, price_data) = if seq_num % 2 == 0 {
+                (
+                    BookSideOrderTree::Fixed,
+                    fixed_price_data(rng.gen_range(2..100)).unwrap(),
+                )
+            } else {
+                (
+                    BookSideOrderTree::OraclePegged,
+                    oracle_pegged_price_data(rng.gen_range(-20..20)),
+                )
+            };
+            let key = new_node_key(side, price_data, seq_num);
+            bookside
+                .insert_leaf(component, &new_leaf(key, seq_num as i64))
+                .unwrap();
+            keys.push((component, key));
+        }
+        for (component, key) in keys.iter() {
+            if rng.gen_bool(0.5) {
+                bookside.remove_by_key(*component, *key).unwrap();
+            }
+        }
+        assert!(bookside.nodes.free_list_len > 0);
+
+        let orders = |bookside: &BookSide| -> Vec<(u128, Pubkey, i64, i64)> {
+            bookside
+                .iter_all_including_invalid(0, 50)
+                .map(|it| (it.node.key, it.node.owner, it.node.quantity, it.price_lots))
+                .collect()
+        };
+        let orders_before = orders(&bookside);
+        let leaf_count = bookside.roots[0].leaf_count + bookside.roots[1].leaf_count;
+
+        assert!(bookside.defragment().unwrap() > 0);
+        assert_eq!(orders(&bookside), orders_before);
+        assert_eq!(bookside.nodes.free_list_len, 0);
+        // a tree with n leaves has n-1 inner nodes
+        let tree_count = bookside.roots.iter().filter(|r| r.leaf_count > 0).count() as u32;
+        assert_eq!(bookside.nodes.bump_index, 2 * leaf_count - tree_count);
+
+        // new orders can still be added and removed
+        let key = new_node_key(side, fixed_price_data(1).unwrap(), 1000);
+        bookside
+            .insert_leaf(BookSideOrderTree::Fixed, &new_leaf(key, 0))
+            .unwrap();
+        assert_eq!(orders(&bookside)[0].0, key);
+        bookside
+            .remove_by_key(BookSideOrderTree::Fixed, key)
+            .unwrap();
+        assert_eq!(orders(&bookside), orders_before);
+        bookside.defragment().unwrap();
+        assert_eq!(orders(&bookside), orders_before);
+
+        // idempotent
+        let nodes_before = bookside.nodes.nodes;
+        assert_eq!(bookside.defragment().unwrap(), 0);
+        assert_eq!(orders(&bookside), orders_before);
+        assert!(bookside
+            .nodes
+            .nodes
+            .iter()
+            .zip(nodes_before.iter())
+            .all(|(a, b)| bytemuck::bytes_of(a) == bytemuck::bytes_of(b)));
+    }
+
+    #[test]
+    fn bookside_depth_to_notional() {
+        let bookside = bookside_setup();
+
+        // book at oracle 100 is 1000 lots each at 120, 100, 90, 85, 80
+
+        // fully within the first order
+        let (base, price) = bookside.depth_to_notional(I80F48::from(60_000), 0, 100);
+        assert_eq!(base, I80F48::from(500));
+        assert_eq!(price, I80F48::from(120));
+
+        // crosses into the second order
+        let (base, price) = bookside.depth_to_notional(I80F48::from(150_000), 0, 100);
+        assert_eq!(base, I80F48::from(1300));
+        assert_eq!(price, I80F48::from(150_000) / I80F48::from(1300));
+
+        // not enough depth: everything is filled
+        let total_quote = (120 + 100 + 90 + 85 + 80) * 1000;
+        let (base, price) = bookside.depth_to_notional(I80F48::from(10_000_000), 0, 100);
+        assert_eq!(base, I80F48::from(5000));
+        assert_eq!(price, I80F48::from(total_quote) / I80F48::from(5000));
+
+        // expired orders are skipped
+        let (base, price) = bookside.depth_to_notional(I80F48::from(50_000), 1005, 100);
+        assert_eq!(base, I80F48::from(500));
+        assert_eq!(price, I80F48::from(100));
+
+        // nothing requested
+        let (base, price) = bookside.depth_to_notional(I80F48::ZERO, 0, 100);
+        assert_eq!(base, I80F48::ZERO);
+        assert_eq!(price, I80F48::ZERO);
+    }
+
+    #[test]
+    fn bookside_find_order() {
+        use std::cell::RefCell;
+
+        let side = Side::Bid;
+        let order_tree = RefCell::new(new_order_tree(OrderTreeType::Bids));
+        let mut root_fixed = OrderTreeRoot::zeroed();
+        let mut root_pegged = OrderTreeRoot::zeroed();
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let new_node = |key: u128, owner: Pubkey, tif: u16| {
+            LeafNode::new(0, key, owner, 10, 1000, PostOrderType::Limit, tif, -1, 0)
+        };
+        let mut add_fixed = |price: i64, seq_num: u64, owner: Pubkey, tif: u16| {
+            let key = new_node_key(side, fixed_price_data(price).unwrap(), seq_num);
+            order_tree
+                .borrow_mut()
+                .insert_leaf(&mut root_fixed, &new_node(key, owner, tif))
+                .unwrap();
+        };
+        add_fixed(100, 7, owner, 0);
+        add_fixed(100, 5, owner, 0);
+        add_fixed(100, 9, owner, 0);
+        add_fixed(100, 1, other, 0);
+        add_fixed(100, 3, owner, 5);
+        add_fixed(110, 0, owner, 0);
+        let mut add_pegged = |price_offset: i64, seq_num: u64, owner: Pubkey| {
+            let key = new_node_key(side, oracle_pegged_price_data(price_offset), seq_num);
+            order_tree
+                .borrow_mut()
+                .insert_leaf(&mut root_pegged, &new_node(key, owner, 0))
+                .unwrap();
+        };
+        add_pegged(-10, 4, owner);
+
+        let bookside = BookSide {
+            roots: [root_fixed, root_pegged],
+            reserved_roots: [OrderTreeRoot::zeroed(); 4],
+            reserved: [0; 256],
+            nodes: order_tree.into_inner(),
+        };
+
+        // the oldest order at 100 is the expiring one with seq_num 3
+        let id = bookside.find_order(&owner, 100, 1000, 50).unwrap();
+        assert_eq!(id.seq_num(), 3);
+        assert_eq!(fixed_price_lots(id.price_data()), 100);
+
+        // once it has expired, the next oldest fixed order is found
+        let id = bookside.find_order(&owner, 100, 1006, 50).unwrap();
+        assert_eq!(id.seq_num(), 5);
+
+        // at oracle 110 the pegged order is at 100 too, and older than the fixed ones
+        let id = bookside.find_order(&owner, 100, 1006, 110).unwrap();
+        assert_eq!(id.seq_num(), 4);
+        assert_eq!(oracle_pegged_price_offset(id.price_data()), -10);
+
+        // other owners and prices
+        let id = bookside.find_order(&other, 100, 1000, 50).unwrap();
+        assert_eq!(id.seq_num(), 1);
+        assert!(bookside.find_order(&other, 110, 1000, 50).is_none());
+        assert!(bookside.find_order(&owner, 105, 1000, 50).is_none());
+    }
+
+    #[test]
+    fn bookside_account_resting_quantity() {
+        use std::cell::RefCell;
+
+        let side = Side::Ask;
+        let order_tree = RefCell::new(new_order_tree(OrderTreeType::Asks));
+        let mut root_fixed = OrderTreeRoot::zeroed();
+        let mut root_pegged = OrderTreeRoot::zeroed();
+        let owner = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let new_node = |key: u128, owner: Pubkey, quantity: i64, tif: u16, peg_limit: i64| {
+            LeafNode::new(
+                0,
+                key,
+                owner,
+                quantity,
+                1000,
+                PostOrderType::Limit,
+                tif,
+                peg_limit,
+                0,
+            )
+        };
+        let mut add_fixed = |price: i64, seq_num: u64, owner: Pubkey, quantity: i64, tif: u16| {
+            let key = new_node_key(side, fixed_price_data(price).unwrap(), seq_num);
+            order_tree
+                .borrow_mut()
+                .insert_leaf(&mut root_fixed, &new_node(key, owner, quantity, tif, -1))
+                .unwrap();
+        };
+        add_fixed(100, 0, owner, 1, 0);
+        add_fixed(105, 1, owner, 2, 0);
+        add_fixed(110, 2, other, 4, 0);
+        add_fixed(120, 3, owner, 8, 5);
+        let mut add_pegged = |price_offset: i64, seq_num: u64, owner: Pubkey, quantity: i64| {
+            let key = new_node_key(side, oracle_pegged_price_data(price_offset), seq_num);
+            order_tree
+                .borrow_mut()
+                .insert_leaf(&mut root_pegged, &new_node(key, owner, quantity, 0, 100))
+                .unwrap();
+        };
+        add_pegged(10, 4, owner, 16);
+
+        let bookside = BookSide {
+            roots: [root_fixed, root_pegged],
+            reserved_roots: [OrderTreeRoot::zeroed(); 4],
+            reserved: [0; 256],
+            nodes: order_tree.into_inner(),
+        };
+
+        // all orders are valid at oracle 95: the pegged ask is at 105, above its limit of 100
+        assert_eq!(bookside.account_resting_quantity(&owner, 1000, 95), 27);
+        assert_eq!(bookside.account_resting_quantity(&other, 1000, 95), 4);
+
+        // the expiring order is gone
+        assert_eq!(bookside.account_resting_quantity(&owner, 1005, 95), 19);
+
+        // at oracle 80 the pegged ask would be at 90, below its peg limit, so it's invalid
+        assert_eq!(bookside.account_resting_quantity(&owner, 1005, 80), 3);
+
+        assert_eq!(
+            bookside.account_resting_quantity(&Pubkey::new_unique(), 1000, 80),
+            0
+        );
+    }
 }
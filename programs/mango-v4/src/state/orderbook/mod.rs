@@ -985,4 +985,449 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn book_maker_rebate_cap() {
+        let (mut market, oracle_price, mut event_queue, book_accs) = test_setup(1000.0);
+        let mut book = book_accs.orderbook();
+        let settle_token_index = 0;
+
+        market.maker_fee = I80F48::from_num(-0.001f32);
+        market.taker_fee = I80F48::from_num(0.002f32);
+        market.fees_accrued = I80F48::from(1000);
+        market.open_interest = 100;
+        // a budget of 10 native quote per epoch
+        market.maker_rebate_cap_per_open_interest = 0.1;
+        market.maker_rebate_epoch_seconds = 100;
+
+        let mut new_order = |book: &mut Orderbook,
+                             event_queue: &mut EventQueue,
+                             side,
+                             max_base_lots,
+                             owner: &Pubkey,
+                             now_ts|
+         -> Option<u128> {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, settle_token_index)
+                .unwrap();
+
+            book.new_order(
+                Order {
+                    side,
+                    max_base_lots,
+                    max_quote_lots: i64::MAX,
+                    client_order_id: 0,
+                    time_in_force: 0,
+                    reduce_only: false,
+                    self_trade_behavior: SelfTradeBehavior::DecrementTake,
+                    params: OrderParams::Fixed {
+                        price_lots: 1000,
+                        order_type: PostOrderType::Limit,
+                    },
+                },
+                &mut market,
+                event_queue,
+                oracle_price,
+                &mut account.borrow_mut(),
+                owner,
+                now_ts,
+                u8::MAX,
+            )
+            .unwrap()
+        };
+
+        let maker_pk = Pubkey::new_unique();
+        new_order(&mut book, &mut event_queue, Side::Bid, 100, &maker_pk, 1000);
+
+        let mut take_maker_fee =
+            |book: &mut Orderbook, event_queue: &mut EventQueue, max_base_lots, now_ts| -> f32 {
+                new_order(
+                    book,
+                    event_queue,
+                    Side::Ask,
+                    max_base_lots,
+                    &Pubkey::new_unique(),
+                    now_ts,
+                );
+                let event = event_queue.pop_front().unwrap();
+                let fill: &FillEvent = bytemuck::cast_ref(&event);
+                fill.maker_fee
+            };
+
+        // a fill of 6 base lots at 1000 earns a rebate of 6
+        assert_eq!(take_maker_fee(&mut book, &mut event_queue, 6, 1000), -0.001);
+
+        // another rebate of 6 would exceed the budget of 10
+        assert_eq!(take_maker_fee(&mut book, &mut event_queue, 6, 1050), 0.0);
+
+        // small fills that still fit the budget earn the rebate
+        assert_eq!(take_maker_fee(&mut book, &mut event_queue, 3, 1050), -0.001);
+
+        // a new epoch resets the budget
+        assert_eq!(take_maker_fee(&mut book, &mut event_queue, 6, 1100), -0.001);
+        assert!((market.maker_rebates_paid_in_epoch - 6.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn book_price_impact_taker_fee() {
+        let (mut market, oracle_price, mut event_queue, book_accs) = test_setup(1000.0);
+        let mut book = book_accs.orderbook();
+        let settle_token_index = 0;
+
+        let taker_fee = 0.002;
+        market.taker_fee = I80F48::from_num(taker_fee);
+        market.taker_fee_price_impact_factor = 0.5;
+
+        let new_order = |book: &mut Orderbook,
+                         market: &mut PerpMarket,
+                         event_queue: &mut EventQueue,
+                         side,
+                         price_lots,
+                         max_base_lots| {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, settle_token_index)
+                .unwrap();
+
+            book.new_order(
+                Order {
+                    side,
+                    max_base_lots,
+                    max_quote_lots: i64::MAX,
+                    client_order_id: 0,
+                    time_in_force: 0,
+                    reduce_only: false,
+                    self_trade_behavior: SelfTradeBehavior::DecrementTake,
+                    params: OrderParams::Fixed {
+                        price_lots,
+                        order_type: PostOrderType::Limit,
+                    },
+                },
+                market,
+                event_queue,
+                oracle_price,
+                &mut account.borrow_mut(),
+                &Pubkey::new_unique(),
+                0, // now_ts
+                u8::MAX,
+            )
+            .unwrap();
+        };
+
+        for price_lots in [1000, 1010, 1020] {
+            new_order(
+                &mut book,
+                &mut market,
+                &mut event_queue,
+                Side::Ask,
+                price_lots,
+                10,
+            );
+        }
+
+        // a small taker fills at a single price and pays the flat taker fee
+        let fees_before = market.fees_accrued;
+        new_order(&mut book, &mut market, &mut event_queue, Side::Bid, 1020, 5);
+        let small_fee_rate = ((market.fees_accrued - fees_before) / 5000).to_num::<f64>();
+        assert!((small_fee_rate - taker_fee).abs() < 1e-9);
+
+        // a taker sweeping the book from 1000 to 1020 fills at an average of 1012,
+        // a 1.2% price impact
+        let fees_before = market.fees_accrued;
+        new_order(
+            &mut book,
+            &mut market,
+            &mut event_queue,
+            Side::Bid,
+            1020,
+            25,
+        );
+        let quote = 5 * 1000 + 10 * 1010 + 10 * 1020;
+        let deep_fee_rate = ((market.fees_accrued - fees_before) / quote).to_num::<f64>();
+        assert!(deep_fee_rate > small_fee_rate);
+        assert!((deep_fee_rate - (taker_fee + 0.5 * 0.012)).abs() < 1e-9);
+
+        // a large fill against a single order away from the oracle price also pays
+        new_order(
+            &mut book,
+            &mut market,
+            &mut event_queue,
+            Side::Ask,
+            1050,
+            50,
+        );
+        let fees_before = market.fees_accrued;
+        new_order(
+            &mut book,
+            &mut market,
+            &mut event_queue,
+            Side::Bid,
+            1050,
+            50,
+        );
+        let single_fee_rate = ((market.fees_accrued - fees_before) / (50 * 1050)).to_num::<f64>();
+        assert!((single_fee_rate - (taker_fee + 0.5 * 0.05)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn book_no_cross_after_placement_near_peg() {
+        let (mut market, oracle_price, mut event_queue, book_accs) = test_setup(1000.0);
+        let mut book = book_accs.orderbook();
+        let settle_token_index = 0;
+        // allow resting orders on both sides of the oracle
+        market.maint_base_asset_weight = I80F48::from_num(0.9);
+        market.maint_base_liab_weight = I80F48::from_num(1.1);
+        let oracle_price_lots = market.native_price_to_lot(oracle_price);
+        let moved_oracle_price = I80F48::from_num(990.0);
+        let moved_oracle_price_lots = market.native_price_to_lot(moved_oracle_price);
+
+        let mut new_order = |book: &mut Orderbook, oracle_price, side, params| {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, settle_token_index)
+                .unwrap();
+
+            book.new_order(
+                Order {
+                    side,
+                    max_base_lots: 10,
+                    max_quote_lots: i64::MAX,
+                    client_order_id: 0,
+                    time_in_force: 0,
+                    reduce_only: false,
+                    self_trade_behavior: SelfTradeBehavior::DecrementTake,
+                    params,
+                },
+                &mut market,
+                &mut event_queue,
+                oracle_price,
+                &mut account.borrow_mut(),
+                &Pubkey::new_unique(),
+                0, // now_ts
+                u8::MAX,
+            )
+            .unwrap()
+        };
+        let fixed = |price_lots| OrderParams::Fixed {
+            price_lots,
+            order_type: PostOrderType::Limit,
+        };
+        let pegged = |price_offset_lots| OrderParams::OraclePegged {
+            price_offset_lots,
+            order_type: PostOrderType::Limit,
+            peg_limit: -1,
+            max_oracle_staleness_slots: -1,
+        };
+
+        // a fixed bid just below a pegged ask rests without crossing it
+        new_order(&mut book, oracle_price, Side::Ask, pegged(5));
+        assert!(new_order(&mut book, oracle_price, Side::Bid, fixed(1004)).is_some());
+        assert!(!book.is_crossing(Side::Bid, 1004, 0, oracle_price_lots));
+        assert!(book.is_crossing(Side::Bid, 1005, 0, oracle_price_lots));
+        assert!(book.is_crossing(Side::Ask, 1004, 0, oracle_price_lots));
+
+        // after the oracle moved down, the pegged ask is below the fixed bid: a new bid
+        // at or above the pegged ask price must match it instead of resting next to it
+        let oracle_price = moved_oracle_price;
+        let oracle_price_lots = moved_oracle_price_lots;
+        assert!(book.is_crossing(Side::Bid, 1004, 0, oracle_price_lots));
+        assert!(new_order(&mut book, oracle_price, Side::Bid, fixed(996)).is_none());
+        assert_eq!(book.bids.best_price(0, oracle_price_lots), Some(1004));
+        assert_eq!(book.asks.best_price(0, oracle_price_lots), None);
+
+        // a pegged bid placed through a fixed ask matches it as well
+        new_order(&mut book, oracle_price, Side::Ask, fixed(1010));
+        assert!(new_order(&mut book, oracle_price, Side::Bid, pegged(25)).is_none());
+        assert!(!book.is_crossing(Side::Bid, 1004, 0, oracle_price_lots));
+    }
+
+    #[test]
+    fn book_order_snapshots() {
+        let (mut market, oracle_price, mut event_queue, book_accs) = test_setup(1000.0);
+        let mut book = book_accs.orderbook();
+        let settle_token_index = 0;
+        market.maint_base_asset_weight = I80F48::from_num(0.9);
+        market.maint_base_liab_weight = I80F48::from_num(1.1);
+        let oracle_price_lots = market.native_price_to_lot(oracle_price);
+        let owner = Pubkey::new_unique();
+
+        let mut new_order = |book: &mut Orderbook, side, time_in_force, params, client_order_id| {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, settle_token_index)
+                .unwrap();
+
+            book.new_order(
+                Order {
+                    side,
+                    max_base_lots: 2,
+                    max_quote_lots: i64::MAX,
+                    client_order_id,
+                    time_in_force,
+                    reduce_only: false,
+                    self_trade_behavior: SelfTradeBehavior::DecrementTake,
+                    params,
+                },
+                &mut market,
+                &mut event_queue,
+                oracle_price,
+                &mut account.borrow_mut(),
+                &owner,
+                0, // now_ts
+                u8::MAX,
+            )
+            .unwrap()
+            .unwrap()
+        };
+        let fixed = |price_lots| OrderParams::Fixed {
+            price_lots,
+            order_type: PostOrderType::Limit,
+        };
+        let pegged = |price_offset_lots| OrderParams::OraclePegged {
+            price_offset_lots,
+            order_type: PostOrderType::Limit,
+            peg_limit: 1005,
+            max_oracle_staleness_slots: -1,
+        };
+
+        let bid_990 = new_order(&mut book, Side::Bid, 0, fixed(990), 1);
+        let bid_995 = new_order(&mut book, Side::Bid, 5, fixed(995), 2);
+        let ask_pegged = new_order(&mut book, Side::Ask, 0, pegged(10), 3);
+        let ask_1005 = new_order(&mut book, Side::Ask, 0, fixed(1005), 4);
+
+        let now_ts = 10;
+        let bids = book.bids.order_snapshots(now_ts, oracle_price_lots);
+        let asks = book.asks.order_snapshots(now_ts, oracle_price_lots);
+        assert_eq!(bids, book.bids.order_snapshots(now_ts, oracle_price_lots));
+
+        // the borsh encoding round trips for off-chain replay
+        let snapshot = (bids, asks);
+        let bytes = snapshot.try_to_vec().unwrap();
+        assert_eq!(
+            <(Vec<OrderSnapshot>, Vec<OrderSnapshot>)>::try_from_slice(&bytes).unwrap(),
+            snapshot
+        );
+        let (bids, asks) = snapshot;
+
+        // a full page of snapshots fits in the transaction return data
+        let page = vec![asks[1]; ORDER_SNAPSHOTS_PER_PAGE];
+        assert!(
+            page.try_to_vec().unwrap().len()
+                <= anchor_lang::solana_program::program::MAX_RETURN_DATA
+        );
+
+        // bids best first; the 995 bid has expired but is still part of the snapshot
+        assert_eq!(
+            bids.iter().map(|o| o.key).collect::<Vec<_>>(),
+            vec![bid_995, bid_990]
+        );
+        assert!(!bids[0].is_valid);
+        assert_eq!(bids[0].time_in_force, 5);
+        assert_eq!(bids[0].client_order_id, 2);
+        assert!(bids[1].is_valid);
+        assert_eq!(bids[1].price_lots, 990);
+        assert_eq!(bids[1].order_tree, BookSideOrderTree::Fixed);
+
+        assert_eq!(
+            asks.iter().map(|o| o.key).collect::<Vec<_>>(),
+            vec![ask_1005, ask_pegged]
+        );
+        assert_eq!(asks[1].side, Side::Ask);
+        assert_eq!(asks[1].order_tree, BookSideOrderTree::OraclePegged);
+        assert_eq!(asks[1].price_lots, 1010);
+        assert_eq!(asks[1].peg_limit, 1005);
+        assert_eq!(asks[1].quantity, 2);
+        assert_eq!(asks[1].owner, owner);
+        assert!(asks.iter().all(|o| o.is_valid));
+    }
+
+    #[test]
+    fn book_fill_price_twap() {
+        let (mut market, oracle_price, mut event_queue, book_accs) = test_setup(1000.0);
+        let mut book = book_accs.orderbook();
+        let settle_token_index = 0;
+        let maker_pk = Pubkey::new_unique();
+        let taker_pk = Pubkey::new_unique();
+
+        let mut new_order = |book: &mut Orderbook, side, price_lots, owner, now_ts| {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, settle_token_index)
+                .unwrap();
+
+            book.new_order(
+                Order {
+                    side,
+                    max_base_lots: 1,
+                    max_quote_lots: i64::MAX,
+                    client_order_id: 0,
+                    time_in_force: 0,
+                    reduce_only: false,
+                    self_trade_behavior: SelfTradeBehavior::DecrementTake,
+                    params: OrderParams::Fixed {
+                        price_lots,
+                        order_type: PostOrderType::Limit,
+                    },
+                },
+                &mut market,
+                &mut event_queue,
+                oracle_price,
+                &mut account.borrow_mut(),
+                &owner,
+                now_ts,
+                u8::MAX,
+            )
+            .unwrap();
+        };
+
+        new_order(&mut book, Side::Ask, 1000, maker_pk, 900);
+        new_order(&mut book, Side::Ask, 1100, maker_pk, 900);
+        new_order(&mut book, Side::Ask, 1200, maker_pk, 900);
+
+        // fills at 1000 for 10s, 1100 for 30s, then 1200
+        new_order(&mut book, Side::Bid, 1200, taker_pk, 1000);
+        new_order(&mut book, Side::Bid, 1200, taker_pk, 1010);
+        new_order(&mut book, Side::Bid, 1200, taker_pk, 1040);
+        assert_eq!(market.last_fill_price_lots, 1200);
+        assert_eq!(market.last_fill_ts, 1040);
+        assert_eq!(
+            market.fill_price_cumulative,
+            I80F48::from(1000 * 10 + 1100 * 30)
+        );
+
+        // the accumulator only starts advancing with the first fill
+        let start_cumulative = I80F48::ZERO;
+        let twap = |market: &PerpMarket, now_ts| {
+            market
+                .fill_price_twap_lots(start_cumulative, 1000, now_ts)
+                .unwrap()
+        };
+        assert_eq!(twap(&market, 1010), 1000);
+        assert_eq!(twap(&market, 1040), 1075);
+        // the last fill price is assumed to hold until now
+        assert_eq!(
+            twap(&market, 1060),
+            I80F48::from(1000 * 10 + 1100 * 30 + 1200 * 20) / I80F48::from(60)
+        );
+        assert!(market
+            .fill_price_twap_lots(start_cumulative, 1000, 1000)
+            .is_none());
+
+        // the twap stays correct when the accumulator wraps around
+        let offset = I80F48::MAX - I80F48::from(20_000);
+        market.fill_price_cumulative = market.fill_price_cumulative.wrapping_add(offset);
+        let start_cumulative = start_cumulative.wrapping_add(offset);
+        assert_eq!(
+            market
+                .fill_price_twap_lots(start_cumulative, 1000, 1060)
+                .unwrap(),
+            I80F48::from(1000 * 10 + 1100 * 30 + 1200 * 20) / I80F48::from(60)
+        );
+    }
 }
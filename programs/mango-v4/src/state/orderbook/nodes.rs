@@ -36,6 +36,44 @@ pub fn new_node_key(side: Side, price_data: u64, seq_num: u64) -> u128 {
     upper | (seq_num as u128)
 }
 
+/// A perp order id together with the side of the book it belongs to.
+///
+/// The raw u128 key does not record the side, but it's needed to recover the
+/// seq_num since bids store it inverted, see new_node_key().
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderId {
+    pub side: Side,
+    pub key: u128,
+}
+
+impl OrderId {
+    pub fn new(side: Side, price_data: u64, seq_num: u64) -> Self {
+        Self {
+            side,
+            key: new_node_key(side, price_data, seq_num),
+        }
+    }
+
+    pub fn from_key(side: Side, key: u128) -> Self {
+        Self { side, key }
+    }
+
+    /// The order's price_data, see LeafNode::price_data()
+    pub fn price_data(&self) -> u64 {
+        (self.key >> 64) as u64
+    }
+
+    /// The seq_num that was passed to new_node_key()
+    pub fn seq_num(&self) -> u64 {
+        let lower = self.key as u64;
+        if self.side == Side::Bid {
+            !lower
+        } else {
+            lower
+        }
+    }
+}
+
 /// Creates price data for an oracle pegged order from the price offset
 ///
 /// Reverse of oracle_pegged_price_offset()
@@ -159,7 +197,9 @@ pub struct LeafNode {
     /// A value of 0 means no expiry.
     pub time_in_force: u16,
 
-    pub padding2: [u8; 2],
+    /// Seconds after `timestamp` before the order can be cancelled.
+    /// Copied from the perp market when the order is placed.
+    pub min_rest_seconds: u16,
 
     /// The binary tree key, see new_node_key()
     pub key: u128,
@@ -210,7 +250,7 @@ impl LeafNode {
             order_type: order_type.into(),
             padding: Default::default(),
             time_in_force,
-            padding2: Default::default(),
+            min_rest_seconds: 0,
             key,
             owner,
             quantity,
@@ -244,6 +284,12 @@ impl LeafNode {
     pub fn is_expired(&self, now_ts: u64) -> bool {
         self.time_in_force > 0 && now_ts >= self.timestamp + self.time_in_force as u64
     }
+
+    /// Returns if the order has rested long enough to be cancelled at `now_ts`
+    #[inline(always)]
+    pub fn is_cancel_allowed(&self, now_ts: u64) -> bool {
+        now_ts >= self.timestamp + self.min_rest_seconds as u64
+    }
 }
 
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -433,4 +479,23 @@ mod tests {
             assert!(l_key < r_key);
         }
     }
+
+    #[test]
+    fn order_id_round_trip() {
+        for side in [Side::Bid, Side::Ask] {
+            for (price_data, seq_num) in [
+                (fixed_price_data(1).unwrap(), 0),
+                (fixed_price_data(42).unwrap(), 7),
+                (oracle_pegged_price_data(-5), u64::MAX),
+                (oracle_pegged_price_data(i64::MAX), 12345),
+            ] {
+                let id = OrderId::new(side, price_data, seq_num);
+                assert_eq!(id.key, new_node_key(side, price_data, seq_num));
+                assert_eq!(id.side, side);
+                assert_eq!(id.price_data(), price_data);
+                assert_eq!(id.seq_num(), seq_num);
+                assert_eq!(OrderId::from_key(side, id.key), id);
+            }
+        }
+    }
 }
@@ -1,11 +1,11 @@
 use anchor_lang::prelude::*;
-use bytemuck::{cast, cast_mut, cast_ref};
+use bytemuck::{cast, cast_mut, cast_ref, Zeroable};
 
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use static_assertions::const_assert_eq;
 
 use super::*;
-use crate::error::MangoError;
+use crate::error::*;
 
 pub const MAX_ORDERTREE_NODES: usize = 1024;
 
@@ -399,6 +399,76 @@ impl OrderTreeNodes {
         }
     }
 
+    /// Moves all used nodes to the front of the node array and clears the free list.
+    ///
+    /// Node contents and the tree structure are unchanged, only the handles of moved
+    /// nodes change. `roots` must contain the roots of all trees that share these nodes.
+    ///
+    /// Returns the number of nodes that were moved.
+    pub fn defragment(&mut self, roots: &mut [OrderTreeRoot]) -> Result<u32> {
+        // new_handles[h] is the handle the node at h ends up at, NodeHandle::MAX if unused
+        let mut new_handles: Vec<NodeHandle> = (0..self.bump_index)
+            .map(|h| {
+                if self.node(h).is_some() {
+                    h
+                } else {
+                    NodeHandle::MAX
+                }
+            })
+            .collect();
+
+        let mut moved = 0;
+        let mut lowest_free: NodeHandle = 0;
+        let mut end: NodeHandle = self.bump_index;
+        loop {
+            while lowest_free < end && self.node(lowest_free).is_some() {
+                lowest_free += 1;
+            }
+            while end > lowest_free && self.node(end - 1).is_none() {
+                end -= 1;
+            }
+            if lowest_free >= end {
+                break;
+            }
+
+            let from = end - 1;
+            self.nodes[lowest_free as usize] = self.nodes[from as usize];
+            self.nodes[from as usize] = AnyNode::zeroed();
+            new_handles[from as usize] = lowest_free;
+            moved += 1;
+        }
+
+        // everything at or above `end` is unused now
+        for node in self.nodes[end as usize..self.bump_index as usize].iter_mut() {
+            *node = AnyNode::zeroed();
+        }
+        self.bump_index = end;
+        self.free_list_len = 0;
+        self.free_list_head = 0;
+
+        // update all links in a single pass over the remaining nodes
+        let relink = |handle: NodeHandle| -> Result<NodeHandle> {
+            match new_handles.get(handle as usize) {
+                Some(&new_handle) if new_handle != NodeHandle::MAX => Ok(new_handle),
+                _ => Err(error_msg!("order tree links to unused node {}", handle)),
+            }
+        };
+        for root in roots.iter_mut() {
+            if let Some(handle) = root.node() {
+                root.maybe_node = relink(handle)?;
+            }
+        }
+        for node in self.nodes[..end as usize].iter_mut() {
+            if let Some(inner) = node.as_inner_mut() {
+                for child in inner.children.iter_mut() {
+                    *child = relink(*child)?;
+                }
+            }
+        }
+
+        Ok(moved)
+    }
+
     pub fn is_full(&self) -> bool {
         self.free_list_len <= 1 && (self.bump_index as usize) >= self.nodes.len() - 1
     }
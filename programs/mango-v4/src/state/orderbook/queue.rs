@@ -101,6 +101,28 @@ impl EventQueue {
             index: 0,
         }
     }
+
+    /// Number of events at the front of the queue that only involve mango accounts in `accounts`.
+    ///
+    /// Consuming events stops at the first event whose accounts weren't passed, so a keeper
+    /// that wants to settle fills for a set of accounts first can consume this many events
+    /// with only those accounts and leave the rest of the queue in order.
+    pub fn front_len_for_accounts(&self, accounts: &[Pubkey]) -> usize {
+        self.iter()
+            .take_while(|event| match EventType::try_from(event.event_type) {
+                Ok(EventType::Fill) => {
+                    let fill: &FillEvent = cast_ref(*event);
+                    accounts.contains(&fill.maker) && accounts.contains(&fill.taker)
+                }
+                Ok(EventType::Out) => {
+                    let out: &OutEvent = cast_ref(*event);
+                    accounts.contains(&out.owner)
+                }
+                Ok(EventType::Liquidate) => true,
+                Err(_) => false,
+            })
+            .count()
+    }
 }
 
 struct EventQueueIterator<'a> {
@@ -264,6 +286,45 @@ impl FillEvent {
     }
 }
 
+/// Aggregates a batch of fills by price, for trade tapes and candles.
+///
+/// Returns (price, total quantity, fill count) tuples sorted by price.
+pub fn aggregate_fills_by_price<'a>(
+    fills: impl IntoIterator<Item = &'a FillEvent>,
+) -> Vec<(i64, i64, u32)> {
+    let mut by_price = std::collections::BTreeMap::<i64, (i64, u32)>::new();
+    for fill in fills {
+        let entry = by_price.entry(fill.price).or_default();
+        entry.0 += fill.quantity;
+        entry.1 += 1;
+    }
+    by_price
+        .into_iter()
+        .map(|(price, (quantity, count))| (price, quantity, count))
+        .collect()
+}
+
+/// Sums the maker rebates owed to each maker account for a batch of fills, in native quote.
+///
+/// Uses the same fee computation as executing the maker side of a fill, so the result
+/// can be checked against settled perp positions. Fills with a non-negative maker fee
+/// owe no rebate and are skipped.
+pub fn aggregate_maker_rebates<'a>(
+    fills: impl IntoIterator<Item = &'a FillEvent>,
+    quote_lot_size: i64,
+) -> std::collections::HashMap<Pubkey, I80F48> {
+    let mut rebates = std::collections::HashMap::<Pubkey, I80F48>::new();
+    for fill in fills {
+        if fill.maker_fee >= 0.0 {
+            continue;
+        }
+        let quote = I80F48::from(quote_lot_size) * I80F48::from(fill.price * fill.quantity);
+        let rebate = -(quote.abs() * I80F48::from_num(fill.maker_fee));
+        *rebates.entry(fill.maker).or_insert(I80F48::ZERO) += rebate;
+    }
+    rebates
+}
+
 impl TryFrom<AnyEvent> for FillEvent {
     type Error = error::Error;
 
@@ -368,3 +429,175 @@ impl<'a> TryFrom<&'a AnyEvent> for &'a OutEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{MangoAccount, MangoAccountValue, PerpMarket};
+    use bytemuck::{cast, Zeroable};
+
+    #[test]
+    fn test_aggregate_fills_by_price() {
+        let fill = |price: i64, quantity: i64| {
+            FillEvent::new(
+                Side::Bid,
+                false,
+                0,
+                0,
+                0,
+                Pubkey::default(),
+                0,
+                I80F48::ZERO,
+                0,
+                Pubkey::default(),
+                0,
+                I80F48::ZERO,
+                price,
+                quantity,
+            )
+        };
+
+        let mut queue = EventQueue::zeroed();
+        for (price, quantity) in [(100, 1), (99, 2), (100, 3), (101, 4), (99, 5), (100, 6)] {
+            queue.push_back(cast(fill(price, quantity))).unwrap();
+        }
+        queue
+            .push_back(cast(OutEvent::new(
+                Side::Ask,
+                0,
+                0,
+                0,
+                Pubkey::default(),
+                1,
+            )))
+            .unwrap();
+
+        // drain the queue like consume_events does
+        let mut fills = vec![];
+        while let Ok(event) = queue.pop_front() {
+            if let Ok(fill) = FillEvent::try_from(event) {
+                fills.push(fill);
+            }
+        }
+        assert!(queue.is_empty());
+
+        assert_eq!(
+            aggregate_fills_by_price(&fills),
+            vec![(99, 7, 2), (100, 10, 3), (101, 4, 1)]
+        );
+        assert!(aggregate_fills_by_price(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_maker_rebates() {
+        let fill = |maker: Pubkey, maker_fee: f64, price: i64, quantity: i64| {
+            FillEvent::new(
+                Side::Bid,
+                false,
+                0,
+                0,
+                0,
+                maker,
+                0,
+                I80F48::from_num(maker_fee),
+                0,
+                Pubkey::new_unique(),
+                0,
+                I80F48::ZERO,
+                price,
+                quantity,
+            )
+        };
+
+        let maker_a = Pubkey::new_unique();
+        let maker_b = Pubkey::new_unique();
+        let maker_c = Pubkey::new_unique();
+        let fills = vec![
+            fill(maker_a, -0.001, 100, 2),
+            fill(maker_b, -0.001, 99, 5),
+            fill(maker_a, -0.002, 101, 1),
+            fill(maker_c, 0.001, 100, 3),
+        ];
+
+        let mut market = PerpMarket::default_for_tests();
+        market.quote_lot_size = 10;
+        let rebates = aggregate_maker_rebates(&fills, market.quote_lot_size);
+        assert_eq!(rebates.len(), 2);
+        assert!(!rebates.contains_key(&maker_c));
+
+        // the rebates match what executing the fills credits to the makers
+        for maker in [maker_a, maker_b] {
+            let buffer = MangoAccount::default_for_tests().try_to_vec().unwrap();
+            let mut account = MangoAccountValue::from_bytes(&buffer).unwrap();
+            account
+                .ensure_perp_position(market.perp_market_index, 0)
+                .unwrap();
+            for fill in fills.iter().filter(|f| f.maker == maker) {
+                account
+                    .execute_perp_maker(market.perp_market_index, &mut market, fill)
+                    .unwrap();
+            }
+            let settled = account
+                .perp_position(market.perp_market_index)
+                .unwrap()
+                .realized_other_pnl_native;
+            assert_eq!(rebates[&maker], settled);
+        }
+        let expected_a = 10.0 * (200.0 * 0.001 + 101.0 * 0.002);
+        assert!((rebates[&maker_a].to_num::<f64>() - expected_a).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_front_len_for_accounts() {
+        let fill = |maker: Pubkey, taker: Pubkey| {
+            FillEvent::new(
+                Side::Bid,
+                false,
+                0,
+                0,
+                0,
+                maker,
+                0,
+                I80F48::ZERO,
+                0,
+                taker,
+                0,
+                I80F48::ZERO,
+                100,
+                1,
+            )
+        };
+        let out = |owner: Pubkey| OutEvent::new(Side::Ask, 0, 0, 0, owner, 1);
+
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+
+        let mut queue = EventQueue::zeroed();
+        let events: [AnyEvent; 5] = [
+            cast(fill(a, b)),
+            cast(out(a)),
+            cast(fill(c, a)),
+            cast(out(b)),
+            cast(fill(b, a)),
+        ];
+        for event in events {
+            queue.push_back(event).unwrap();
+        }
+
+        assert_eq!(queue.front_len_for_accounts(&[a, b]), 2);
+        assert_eq!(queue.front_len_for_accounts(&[a, b, c]), 5);
+        assert_eq!(queue.front_len_for_accounts(&[a]), 0);
+        assert_eq!(queue.front_len_for_accounts(&[]), 0);
+
+        // consuming the prefix leaves the remaining events in order
+        for _ in 0..queue.front_len_for_accounts(&[a, b]) {
+            queue.pop_front().unwrap();
+        }
+        assert_eq!(queue.len(), 3);
+        let front: &FillEvent = cast_ref(queue.peek_front().unwrap());
+        assert_eq!(front.maker, c);
+        assert_eq!(queue.front_len_for_accounts(&[a, b]), 0);
+        assert_eq!(queue.front_len_for_accounts(&[a, c]), 1);
+    }
+}
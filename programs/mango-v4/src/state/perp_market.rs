@@ -25,6 +25,8 @@ pub struct PerpMarket {
     ///
     /// Currently required to be 0, USDC. In the future settlement
     /// may be allowed to happen in other tokens.
+    ///
+    /// Can only be changed while the market has never been traded on.
     pub settle_token_index: TokenIndex,
 
     /// Index of this perp market. Other data, like the MangoAccount's PerpPosition
@@ -167,7 +169,83 @@ pub struct PerpMarket {
 
     pub positive_pnl_liquidation_fee: I80F48,
 
-    pub reserved: [u8; 1888],
+    /// In native units of settlement token, given to the keeper for each event
+    /// processed by perp_consume_events. Paid out of fees_accrued.
+    pub consume_events_reward: f32,
+
+    /// Time in force in seconds for orders placed without an expiry timestamp.
+    ///
+    /// Zero means such orders never expire.
+    pub default_order_expiry_seconds: u16,
+    pub padding5: [u8; 2],
+
+    /// Circuit breaker: halt order placement when the oracle price moves by more than
+    /// this fraction within circuit_breaker_window_seconds. Zero disables it.
+    pub circuit_breaker_threshold: f32,
+    pub circuit_breaker_window_seconds: u32,
+    /// How long order placement stays halted after the breaker trips
+    pub circuit_breaker_cooldown_seconds: u32,
+    pub padding6: [u8; 4],
+
+    /// Lowest and highest oracle price seen in the current circuit breaker window.
+    /// Zero if no window was started yet.
+    pub circuit_breaker_window_low_price: I80F48,
+    pub circuit_breaker_window_high_price: I80F48,
+    /// Start of the current circuit breaker window
+    pub circuit_breaker_window_start_ts: u64,
+    /// Order placement is halted until this timestamp
+    pub circuit_breaker_halted_until: u64,
+
+    /// Accounts need at least this much equity, in native quote, to place orders.
+    /// Zero disables the check.
+    pub min_equity_to_place_order: u64,
+
+    /// Maker rebate cap: per epoch, makers receive at most this much rebate (in native
+    /// quote) per base lot of open interest. Fills beyond the cap get no rebate.
+    pub maker_rebate_cap_per_open_interest: f32,
+    /// Length of a maker rebate cap epoch. Zero disables the cap.
+    pub maker_rebate_epoch_seconds: u32,
+    pub maker_rebate_epoch_start_ts: u64,
+    /// Maker rebates granted in the current epoch, in native quote
+    pub maker_rebates_paid_in_epoch: f64,
+
+    /// Extra taker fee per unit of price impact: a taker order whose average fill price
+    /// over the consumed book depth is 2% away from the oracle price pays an additional
+    /// 0.02 * factor taker fee. Zero disables it.
+    pub taker_fee_price_impact_factor: f32,
+
+    /// Orders can't be cancelled until they have rested on the book for this long,
+    /// unless the market is halted. Zero disables the check.
+    pub min_order_rest_seconds: u16,
+    pub padding7: [u8; 2],
+
+    /// Order placement is halted while the event queue is filled to at least this
+    /// fraction of its capacity, so fills can't pile up when event consumption lags.
+    /// Zero disables the check.
+    pub event_queue_halt_fraction: f32,
+    pub padding8: [u8; 4],
+
+    /// Orders that can rest on the book must have a price of at least this many lots.
+    /// Zero disables the check.
+    pub min_order_price_lots: i64,
+    /// Orders that can rest on the book must have a price of at most this many lots.
+    /// Zero disables the check.
+    pub max_order_price_lots: i64,
+
+    /// Accumulated fill price over time, in price lots * seconds.
+    ///
+    /// Whenever a fill happens, the previous fill price is added for the time since
+    /// the previous fill. The difference between two observations divided by the time
+    /// between them is the time-weighted average fill price.
+    ///
+    /// Wraps around on overflow: only the difference between two observations matters.
+    pub fill_price_cumulative: I80F48,
+    /// Price of the most recent fill, in lots.
+    pub last_fill_price_lots: i64,
+    /// Timestamp of the most recent fill, zero if there was none yet.
+    pub last_fill_ts: u64,
+
+    pub reserved: [u8; 1720],
 }
 
 const_assert_eq!(
@@ -203,7 +281,24 @@ const_assert_eq!(
         + 1
         + 7
         + 3 * 16
-        + 1888
+        + 4
+        + 2
+        + 2
+        + 4 * 4
+        + 16 * 2
+        + 8 * 2
+        + 8
+        + 4 * 2
+        + 8 * 2
+        + 4
+        + 2
+        + 2
+        + 4
+        + 4
+        + 8 * 2
+        + 16
+        + 8 * 2
+        + 1720
 );
 const_assert_eq!(size_of::<PerpMarket>(), 2808);
 const_assert_eq!(size_of::<PerpMarket>() % 8, 0);
@@ -350,6 +445,169 @@ impl PerpMarket {
         Ok(())
     }
 
+    /// Track oracle price moves and trip the circuit breaker if they are too large.
+    ///
+    /// The oracle price is compared to the lowest and highest price seen in the current
+    /// window, so moves between any two observations in the window are caught.
+    /// A new window starts when the old one has elapsed or when the breaker trips.
+    ///
+    /// Returns true if the breaker tripped in this call.
+    pub fn update_circuit_breaker(&mut self, oracle_price: I80F48, now_ts: u64) -> bool {
+        if self.circuit_breaker_threshold <= 0.0 || !oracle_price.is_positive() {
+            return false;
+        }
+
+        let window_end =
+            self.circuit_breaker_window_start_ts + self.circuit_breaker_window_seconds as u64;
+        if !self.circuit_breaker_window_low_price.is_positive() || now_ts >= window_end {
+            self.start_circuit_breaker_window(oracle_price, now_ts);
+            return false;
+        }
+
+        let low = self.circuit_breaker_window_low_price.min(oracle_price);
+        let high = self.circuit_breaker_window_high_price.max(oracle_price);
+        let threshold = I80F48::from_num(self.circuit_breaker_threshold);
+        if high > low * (I80F48::ONE + threshold) {
+            msg!(
+                "circuit breaker tripped: price {}, window low {}, window high {}",
+                oracle_price,
+                self.circuit_breaker_window_low_price,
+                self.circuit_breaker_window_high_price
+            );
+            self.circuit_breaker_halted_until =
+                now_ts + self.circuit_breaker_cooldown_seconds as u64;
+            self.start_circuit_breaker_window(oracle_price, now_ts);
+            return true;
+        }
+        self.circuit_breaker_window_low_price = low;
+        self.circuit_breaker_window_high_price = high;
+        false
+    }
+
+    fn start_circuit_breaker_window(&mut self, oracle_price: I80F48, now_ts: u64) {
+        self.circuit_breaker_window_low_price = oracle_price;
+        self.circuit_breaker_window_high_price = oracle_price;
+        self.circuit_breaker_window_start_ts = now_ts;
+    }
+
+    /// Forget the current circuit breaker window, the next update starts a new one.
+    pub fn reset_circuit_breaker_window(&mut self) {
+        self.circuit_breaker_window_low_price = I80F48::ZERO;
+        self.circuit_breaker_window_high_price = I80F48::ZERO;
+    }
+
+    pub fn is_circuit_breaker_halted(&self, now_ts: u64) -> bool {
+        now_ts < self.circuit_breaker_halted_until
+    }
+
+    /// Is the event queue, holding `event_queue_len` events, too full to place orders?
+    pub fn is_event_queue_congested(&self, event_queue_len: usize) -> bool {
+        self.event_queue_halt_fraction > 0.0
+            && event_queue_len as f32
+                >= self.event_queue_halt_fraction * orderbook::MAX_NUM_EVENTS as f32
+    }
+
+    /// Is `price_lots` within the market's configured order price range?
+    pub fn is_order_price_allowed(&self, price_lots: i64) -> bool {
+        (self.min_order_price_lots == 0 || price_lots >= self.min_order_price_lots)
+            && (self.max_order_price_lots == 0 || price_lots <= self.max_order_price_lots)
+    }
+
+    /// Advance the fill price accumulator for a fill at `price_lots`.
+    pub fn record_fill_price(&mut self, price_lots: i64, now_ts: u64) {
+        self.fill_price_cumulative = self.fill_price_cumulative_at(now_ts);
+        self.last_fill_price_lots = price_lots;
+        self.last_fill_ts = self.last_fill_ts.max(now_ts);
+    }
+
+    /// The fill price accumulator as of `now_ts`, assuming the last fill price held
+    /// since the last fill.
+    pub fn fill_price_cumulative_at(&self, now_ts: u64) -> I80F48 {
+        if self.last_fill_ts == 0 {
+            return self.fill_price_cumulative;
+        }
+        let elapsed = now_ts.saturating_sub(self.last_fill_ts);
+        let added = I80F48::from(self.last_fill_price_lots).wrapping_mul(I80F48::from(elapsed));
+        self.fill_price_cumulative.wrapping_add(added)
+    }
+
+    /// Time-weighted average fill price in lots since an earlier observation of
+    /// `fill_price_cumulative_at(earlier_ts)`.
+    ///
+    /// Returns None if no time has passed since the observation.
+    pub fn fill_price_twap_lots(
+        &self,
+        earlier_cumulative: I80F48,
+        earlier_ts: u64,
+        now_ts: u64,
+    ) -> Option<I80F48> {
+        if now_ts <= earlier_ts {
+            return None;
+        }
+        let elapsed = I80F48::from(now_ts - earlier_ts);
+        let cumulative = self.fill_price_cumulative_at(now_ts);
+        Some(cumulative.wrapping_sub(earlier_cumulative) / elapsed)
+    }
+
+    /// The timestamp to check order minimum rest times against when cancelling,
+    /// None if cancels are unrestricted because the market is halted.
+    pub fn order_rest_check_ts(&self, now_ts: u64) -> Option<u64> {
+        if self.is_force_close() || self.is_circuit_breaker_halted(now_ts) {
+            None
+        } else {
+            Some(now_ts)
+        }
+    }
+
+    /// The maker fee for a fill of `quote_native`, after applying the maker rebate cap.
+    ///
+    /// A rebate is only granted if it fits into the current epoch's budget and doesn't
+    /// exceed fees_accrued. Otherwise the maker pays no fee and gets no rebate.
+    pub fn maker_fee_for_fill(&mut self, quote_native: I80F48, now_ts: u64) -> I80F48 {
+        if !self.maker_fee.is_negative() || self.maker_rebate_epoch_seconds == 0 {
+            return self.maker_fee;
+        }
+
+        let epoch_end = self.maker_rebate_epoch_start_ts + self.maker_rebate_epoch_seconds as u64;
+        if now_ts >= epoch_end {
+            self.maker_rebate_epoch_start_ts = now_ts;
+            self.maker_rebates_paid_in_epoch = 0.0;
+        }
+
+        let rebate = -quote_native * self.maker_fee;
+        let budget = self.maker_rebate_cap_per_open_interest as f64 * self.open_interest as f64;
+        let rebates_paid = self.maker_rebates_paid_in_epoch + rebate.to_num::<f64>();
+        if rebates_paid > budget || rebate > self.fees_accrued {
+            return I80F48::ZERO;
+        }
+        self.maker_rebates_paid_in_epoch = rebates_paid;
+        self.maker_fee
+    }
+
+    /// The extra taker fee rate for an order that filled at an average of `fill_price_lots`,
+    /// measured against the oracle price.
+    pub fn price_impact_taker_fee(
+        &self,
+        fill_price_lots: I80F48,
+        oracle_price_lots: i64,
+    ) -> I80F48 {
+        if self.taker_fee_price_impact_factor <= 0.0
+            || fill_price_lots <= I80F48::ZERO
+            || oracle_price_lots <= 0
+        {
+            return I80F48::ZERO;
+        }
+        let oracle_price_lots = I80F48::from(oracle_price_lots);
+        let price_impact = (fill_price_lots - oracle_price_lots).abs() / oracle_price_lots;
+        price_impact * I80F48::from_num(self.taker_fee_price_impact_factor)
+    }
+
+    /// The spread between native `best_bid` and `best_ask` including the taker fee on
+    /// both legs: the cost of buying at the ask and immediately selling at the bid.
+    pub fn effective_spread(&self, best_bid: I80F48, best_ask: I80F48) -> I80F48 {
+        best_ask * (I80F48::ONE + self.taker_fee) - best_bid * (I80F48::ONE - self.taker_fee)
+    }
+
     /// Convert from the price stored on the book to the price used in value calculations
     pub fn lot_to_native_price(&self, price: i64) -> I80F48 {
         I80F48::from_num(price) * I80F48::from_num(self.quote_lot_size)
@@ -492,7 +750,77 @@ impl PerpMarket {
             maint_overall_asset_weight: I80F48::ONE,
             init_overall_asset_weight: I80F48::ONE,
             positive_pnl_liquidation_fee: I80F48::ZERO,
-            reserved: [0; 1888],
+            consume_events_reward: 0.0,
+            default_order_expiry_seconds: 0,
+            padding5: Default::default(),
+            circuit_breaker_threshold: 0.0,
+            circuit_breaker_window_seconds: 0,
+            circuit_breaker_cooldown_seconds: 0,
+            padding6: Default::default(),
+            circuit_breaker_window_low_price: I80F48::ZERO,
+            circuit_breaker_window_high_price: I80F48::ZERO,
+            circuit_breaker_window_start_ts: 0,
+            circuit_breaker_halted_until: 0,
+            min_equity_to_place_order: 0,
+            maker_rebate_cap_per_open_interest: 0.0,
+            maker_rebate_epoch_seconds: 0,
+            maker_rebate_epoch_start_ts: 0,
+            maker_rebates_paid_in_epoch: 0.0,
+            taker_fee_price_impact_factor: 0.0,
+            min_order_rest_seconds: 0,
+            padding7: Default::default(),
+            event_queue_halt_fraction: 0.0,
+            padding8: Default::default(),
+            min_order_price_lots: 0,
+            max_order_price_lots: 0,
+            fill_price_cumulative: I80F48::ZERO,
+            last_fill_price_lots: 0,
+            last_fill_ts: 0,
+            reserved: [0; 1720],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_spread() {
+        let mut market = PerpMarket::default_for_tests();
+        let bid = I80F48::from(99);
+        let ask = I80F48::from(101);
+        assert_eq!(market.effective_spread(bid, ask), I80F48::from(2));
+
+        // 101 * 1.01 - 99 * 0.99 = 4
+        market.taker_fee = I80F48::from_num(0.01);
+        let spread = market.effective_spread(bid, ask);
+        assert!((spread - I80F48::from(4)).abs() < I80F48::from_num(0.000001));
+    }
+
+    #[test]
+    fn test_circuit_breaker_window_range() {
+        let mut market = PerpMarket::default_for_tests();
+        market.circuit_breaker_threshold = 0.1;
+        market.circuit_breaker_window_seconds = 100;
+        market.circuit_breaker_cooldown_seconds = 50;
+
+        // each step stays within 10% of the window start, but the range is above 10%
+        assert!(!market.update_circuit_breaker(I80F48::from(100), 1000));
+        assert!(!market.update_circuit_breaker(I80F48::from(95), 1010));
+        assert!(!market.update_circuit_breaker(I80F48::from(104), 1020));
+        assert!(market.update_circuit_breaker(I80F48::from(106), 1030));
+        assert!(market.is_circuit_breaker_halted(1030));
+        assert!(!market.is_circuit_breaker_halted(1080));
+
+        // the trip started a new window at the last price
+        assert_eq!(market.circuit_breaker_window_low_price, I80F48::from(106));
+        assert_eq!(market.circuit_breaker_window_high_price, I80F48::from(106));
+        assert_eq!(market.circuit_breaker_window_start_ts, 1030);
+
+        // an elapsed window forgets the old range
+        assert!(!market.update_circuit_breaker(I80F48::from(100), 1131));
+        assert_eq!(market.circuit_breaker_window_low_price, I80F48::from(100));
+        assert_eq!(market.circuit_breaker_window_start_ts, 1131);
+    }
+}
@@ -27,6 +27,7 @@ mod test_liq_perps_base_and_bankruptcy;
 mod test_liq_perps_force_cancel;
 mod test_liq_perps_positive_pnl;
 mod test_liq_tokens;
+mod test_liquidation_priority;
 mod test_margin_trade;
 mod test_perp;
 mod test_perp_settle;
@@ -34,4 +35,6 @@ mod test_perp_settle_fees;
 mod test_position_lifetime;
 mod test_reduce_only;
 mod test_serum;
+mod test_token_reconcile_vault;
+mod test_token_sweep_fees;
 mod test_token_update_index_and_rate;
@@ -177,6 +177,8 @@ async fn test_bankrupt_tokens_socialize_loss() -> Result<(), TransportError> {
             liab_token_index: borrow_token1.index,
             liab_bank_index: 1,
             max_liab_transfer: I80F48::from_num(100000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
         },
     )
     .await
@@ -201,6 +203,8 @@ async fn test_bankrupt_tokens_socialize_loss() -> Result<(), TransportError> {
             liab_token_index: borrow_token1.index,
             liab_bank_index: 1,
             max_liab_transfer: I80F48::from_num(100000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
         },
     )
     .await
@@ -488,6 +492,8 @@ async fn test_bankrupt_tokens_insurance_fund() -> Result<(), TransportError> {
             liab_token_index: borrow_token2.index,
             liab_bank_index: 1,
             max_liab_transfer: I80F48::from_num(100000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
         },
     )
     .await
@@ -508,6 +514,8 @@ async fn test_bankrupt_tokens_insurance_fund() -> Result<(), TransportError> {
             liab_token_index: borrow_token2.index,
             liab_bank_index: 1,
             max_liab_transfer: I80F48::from_num(100000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
         },
     )
     .await
@@ -589,6 +597,74 @@ async fn test_bankrupt_tokens_insurance_fund() -> Result<(), TransportError> {
         liqor_before + usdc_amount as i64
     );
 
+    // the liquidation fee is clamped to the group maximum
+    send_tx(
+        solana,
+        GroupEdit {
+            group,
+            admin,
+            options: mango_v4::instruction::GroupEdit {
+                max_liquidation_fee_opt: Some(0.01),
+                ..group_edit_instruction_default()
+            },
+        },
+    )
+    .await
+    .unwrap();
+
+    let liab_transfer: f64 = 100.0 * usdc_to_liab;
+    let insurance_vault_before = solana.token_account_balance(insurance_vault).await;
+    send_tx(
+        solana,
+        TokenLiqBankruptcyInstruction {
+            liqee: account,
+            liqor: vault_account,
+            liqor_owner: owner,
+            liab_mint_info: borrow_token2.mint_info,
+            max_liab_transfer: I80F48::from_num(liab_transfer),
+        },
+    )
+    .await
+    .unwrap();
+    let usdc_amount = (liab_transfer / usdc_to_liab * 1.01).ceil() as u64;
+    assert_eq!(
+        solana.token_account_balance(insurance_vault).await,
+        insurance_vault_before - usdc_amount
+    );
+
+    // banks with a flat liquidation fee pay no fee in bankruptcy
+    send_tx(
+        solana,
+        TokenEditLiquidationFee {
+            group,
+            admin,
+            mint: borrow_token2.mint.pubkey,
+            liquidation_fee_mode: 1,
+            flat_liquidation_fee_quote: 5.0,
+        },
+    )
+    .await
+    .unwrap();
+
+    let insurance_vault_before = solana.token_account_balance(insurance_vault).await;
+    send_tx(
+        solana,
+        TokenLiqBankruptcyInstruction {
+            liqee: account,
+            liqor: vault_account,
+            liqor_owner: owner,
+            liab_mint_info: borrow_token2.mint_info,
+            max_liab_transfer: I80F48::from_num(liab_transfer),
+        },
+    )
+    .await
+    .unwrap();
+    let usdc_amount = (liab_transfer / usdc_to_liab).ceil() as u64;
+    assert_eq!(
+        solana.token_account_balance(insurance_vault).await,
+        insurance_vault_before - usdc_amount
+    );
+
     // bankruptcy of a non-USDC liability: USDC to liqor, liability to liqee
     // liquidating fully and then doing socialized loss because the insurance fund is exhausted
     let insurance_vault_before = solana.token_account_balance(insurance_vault).await;
@@ -130,6 +130,7 @@ async fn test_fees_buyback_with_mngo() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -178,6 +178,7 @@ async fn test_liq_perps_bankruptcy() -> Result<(), TransportError> {
         tx.add_instruction(PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account, helper_account],
+            keeper: None,
         })
         .await;
         tx.send().await.unwrap();
@@ -207,6 +208,7 @@ async fn test_liq_perps_bankruptcy() -> Result<(), TransportError> {
         tx.add_instruction(PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account, helper_account],
+            keeper: None,
         })
         .await;
         tx.send().await.unwrap();
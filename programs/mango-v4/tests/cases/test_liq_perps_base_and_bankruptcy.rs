@@ -164,6 +164,7 @@ async fn test_liq_perps_base_and_bankruptcy() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -162,3 +162,145 @@ async fn test_liq_perps_force_cancel() -> Result<(), TransportError> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_perp_force_cancel_reduce_only_market() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, a perp market and an account with a resting order
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+    let base_token = &tokens[1];
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.8,
+            init_base_asset_weight: 0.6,
+            maint_base_liab_weight: 1.2,
+            init_base_liab_weight: 1.4,
+            base_liquidation_fee: 0.05,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, base_token).await
+        },
+    )
+    .await
+    .unwrap();
+
+    let price_lots = {
+        let perp_market = solana.get_account::<PerpMarket>(perp_market).await;
+        perp_market.native_price_to_lot(I80F48::ONE)
+    };
+
+    let account = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        &mints[0..1],
+        1000,
+        0,
+    )
+    .await;
+
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots,
+            max_base_lots: 1,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let open_orders = |account: MangoAccountValue| {
+        account
+            .all_perp_orders()
+            .filter(|oo| oo.is_active_for_market(0))
+            .count()
+    };
+
+    //
+    // TEST: A healthy account's orders can't be cancelled by others on a normal market
+    //
+    send_tx(
+        solana,
+        PerpLiqForceCancelOrdersInstruction {
+            account,
+            perp_market,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(open_orders(get_mango_account(solana, account).await), 1);
+
+    //
+    // TEST: Once the market is reduce only anyone can cancel resting orders
+    //
+    send_tx(
+        solana,
+        PerpMakeReduceOnly {
+            group,
+            admin,
+            perp_market,
+            reduce_only: true,
+            force_close: false,
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpLiqForceCancelOrdersInstruction {
+            account,
+            perp_market,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(open_orders(get_mango_account(solana, account).await), 0);
+
+    let log = solana
+        .program_log_events::<mango_v4::logs::PerpForceCancelOrdersLog>()
+        .pop()
+        .unwrap();
+    assert_eq!(log.mango_account, account);
+    assert_eq!(log.orders_cancelled, 1);
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let bids = solana
+        .get_account_boxed::<BookSide>(perp_market_data.bids)
+        .await;
+    assert_eq!(bids.roots[0].leaf_count, 0);
+
+    Ok(())
+}
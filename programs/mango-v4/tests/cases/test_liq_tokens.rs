@@ -312,9 +312,23 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
     set_bank_stub_oracle_price(solana, group, borrow_token1, admin, 2.0).await;
 
     //
-    // TEST: liquidate borrow2 against too little collateral2
+    // TEST: liquidate borrow2 against too little collateral2, with batched balance logs
     //
 
+    send_tx(
+        solana,
+        GroupEdit {
+            group,
+            admin,
+            options: mango_v4::instruction::GroupEdit {
+                batch_token_balance_logs_opt: Some(true),
+                ..group_edit_instruction_default()
+            },
+        },
+    )
+    .await
+    .unwrap();
+
     send_tx(
         solana,
         TokenLiqWithTokenInstruction {
@@ -326,6 +340,8 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
             asset_bank_index: 0,
             liab_bank_index: 0,
             max_liab_transfer: I80F48::from_num(10000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
         },
     )
     .await
@@ -337,6 +353,44 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
         -50 + 20
     );
     assert!(account_position_closed(solana, account, collateral_token2.bank).await,);
+
+    // the liqee and liqor balance changes come in a single log
+    assert!(solana
+        .program_log_events::<mango_v4::logs::TokenBalanceLog>()
+        .is_empty());
+    let batch_log = solana
+        .program_log_events::<mango_v4::logs::TokenBalanceBatchLog>()
+        .pop()
+        .unwrap();
+    let balances = batch_log
+        .balances
+        .iter()
+        .map(|b| (b.mango_account, b.token_index))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        balances,
+        vec![
+            (account, collateral_token2.index),
+            (account, borrow_token2.index),
+            (vault_account, collateral_token2.index),
+            (vault_account, borrow_token2.index),
+        ]
+    );
+    assert_eq!(batch_log.balances[0].indexed_position, 0);
+
+    send_tx(
+        solana,
+        GroupEdit {
+            group,
+            admin,
+            options: mango_v4::instruction::GroupEdit {
+                batch_token_balance_logs_opt: Some(false),
+                ..group_edit_instruction_default()
+            },
+        },
+    )
+    .await
+    .unwrap();
     let liqee = get_mango_account(solana, account).await;
     assert!(liqee.being_liquidated());
 
@@ -353,6 +407,8 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
             asset_token_index: collateral_token1.index,
             liab_token_index: borrow_token2.index,
             max_liab_transfer: I80F48::from_num(10000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
             asset_bank_index: 0,
             liab_bank_index: 0,
         },
@@ -381,6 +437,8 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
             asset_token_index: collateral_token1.index,
             liab_token_index: borrow_token1.index,
             max_liab_transfer: I80F48::from_num(10.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
             asset_bank_index: 0,
             liab_bank_index: 0,
         },
@@ -412,6 +470,8 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
             asset_token_index: collateral_token1.index,
             liab_token_index: borrow_token1.index,
             max_liab_transfer: I80F48::from_num(10000.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
             asset_bank_index: 0,
             liab_bank_index: 0,
         },
@@ -467,6 +527,8 @@ async fn test_liq_tokens_with_token() -> Result<(), TransportError> {
             asset_token_index: collateral_token1.index,
             liab_token_index: borrow_token1.index,
             max_liab_transfer: I80F48::from_num(10001.0),
+            reject_liqor_asset_netting: false,
+            dust_liqor_asset: false,
             asset_bank_index: 0,
             liab_bank_index: 0,
         },
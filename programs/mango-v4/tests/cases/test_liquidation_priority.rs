@@ -0,0 +1,82 @@
+use super::*;
+
+#[tokio::test]
+async fn test_liquidation_priority() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group and an account to fill the vaults
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    //
+    // SETUP: Three accounts that deposit token0 and borrow different amounts of token1
+    //
+    let mut accounts = vec![];
+    for (account_num, borrow) in [(1, 100), (2, 300), (3, 400)] {
+        let account = create_funded_account(
+            &solana,
+            group,
+            owner,
+            account_num,
+            &context.users[1],
+            &mints[0..1],
+            1000,
+            0,
+        )
+        .await;
+        send_tx(
+            solana,
+            TokenWithdrawInstruction {
+                amount: borrow,
+                allow_borrow: true,
+                account,
+                owner,
+                token_account: context.users[1].token_accounts[1],
+                bank_index: 0,
+            },
+        )
+        .await
+        .unwrap();
+        accounts.push(account);
+    }
+
+    //
+    // TEST: Healthy accounts have a score of zero
+    //
+    for &account in accounts.iter() {
+        assert_eq!(account_liquidation_priority(solana, account).await, 0.0);
+    }
+
+    //
+    // TEST: After a price change the most unhealthy account has the highest score
+    //
+    set_bank_stub_oracle_price(solana, group, &tokens[1], admin, 2.0).await;
+
+    // init health is 1000 * 0.6 - borrow * 2 * 1.4, ignoring loan origination fees
+    let low = account_liquidation_priority(solana, accounts[0]).await;
+    let mid = account_liquidation_priority(solana, accounts[1]).await;
+    let high = account_liquidation_priority(solana, accounts[2]).await;
+    assert_eq!(low, 0.0);
+    assert!((mid - 240.0).abs() < 1.0);
+    assert!((high - 520.0).abs() < 1.0);
+    assert_eq!(mid, -account_init_health(solana, accounts[1]).await);
+
+    Ok(())
+}
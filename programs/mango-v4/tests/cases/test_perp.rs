@@ -278,6 +278,7 @@ async fn test_perp_fixed() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -356,6 +357,7 @@ async fn test_perp_fixed() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -636,6 +638,7 @@ async fn test_perp_oracle_peg() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -727,6 +730,7 @@ async fn test_perp_oracle_peg() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -818,6 +822,7 @@ async fn test_perp_oracle_peg() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -945,6 +950,7 @@ async fn test_perp_realize_partially() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -999,6 +1005,7 @@ async fn test_perp_realize_partially() -> Result<(), TransportError> {
         PerpConsumeEventsInstruction {
             perp_market,
             mango_accounts: vec![account_0, account_1],
+            keeper: None,
         },
     )
     .await
@@ -1035,6 +1042,271 @@ async fn test_perp_realize_partially() -> Result<(), TransportError> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_perp_consume_events_reward() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, two trading accounts and a keeper account
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let deposit_amount = 1000;
+    let account_0 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        mints,
+        deposit_amount,
+        0,
+    )
+    .await;
+    let account_1 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        1,
+        &context.users[1],
+        mints,
+        deposit_amount,
+        0,
+    )
+    .await;
+    let keeper =
+        create_funded_account(&solana, group, owner, 2, &context.users[1], &[], 0, 0).await;
+
+    //
+    // SETUP: Create a perp market with a taker fee and a keeper reward
+    //
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.01,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpSetConsumeEventsReward {
+            group,
+            admin,
+            perp_market,
+            consume_events_reward: 5.0,
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::from(1000));
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1000.0).await;
+
+    let trade = |side: Side, account: Pubkey| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side,
+        price_lots,
+        max_base_lots: 1,
+        ..PerpPlaceOrderInstruction::default()
+    };
+
+    //
+    // TEST: Consuming a fill event pays the reward to the keeper
+    //
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+    send_tx(solana, trade(Side::Ask, account_1)).await.unwrap();
+
+    // 1 lot at price 1000 is 100_000 native quote, taker fee is 1%
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(assert_equal(perp_market_data.fees_accrued, 1000.0, 0.001));
+
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: Some((keeper, owner)),
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(assert_equal(perp_market_data.fees_accrued, 995.0, 0.001));
+    let keeper_data = solana.get_account::<MangoAccount>(keeper).await;
+    assert!(assert_equal(
+        keeper_data.perps[0].quote_position_native(),
+        5.0,
+        0.001
+    ));
+
+    //
+    // TEST: The reward is limited by fees_accrued
+    //
+    send_tx(
+        solana,
+        PerpSetConsumeEventsReward {
+            group,
+            admin,
+            perp_market,
+            consume_events_reward: 2000.0,
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+    send_tx(solana, trade(Side::Ask, account_1)).await.unwrap();
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: Some((keeper, owner)),
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(assert_equal(perp_market_data.fees_accrued, 0.0, 0.001));
+    let keeper_data = solana.get_account::<MangoAccount>(keeper).await;
+    assert!(assert_equal(
+        keeper_data.perps[0].quote_position_native(),
+        2000.0,
+        0.001
+    ));
+
+    //
+    // TEST: A keeper without room for a perp position doesn't block consuming events
+    //
+    let keeper_without_perps = send_tx(
+        solana,
+        AccountCreateInstruction {
+            account_num: 3,
+            token_count: 8,
+            serum3_count: 0,
+            perp_count: 0,
+            perp_oo_count: 0,
+            group,
+            owner,
+            payer,
+        },
+    )
+    .await
+    .unwrap()
+    .account;
+
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+    send_tx(solana, trade(Side::Ask, account_1)).await.unwrap();
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: Some((keeper_without_perps, owner)),
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(assert_equal(perp_market_data.fees_accrued, 1000.0, 0.001));
+    let event_queue = solana
+        .get_account_boxed::<EventQueue>(perp_market_data.event_queue)
+        .await;
+    assert_eq!(event_queue.len(), 0);
+
+    //
+    // TEST: Events consumed before a missing account are still rewarded
+    //
+    send_tx(
+        solana,
+        PerpSetConsumeEventsReward {
+            group,
+            admin,
+            perp_market,
+            consume_events_reward: 5.0,
+        },
+    )
+    .await
+    .unwrap();
+
+    let account_2 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        4,
+        &context.users[1],
+        mints,
+        deposit_amount,
+        0,
+    )
+    .await;
+
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+    send_tx(solana, trade(Side::Ask, account_1)).await.unwrap();
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+    send_tx(solana, trade(Side::Ask, account_2)).await.unwrap();
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: Some((keeper, owner)),
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(assert_equal(perp_market_data.fees_accrued, 2995.0, 0.001));
+    let keeper_data = solana.get_account::<MangoAccount>(keeper).await;
+    assert!(assert_equal(
+        keeper_data.perps[0].quote_position_native(),
+        2005.0,
+        0.001
+    ));
+    let event_queue = solana
+        .get_account_boxed::<EventQueue>(perp_market_data.event_queue)
+        .await;
+    assert_eq!(event_queue.len(), 1);
+
+    Ok(())
+}
+
 async fn assert_no_perp_orders(solana: &SolanaCookie, account_0: Pubkey) {
     let mango_account_0 = solana.get_account::<MangoAccount>(account_0).await;
 
@@ -1045,3 +1317,1859 @@ async fn assert_no_perp_orders(solana: &SolanaCookie, account_0: Pubkey) {
         assert!(oo.market == FREE_ORDER_SLOT);
     }
 }
+
+#[tokio::test]
+async fn test_perp_default_order_expiry() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group and two trading accounts
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let deposit_amount = 1000;
+    let account_0 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        mints,
+        deposit_amount,
+        0,
+    )
+    .await;
+    let account_1 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        1,
+        &context.users[1],
+        mints,
+        deposit_amount,
+        0,
+    )
+    .await;
+
+    //
+    // SETUP: Create a perp market with a default order expiry
+    //
+    let mango_v4::accounts::PerpCreateMarket {
+        perp_market,
+        bids,
+        asks,
+        ..
+    } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpSetDefaultOrderExpiry {
+            group,
+            admin,
+            perp_market,
+            default_order_expiry_seconds: 10,
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::from(1000));
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1000.0).await;
+
+    let trade = |side: Side, account: Pubkey| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side,
+        price_lots,
+        max_base_lots: 1,
+        ..PerpPlaceOrderInstruction::default()
+    };
+
+    //
+    // TEST: An order without expiry gets the default time in force
+    //
+    send_tx(solana, trade(Side::Bid, account_0)).await.unwrap();
+
+    let bids_data = solana.get_account_boxed::<BookSide>(bids).await;
+    let orders = bids_data
+        .iter_all_including_invalid(0, price_lots)
+        .collect::<Vec<_>>();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].node.time_in_force, 10);
+    let order_ts = orders[0].node.timestamp;
+
+    //
+    // TEST: After the default window the order no longer matches
+    //
+    solana.advance_clock_to(order_ts as i64 + 11).await;
+    send_tx(solana, trade(Side::Ask, account_1)).await.unwrap();
+
+    let mango_account_1 = solana.get_account::<MangoAccount>(account_1).await;
+    assert_eq!(mango_account_1.perps[0].base_position_lots(), 0);
+
+    let bids_data = solana.get_account_boxed::<BookSide>(bids).await;
+    assert_eq!(bids_data.roots[0].leaf_count, 0);
+    let asks_data = solana.get_account_boxed::<BookSide>(asks).await;
+    assert_eq!(asks_data.roots[0].leaf_count, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_log_spread() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let oracle_price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+
+    let spread = || async {
+        send_tx(solana, PerpLogSpreadInstruction { perp_market })
+            .await
+            .unwrap();
+        solana
+            .program_log_events::<mango_v4::logs::PerpSpreadLog>()
+            .pop()
+            .unwrap()
+    };
+
+    //
+    // TEST: An empty book has no spread
+    //
+    let log = spread().await;
+    assert_eq!(log.best_bid_lots, None);
+    assert_eq!(log.best_ask_lots, None);
+    assert_eq!(log.mid_price, None);
+    assert_eq!(log.spread_lots, None);
+
+    //
+    // TEST: A fixed bid and a pegged ask resolve against the same oracle price
+    //
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots: oracle_price_lots - 2,
+            max_base_lots: 1,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+    send_tx(
+        solana,
+        PerpPlaceOrderPeggedInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Ask,
+            price_offset: 4,
+            max_base_lots: 1,
+            max_quote_lots: i64::MAX,
+            client_order_id: 0,
+            peg_limit: -1,
+        },
+    )
+    .await
+    .unwrap();
+
+    let log = spread().await;
+    assert_eq!(log.best_bid_lots, Some(oracle_price_lots - 2));
+    assert_eq!(log.best_ask_lots, Some(oracle_price_lots + 4));
+    assert_eq!(log.spread_lots, Some(6));
+    let mid_price = I80F48::from_bits(log.mid_price.unwrap());
+    let expected_mid_price = perp_market_data.lot_to_native_price(oracle_price_lots + 1);
+    assert!(assert_equal(
+        mid_price,
+        expected_mid_price.to_num::<f64>(),
+        0.000001
+    ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_orderbook_view() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        mints,
+        100000,
+        0,
+    )
+    .await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let oracle_price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+    let impact_quantity = perp_market_data.impact_quantity;
+
+    let view = || async {
+        send_tx(solana, PerpOrderbookViewInstruction { perp_market })
+            .await
+            .unwrap();
+        solana
+            .program_log_events::<mango_v4::logs::PerpOrderbookViewLog>()
+            .pop()
+            .unwrap()
+    };
+    let place = |side: Side, price_lots: i64, max_base_lots: i64| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side,
+        price_lots,
+        max_base_lots,
+        ..PerpPlaceOrderInstruction::default()
+    };
+    let set_expiry = |default_order_expiry_seconds: u16| PerpSetDefaultOrderExpiry {
+        group,
+        admin,
+        perp_market,
+        default_order_expiry_seconds,
+    };
+
+    //
+    // TEST: An empty book logs no prices
+    //
+    let log = view().await;
+    assert_eq!(log.impact_quantity, impact_quantity);
+    assert_eq!(log.best_bid_lots, None);
+    assert_eq!(log.best_ask_lots, None);
+    assert_eq!(log.bid_impact_price_lots, None);
+    assert_eq!(log.ask_impact_price_lots, None);
+
+    //
+    // SETUP: A short-lived bid at the top, two deeper bids and a thin ask
+    //
+    send_tx(solana, set_expiry(10)).await.unwrap();
+    send_tx(
+        solana,
+        place(Side::Bid, oracle_price_lots - 1, impact_quantity),
+    )
+    .await
+    .unwrap();
+    send_tx(solana, set_expiry(0)).await.unwrap();
+    send_tx(solana, place(Side::Bid, oracle_price_lots - 3, 60))
+        .await
+        .unwrap();
+    send_tx(solana, place(Side::Bid, oracle_price_lots - 4, 60))
+        .await
+        .unwrap();
+    send_tx(solana, place(Side::Ask, oracle_price_lots + 2, 10))
+        .await
+        .unwrap();
+
+    let log = view().await;
+    assert_eq!(log.best_bid_lots, Some(oracle_price_lots - 1));
+    assert_eq!(log.best_ask_lots, Some(oracle_price_lots + 2));
+    assert_eq!(
+        I80F48::from_bits(log.bid_impact_price_lots.unwrap()),
+        I80F48::from(oracle_price_lots - 1)
+    );
+    assert_eq!(log.ask_impact_price_lots, None);
+
+    //
+    // TEST: Once the top bid expired, it's skipped
+    //
+    let bids_data = solana
+        .get_account_boxed::<BookSide>(perp_market_data.bids)
+        .await;
+    let expiring_order_ts = bids_data
+        .iter_all_including_invalid(0, oracle_price_lots)
+        .next()
+        .unwrap()
+        .node
+        .timestamp;
+    solana.advance_clock_to(expiring_order_ts as i64 + 11).await;
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+
+    let log = view().await;
+    assert_eq!(log.best_bid_lots, Some(oracle_price_lots - 3));
+    // 60 lots at the best remaining bid and 40 at the next one
+    let expected_bid_impact_price = (I80F48::from(60 * (oracle_price_lots - 3))
+        + I80F48::from(40 * (oracle_price_lots - 4)))
+        / I80F48::from(100);
+    assert_eq!(impact_quantity, 100);
+    assert_eq!(
+        I80F48::from_bits(log.bid_impact_price_lots.unwrap()),
+        expected_bid_impact_price
+    );
+    assert_eq!(log.ask_impact_price_lots, None);
+
+    Ok(())
+}
+
+/// Decodes perp_order_snapshots return data field by field, independent of the
+/// borsh derive on OrderSnapshot
+fn decode_order_snapshots(bytes: &[u8]) -> Vec<OrderSnapshot> {
+    let mut offset = 0;
+    let mut take = |len: usize| {
+        let field = &bytes[offset..offset + len];
+        offset += len;
+        field
+    };
+    let len = u32::from_le_bytes(take(4).try_into().unwrap());
+    let snapshots = (0..len)
+        .map(|_| OrderSnapshot {
+            side: Side::try_from(take(1)[0]).unwrap(),
+            order_tree: BookSideOrderTree::try_from(take(1)[0]).unwrap(),
+            is_valid: take(1)[0] == 1,
+            key: u128::from_le_bytes(take(16).try_into().unwrap()),
+            owner: Pubkey::try_from(take(32)).unwrap(),
+            owner_slot: take(1)[0],
+            price_lots: i64::from_le_bytes(take(8).try_into().unwrap()),
+            quantity: i64::from_le_bytes(take(8).try_into().unwrap()),
+            timestamp: u64::from_le_bytes(take(8).try_into().unwrap()),
+            time_in_force: u16::from_le_bytes(take(2).try_into().unwrap()),
+            peg_limit: i64::from_le_bytes(take(8).try_into().unwrap()),
+            client_order_id: u64::from_le_bytes(take(8).try_into().unwrap()),
+        })
+        .collect();
+    assert_eq!(offset, bytes.len());
+    snapshots
+}
+
+#[tokio::test]
+async fn test_perp_order_snapshots() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        mints,
+        100000,
+        0,
+    )
+    .await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 10.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let oracle_price_lots = perp_market_data.native_price_to_lot(I80F48::from(10));
+
+    let snapshots = |side: Side, start_index: u32| async move {
+        let bytes = simulate_tx_return_data(
+            solana,
+            PerpOrderSnapshotsInstruction {
+                perp_market,
+                side,
+                start_index,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(bytes.len() <= 1024);
+        let decoded = decode_order_snapshots(&bytes);
+        let borsh_decoded: Vec<OrderSnapshot> =
+            anchor_lang::AnchorDeserialize::try_from_slice(&bytes).unwrap();
+        assert_eq!(borsh_decoded, decoded);
+        decoded
+    };
+
+    //
+    // TEST: An empty book returns no orders
+    //
+    assert!(snapshots(Side::Bid, 0).await.is_empty());
+
+    //
+    // SETUP: More bids than fit in one page, a fixed and an oracle pegged ask
+    //
+    let bid_count = ORDER_SNAPSHOTS_PER_PAGE as i64 + 2;
+    for i in 1..=bid_count {
+        send_tx(
+            solana,
+            PerpPlaceOrderInstruction {
+                account,
+                perp_market,
+                owner,
+                side: Side::Bid,
+                price_lots: oracle_price_lots - i,
+                max_base_lots: 1,
+                client_order_id: i as u64,
+                ..PerpPlaceOrderInstruction::default()
+            },
+        )
+        .await
+        .unwrap();
+    }
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Ask,
+            price_lots: oracle_price_lots + 3,
+            max_base_lots: 1,
+            client_order_id: 100,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+    send_tx(
+        solana,
+        PerpPlaceOrderPeggedInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Ask,
+            price_offset: 5,
+            peg_limit: -1,
+            max_base_lots: 2,
+            max_quote_lots: i64::MAX,
+            client_order_id: 101,
+        },
+    )
+    .await
+    .unwrap();
+
+    //
+    // TEST: Paging through the bids returns the whole side in priority order
+    //
+    let first_page = snapshots(Side::Bid, 0).await;
+    assert_eq!(first_page.len(), ORDER_SNAPSHOTS_PER_PAGE);
+    let second_page = snapshots(Side::Bid, ORDER_SNAPSHOTS_PER_PAGE as u32).await;
+    assert_eq!(second_page.len(), 2);
+    assert!(snapshots(Side::Bid, bid_count as u32).await.is_empty());
+
+    let bids: Vec<OrderSnapshot> = first_page.into_iter().chain(second_page).collect();
+    assert_eq!(
+        bids.iter().map(|o| o.client_order_id).collect::<Vec<_>>(),
+        (1..=bid_count as u64).collect::<Vec<_>>()
+    );
+    assert!(bids
+        .iter()
+        .all(|o| o.side == Side::Bid && o.is_valid && o.owner == account));
+    assert_eq!(bids[0].price_lots, oracle_price_lots - 1);
+
+    // the pages match the snapshot built from the book side account
+    let now_ts = solana.get_clock().await.unix_timestamp as u64;
+    let bids_data = solana
+        .get_account_boxed::<BookSide>(perp_market_data.bids)
+        .await;
+    assert_eq!(bids, bids_data.order_snapshots(now_ts, oracle_price_lots));
+
+    // repeated calls give the same result
+    assert_eq!(
+        snapshots(Side::Bid, 0).await,
+        bids[..ORDER_SNAPSHOTS_PER_PAGE]
+    );
+
+    //
+    // TEST: Oracle pegged asks are returned at their current price
+    //
+    let asks = snapshots(Side::Ask, 0).await;
+    assert_eq!(
+        asks.iter().map(|o| o.client_order_id).collect::<Vec<_>>(),
+        vec![100, 101]
+    );
+    assert_eq!(asks[0].order_tree, BookSideOrderTree::Fixed);
+    assert_eq!(asks[0].price_lots, oracle_price_lots + 3);
+    assert_eq!(asks[1].order_tree, BookSideOrderTree::OraclePegged);
+    assert_eq!(asks[1].price_lots, oracle_price_lots + 5);
+    assert_eq!(asks[1].quantity, 2);
+    assert_eq!(asks[1].peg_limit, -1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_circuit_breaker() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market with a circuit breaker
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpSetCircuitBreaker {
+            group,
+            admin,
+            perp_market,
+            threshold: 0.1,
+            window_seconds: 60,
+            cooldown_seconds: 30,
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+
+    let update_funding = || PerpUpdateFundingInstruction {
+        perp_market,
+        bank: tokens[1].bank,
+        oracle: tokens[1].oracle,
+    };
+    let place_bid = |client_order_id: u64| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side: Side::Bid,
+        price_lots,
+        max_base_lots: 1,
+        client_order_id,
+        ..PerpPlaceOrderInstruction::default()
+    };
+
+    //
+    // TEST: Small price moves don't halt order placement
+    //
+    send_tx(solana, update_funding()).await.unwrap();
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.05).await;
+    send_tx(solana, update_funding()).await.unwrap();
+    send_tx(solana, place_bid(1)).await.unwrap();
+
+    //
+    // TEST: A large price move halts order placement
+    //
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.5).await;
+    send_tx(solana, update_funding()).await.unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(perp_market_data.circuit_breaker_halted_until > 0);
+
+    let result = send_tx(solana, place_bid(2)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpCircuitBreakerHalted.into(),
+        "order placement halted".to_string(),
+    );
+
+    // cancels still work during the halt
+    send_tx(
+        solana,
+        PerpCancelAllOrdersInstruction {
+            account,
+            perp_market,
+            owner,
+        },
+    )
+    .await
+    .unwrap();
+
+    //
+    // TEST: Order placement resumes after the cooldown
+    //
+    solana
+        .advance_clock_to(perp_market_data.circuit_breaker_halted_until as i64)
+        .await;
+    send_tx(solana, place_bid(3)).await.unwrap();
+
+    //
+    // TEST: Order placement can trip the breaker itself, the halt is persisted
+    //
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 2.0).await;
+
+    // signers that aren't owner or delegate are rejected before the breaker is updated
+    let result = send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            owner: TestKeypair::new(),
+            ..place_bid(4)
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::SomeError.into(),
+        "not owner or delegate".to_string(),
+    );
+
+    send_tx(solana, place_bid(4)).await.unwrap();
+
+    let now_ts = solana.get_clock().await.unix_timestamp as u64;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(perp_market_data.is_circuit_breaker_halted(now_ts));
+    let mango_account = get_mango_account(solana, account).await;
+    assert_eq!(mango_account.perps[0].bids_base_lots, 1);
+
+    //
+    // TEST: Placement stays halted when the oracle moves back inside the threshold
+    //
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.95).await;
+    let result = send_tx(solana, place_bid(5)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpCircuitBreakerHalted.into(),
+        "order placement halted after trip".to_string(),
+    );
+
+    solana
+        .advance_clock_to(perp_market_data.circuit_breaker_halted_until as i64)
+        .await;
+    send_tx(solana, place_bid(6)).await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_min_equity_to_place_order() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, a tiny and a funded account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let tiny_account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10, 0).await;
+    let funded_account =
+        create_funded_account(&solana, group, owner, 1, &context.users[1], mints, 1000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpSetMinEquityToPlaceOrder {
+            group,
+            admin,
+            perp_market,
+            min_equity_to_place_order: 1000,
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::from_num(0.5));
+
+    let place_bid = |account: Pubkey| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side: Side::Bid,
+        price_lots,
+        max_base_lots: 1,
+        ..PerpPlaceOrderInstruction::default()
+    };
+
+    //
+    // TEST: An account with equity 2 * 10 can't place orders
+    //
+    let result = send_tx(solana, place_bid(tiny_account)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpOrderEquityTooLow.into(),
+        "equity too low".to_string(),
+    );
+
+    //
+    // TEST: The minimum also applies inside a health region
+    //
+    let result = {
+        let mut tx = ClientTransaction::new(solana);
+        tx.add_instruction(HealthRegionBeginInstruction {
+            account: tiny_account,
+        })
+        .await;
+        tx.add_instruction(place_bid(tiny_account)).await;
+        tx.add_instruction(HealthRegionEndInstruction {
+            account: tiny_account,
+            affected_bank: None,
+        })
+        .await;
+        tx.send().await.map_err(TransportError::from)
+    };
+    assert_mango_error(
+        &result,
+        MangoError::PerpOrderEquityTooLow.into(),
+        "equity too low in health region".to_string(),
+    );
+
+    //
+    // TEST: An account with equity 2 * 1000 can
+    //
+    send_tx(solana, place_bid(funded_account)).await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_oracle_type_switch() -> Result<(), TransportError> {
+    let mut test_builder = TestContextBuilder::new();
+
+    // A pyth price account fixture, placed at an unused address
+    let pyth_oracle = Pubkey::new_unique();
+    let pyth_data = read_file(
+        find_file("resources/test/J83w4HKfqxwcq3BEMMkPFSppX3gqekLyLJBexebFVkix.bin").unwrap(),
+    );
+    test_builder.test().add_account(
+        pyth_oracle,
+        solana_sdk::account::Account {
+            lamports: u32::MAX as u64,
+            data: pyth_data.clone(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    let context = test_builder.start_default().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market with a stub oracle
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let stub_oracle = perp_market_data.oracle;
+
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots: perp_market_data.native_price_to_lot(I80F48::ONE),
+            max_base_lots: 1,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+    let stub_health = account_init_health(solana, account).await;
+
+    //
+    // TEST: The oracle account must parse as the claimed type
+    //
+    let result = send_tx(
+        solana,
+        PerpSetOracle {
+            group,
+            admin,
+            perp_market,
+            oracle: stub_oracle,
+            oracle_type: OracleType::Pyth,
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::OracleTypeMismatch.into(),
+        "stub oracle is not pyth".to_string(),
+    );
+
+    let result = send_tx(
+        solana,
+        PerpSetOracle {
+            group,
+            admin,
+            perp_market,
+            oracle: pyth_oracle,
+            oracle_type: OracleType::SwitchboardV2,
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::OracleTypeMismatch.into(),
+        "pyth oracle is not switchboard".to_string(),
+    );
+
+    //
+    // TEST: Switching to pyth makes prices and health read from the new oracle
+    //
+    send_tx(
+        solana,
+        PerpSetOracle {
+            group,
+            admin,
+            perp_market,
+            oracle: pyth_oracle,
+            oracle_type: OracleType::Pyth,
+        },
+    )
+    .await
+    .unwrap();
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert_eq!(perp_market_data.oracle, pyth_oracle);
+
+    let price_account = pyth_sdk_solana::state::load_price_account(&pyth_data).unwrap();
+    let decimals = price_account.expo + 6 - perp_market_data.base_decimals as i32;
+    let expected_price = price_account.to_price().price as f64 * 10f64.powi(decimals);
+
+    send_tx(solana, PerpLogSpreadInstruction { perp_market })
+        .await
+        .unwrap();
+    let log = solana
+        .program_log_events::<mango_v4::logs::PerpSpreadLog>()
+        .pop()
+        .unwrap();
+    assert!(assert_equal(
+        I80F48::from_bits(log.oracle_price),
+        expected_price,
+        expected_price * 0.000001
+    ));
+
+    let pyth_health = account_init_health(solana, account).await;
+    assert!(pyth_health != stub_health);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_settle_token_change() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+
+    let set_settle_token = |settle_token_index| {
+        send_tx(
+            solana,
+            PerpSetSettleToken {
+                group,
+                admin,
+                perp_market,
+                settle_token_index,
+            },
+        )
+    };
+
+    //
+    // TEST: The settle token of an unused market can be changed
+    //
+    set_settle_token(tokens[1].index).await.unwrap();
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert_eq!(perp_market_data.settle_token_index, tokens[1].index);
+
+    set_settle_token(tokens[0].index).await.unwrap();
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert_eq!(perp_market_data.settle_token_index, tokens[0].index);
+
+    //
+    // TEST: The settle token must exist in the group
+    //
+    let result = set_settle_token(99).await;
+    assert_mango_error(
+        &result,
+        MangoError::InvalidBank.into(),
+        "settle token without bank".to_string(),
+    );
+
+    //
+    // TEST: Once an order was placed, the settle token can't change anymore
+    //
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots: perp_market_data.native_price_to_lot(I80F48::ONE),
+            max_base_lots: 1,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let result = set_settle_token(tokens[1].index).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpSettleTokenChangeNotAllowed.into(),
+        "market has an open order".to_string(),
+    );
+
+    // even after the order is gone
+    send_tx(
+        solana,
+        PerpCancelAllOrdersInstruction {
+            account,
+            perp_market,
+            owner,
+        },
+    )
+    .await
+    .unwrap();
+    let result = set_settle_token(tokens[1].index).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpSettleTokenChangeNotAllowed.into(),
+        "market was used".to_string(),
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_min_order_rest_time() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market with a minimum rest time
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    send_tx(
+        solana,
+        PerpSetMinOrderRestTime {
+            group,
+            admin,
+            perp_market,
+            min_order_rest_seconds: 10,
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::from_num(0.5));
+
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots,
+            max_base_lots: 1,
+            client_order_id: 1,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+    let order_id = solana
+        .get_account::<MangoAccount>(account)
+        .await
+        .perp_open_orders[0]
+        .id;
+
+    let cancel = || PerpCancelOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        order_id,
+    };
+
+    //
+    // TEST: An order can't be cancelled right after placing it
+    //
+    let result = send_tx(solana, cancel()).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpOrderMinRestTime.into(),
+        "order did not rest yet".to_string(),
+    );
+
+    // cancel all skips the order instead of failing
+    let cancel_all = || PerpCancelAllOrdersInstruction {
+        account,
+        perp_market,
+        owner,
+    };
+    send_tx(solana, cancel_all()).await.unwrap();
+    let mango_account = solana.get_account::<MangoAccount>(account).await;
+    assert_eq!(mango_account.perp_open_orders[0].id, order_id);
+
+    //
+    // TEST: After the rest time, cancelling works
+    //
+    let place_ts = solana.get_clock().await.unix_timestamp;
+    solana.advance_clock_to(place_ts + 10).await;
+    send_tx(solana, cancel_all()).await.unwrap();
+    assert_no_perp_orders(solana, account).await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_event_queue_halt() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, two accounts and a perp market that halts when three
+    // events are queued
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account_0 =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+    let account_1 =
+        create_funded_account(&solana, group, owner, 1, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket {
+        perp_market,
+        event_queue,
+        ..
+    } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    // 2.5 / 488 full: halts with 3 events, but not with 2
+    send_tx(
+        solana,
+        PerpSetEventQueueHaltFraction {
+            group,
+            admin,
+            perp_market,
+            event_queue_halt_fraction: 2.5 / MAX_NUM_EVENTS as f32,
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+
+    let place = |account: Pubkey, side: Side| PerpPlaceOrderInstruction {
+        account,
+        perp_market,
+        owner,
+        side,
+        price_lots,
+        max_base_lots: 1,
+        ..PerpPlaceOrderInstruction::default()
+    };
+    let event_queue_len = || async {
+        solana
+            .get_account_boxed::<EventQueue>(event_queue)
+            .await
+            .len()
+    };
+
+    //
+    // TEST: Each match adds a fill event, orders can be placed while the queue is short
+    //
+    for i in 0..3 {
+        send_tx(solana, place(account_0, Side::Ask)).await.unwrap();
+        send_tx(solana, place(account_1, Side::Bid)).await.unwrap();
+        assert_eq!(event_queue_len().await, i + 1);
+    }
+
+    //
+    // TEST: With three fill events queued, placing orders is halted
+    //
+    let result = send_tx(solana, place(account_0, Side::Ask)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpEventQueueCongested.into(),
+        "event queue is congested".to_string(),
+    );
+
+    //
+    // TEST: Once events are consumed, orders can be placed again
+    //
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(event_queue_len().await, 0);
+    send_tx(solana, place(account_0, Side::Ask)).await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_order_price_range() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account_0 =
+        create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+
+    let set_range = |min_order_price_lots: i64, max_order_price_lots: i64| PerpSetOrderPriceRange {
+        group,
+        admin,
+        perp_market,
+        min_order_price_lots,
+        max_order_price_lots,
+    };
+    let place = |side: Side, price_lots: i64| PerpPlaceOrderInstruction {
+        account: account_0,
+        perp_market,
+        owner,
+        side,
+        price_lots,
+        max_base_lots: 1,
+        ..PerpPlaceOrderInstruction::default()
+    };
+
+    //
+    // TEST: A range with min above max is rejected
+    //
+    assert!(send_tx(solana, set_range(2 * price_lots, price_lots))
+        .await
+        .is_err());
+
+    send_tx(solana, set_range(price_lots / 2, 2 * price_lots))
+        .await
+        .unwrap();
+
+    //
+    // TEST: Orders outside of the range are rejected
+    //
+    let result = send_tx(solana, place(Side::Bid, 3 * price_lots)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpOrderPriceOutOfRange.into(),
+        "bid price above the maximum".to_string(),
+    );
+    let result = send_tx(solana, place(Side::Ask, price_lots / 4)).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpOrderPriceOutOfRange.into(),
+        "ask price below the minimum".to_string(),
+    );
+
+    //
+    // TEST: Orders inside the range can be placed, and disabling the range lifts the limit
+    //
+    send_tx(solana, place(Side::Bid, price_lots - 1))
+        .await
+        .unwrap();
+    send_tx(solana, place(Side::Ask, 2 * price_lots))
+        .await
+        .unwrap();
+
+    send_tx(solana, set_range(0, 0)).await.unwrap();
+    send_tx(solana, place(Side::Ask, 3 * price_lots))
+        .await
+        .unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_edit_market_health_check() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, two accounts and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        zero_token_is_quote: true,
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account_0 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        &mints[0..1],
+        1000,
+        0,
+    )
+    .await;
+    let account_1 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        1,
+        &context.users[1],
+        &mints[0..1],
+        1000,
+        0,
+    )
+    .await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+
+    //
+    // SETUP: account_1 goes short 1000 native base
+    //
+    for (account, side) in [(account_0, Side::Bid), (account_1, Side::Ask)] {
+        send_tx(
+            solana,
+            PerpPlaceOrderInstruction {
+                account,
+                perp_market,
+                owner,
+                side,
+                price_lots,
+                max_base_lots: 10,
+                ..PerpPlaceOrderInstruction::default()
+            },
+        )
+        .await
+        .unwrap();
+    }
+    send_tx(
+        solana,
+        PerpConsumeEventsInstruction {
+            perp_market,
+            mango_accounts: vec![account_0, account_1],
+            keeper: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    // maint health of account_1 is 1000 + 1000 - 1000 * maint_base_liab_weight
+    let set_weight =
+        |maint_base_liab_weight: f32, sample_accounts: Vec<Pubkey>| PerpSetMaintBaseLiabWeight {
+            group,
+            admin,
+            perp_market,
+            maint_base_liab_weight,
+            sample_accounts,
+        };
+
+    //
+    // TEST: An edit that keeps the sample accounts above maint is allowed
+    //
+    send_tx(solana, set_weight(1.5, vec![account_0, account_1]))
+        .await
+        .unwrap();
+
+    //
+    // TEST: An edit that would make a sample account liquidatable is rejected
+    //
+    let result = send_tx(solana, set_weight(2.5, vec![account_0, account_1])).await;
+    assert_mango_error(
+        &result,
+        MangoError::PerpMarketEditUnsafe.into(),
+        "account_1 would drop below maint health".to_string(),
+    );
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert_eq!(
+        perp_market_data.maint_base_liab_weight,
+        I80F48::from_num(1.5)
+    );
+
+    //
+    // TEST: The long account isn't affected, and without the check the edit goes through
+    //
+    send_tx(solana, set_weight(2.5, vec![account_0]))
+        .await
+        .unwrap();
+    send_tx(solana, set_weight(3.0, vec![])).await.unwrap();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_perp_reset_funding() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..2];
+
+    //
+    // SETUP: Create a group, an account and a perp market
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+
+    let account_0 = create_funded_account(
+        &solana,
+        group,
+        owner,
+        0,
+        &context.users[1],
+        mints,
+        100000,
+        0,
+    )
+    .await;
+
+    let mango_v4::accounts::PerpCreateMarket { perp_market, .. } = send_tx(
+        solana,
+        PerpCreateMarketInstruction {
+            group,
+            admin,
+            payer,
+            perp_market_index: 0,
+            quote_lot_size: 10,
+            base_lot_size: 100,
+            maint_base_asset_weight: 0.975,
+            init_base_asset_weight: 0.95,
+            maint_base_liab_weight: 1.025,
+            init_base_liab_weight: 1.05,
+            base_liquidation_fee: 0.012,
+            maker_fee: 0.0,
+            taker_fee: 0.0,
+            ..PerpCreateMarketInstruction::with_new_book_and_queue(&solana, &tokens[1]).await
+        },
+    )
+    .await
+    .unwrap();
+
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let price_lots = perp_market_data.native_price_to_lot(I80F48::ONE);
+    let max_funding = perp_market_data.max_funding;
+
+    let update_funding = || PerpUpdateFundingInstruction {
+        perp_market,
+        bank: tokens[1].bank,
+        oracle: tokens[1].oracle,
+    };
+
+    // A bid covering the impact quantity and no asks makes funding accrue at max_funding
+    send_tx(
+        solana,
+        PerpPlaceOrderInstruction {
+            account: account_0,
+            perp_market,
+            owner,
+            side: Side::Bid,
+            price_lots,
+            max_base_lots: perp_market_data.impact_quantity,
+            ..PerpPlaceOrderInstruction::default()
+        },
+    )
+    .await
+    .unwrap();
+    send_tx(solana, update_funding()).await.unwrap();
+    solana.advance_clock().await;
+    send_tx(solana, update_funding()).await.unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert!(perp_market_data.long_funding > 0);
+
+    //
+    // SETUP: No funding updates for a while
+    //
+    let last_updated = perp_market_data.funding_last_updated;
+    solana.advance_clock_to(last_updated as i64 + 600).await;
+    set_perp_stub_oracle_price(solana, group, perp_market, &tokens[1], admin, 1.0).await;
+
+    //
+    // TEST: Resetting clears the accumulators and restarts the funding clock
+    //
+    send_tx(
+        solana,
+        PerpResetFunding {
+            group,
+            admin,
+            perp_market,
+        },
+    )
+    .await
+    .unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    assert_eq!(perp_market_data.long_funding, I80F48::ZERO);
+    assert_eq!(perp_market_data.short_funding, I80F48::ZERO);
+    let reset_ts = perp_market_data.funding_last_updated;
+    assert!(reset_ts >= last_updated + 600);
+
+    //
+    // TEST: The next funding update only covers the time since the reset
+    //
+    solana.advance_clock().await;
+    send_tx(solana, update_funding()).await.unwrap();
+
+    let perp_market_data = solana.get_account::<PerpMarket>(perp_market).await;
+    let elapsed = perp_market_data.funding_last_updated - reset_ts;
+    let max_delta =
+        I80F48::from(perp_market_data.base_lot_size) * max_funding * I80F48::from(elapsed)
+            / I80F48::from(24 * 60 * 60);
+    assert!(perp_market_data.long_funding > 0);
+    assert!(perp_market_data.long_funding <= max_delta + I80F48::from_num(1e-9));
+    assert!(elapsed < 600);
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+#[tokio::test]
+async fn test_token_reconcile_vault() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..1];
+    let payer_mint0_account = context.users[1].token_accounts[0];
+
+    //
+    // SETUP: Create a group and an account to fill the vault
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+    let bank = tokens[0].bank;
+    let vault = tokens[0].vault;
+
+    create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    //
+    // TEST: Reconciling a vault that is in sync changes nothing
+    //
+    let bank_before = solana.get_account::<Bank>(bank).await;
+    send_tx(
+        solana,
+        TokenReconcileVaultInstruction { group, admin, bank },
+    )
+    .await
+    .unwrap();
+    let bank_after = solana.get_account::<Bank>(bank).await;
+    assert_eq!(
+        bank_after.collected_fees_native,
+        bank_before.collected_fees_native
+    );
+
+    //
+    // SETUP: Transfer tokens into the vault directly
+    //
+    {
+        let mut tx = ClientTransaction::new(solana);
+        tx.add_instruction_direct(
+            spl_token::instruction::transfer(
+                &spl_token::ID,
+                &payer_mint0_account,
+                &vault,
+                &payer.pubkey(),
+                &[&payer.pubkey()],
+                100,
+            )
+            .unwrap(),
+        );
+        tx.add_signer(payer);
+        tx.send().await.unwrap();
+    }
+
+    //
+    // TEST: The discrepancy must be within the bank's configured bound
+    //
+    let set_max_vault_discrepancy = |max_vault_discrepancy: u64| TokenSetMaxVaultDiscrepancy {
+        group,
+        admin,
+        mint: mints[0].pubkey,
+        max_vault_discrepancy,
+    };
+    send_tx(solana, set_max_vault_discrepancy(50))
+        .await
+        .unwrap();
+    let result = send_tx(
+        solana,
+        TokenReconcileVaultInstruction { group, admin, bank },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::TokenVaultDiscrepancyTooLarge.into(),
+        "discrepancy too large".to_string(),
+    );
+
+    //
+    // TEST: Reconciling books the discrepancy as collected fees
+    //
+    send_tx(solana, set_max_vault_discrepancy(100))
+        .await
+        .unwrap();
+    send_tx(
+        solana,
+        TokenReconcileVaultInstruction { group, admin, bank },
+    )
+    .await
+    .unwrap();
+    let bank_after = solana.get_account::<Bank>(bank).await;
+    assert_eq!(
+        bank_after.collected_fees_native - bank_before.collected_fees_native,
+        I80F48::from(100)
+    );
+    assert_eq!(
+        bank_after.expected_vault_amount(),
+        I80F48::from(solana.token_account_balance(vault).await)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+use super::*;
+
+#[tokio::test]
+async fn test_token_sweep_fees() -> Result<(), TransportError> {
+    let context = TestContext::new().await;
+    let solana = &context.solana.clone();
+
+    let admin = TestKeypair::new();
+    let owner = context.users[0].key;
+    let payer = context.users[1].key;
+    let mints = &context.mints[0..1];
+    let payer_mint0_account = context.users[1].token_accounts[0];
+
+    //
+    // SETUP: Create a group and an account to fill the vault
+    //
+
+    let GroupWithTokens { group, tokens, .. } = GroupWithTokensConfig {
+        admin,
+        payer,
+        mints: mints.to_vec(),
+        ..GroupWithTokensConfig::default()
+    }
+    .create(solana)
+    .await;
+    let bank = tokens[0].bank;
+    let vault = tokens[0].vault;
+
+    create_funded_account(&solana, group, owner, 0, &context.users[1], mints, 10000, 0).await;
+
+    let treasury_owner = Pubkey::new_unique();
+    let treasury = solana
+        .create_token_account(&treasury_owner, mints[0].pubkey)
+        .await;
+
+    //
+    // SETUP: Collect some fees by transferring into the vault and reconciling
+    //
+    {
+        let mut tx = ClientTransaction::new(solana);
+        tx.add_instruction_direct(
+            spl_token::instruction::transfer(
+                &spl_token::ID,
+                &payer_mint0_account,
+                &vault,
+                &payer.pubkey(),
+                &[&payer.pubkey()],
+                100,
+            )
+            .unwrap(),
+        );
+        tx.add_signer(payer);
+        tx.send().await.unwrap();
+    }
+    send_tx(
+        solana,
+        TokenSetMaxVaultDiscrepancy {
+            group,
+            admin,
+            mint: mints[0].pubkey,
+            max_vault_discrepancy: 100,
+        },
+    )
+    .await
+    .unwrap();
+    send_tx(
+        solana,
+        TokenReconcileVaultInstruction { group, admin, bank },
+    )
+    .await
+    .unwrap();
+
+    //
+    // TEST: Sweeping fails while no treasury is configured
+    //
+    assert!(send_tx(
+        solana,
+        TokenSweepFeesInstruction {
+            group,
+            bank,
+            treasury,
+        },
+    )
+    .await
+    .is_err());
+
+    send_tx(
+        solana,
+        GroupEdit {
+            group,
+            admin,
+            options: mango_v4::instruction::GroupEdit {
+                fees_treasury_opt: Some(treasury_owner),
+                ..group_edit_instruction_default()
+            },
+        },
+    )
+    .await
+    .unwrap();
+
+    //
+    // TEST: Token accounts not owned by the treasury are rejected
+    //
+    let result = send_tx(
+        solana,
+        TokenSweepFeesInstruction {
+            group,
+            bank,
+            treasury: payer_mint0_account,
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::FeesTreasuryMismatch.into(),
+        "treasury owner mismatch".to_string(),
+    );
+
+    //
+    // TEST: Sweeping can't take the vault below the min vault to deposits ratio
+    //
+    let set_min_vault_to_deposits_ratio = |ratio: f64| TokenResetNetBorrows {
+        group,
+        admin,
+        mint: mints[0].pubkey,
+        min_vault_to_deposits_ratio_opt: Some(ratio),
+        net_borrow_limit_per_window_quote_opt: None,
+        net_borrow_limit_window_size_ts_opt: None,
+    };
+    send_tx(solana, set_min_vault_to_deposits_ratio(1.005))
+        .await
+        .unwrap();
+    let result = send_tx(
+        solana,
+        TokenSweepFeesInstruction {
+            group,
+            bank,
+            treasury,
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::BankBorrowLimitReached.into(),
+        "vault below min ratio".to_string(),
+    );
+    send_tx(solana, set_min_vault_to_deposits_ratio(0.2))
+        .await
+        .unwrap();
+
+    //
+    // TEST: Sweeping moves the collected fees to the treasury
+    //
+    let bank_before = solana.get_account::<Bank>(bank).await;
+    let vault_before = solana.token_account_balance(vault).await;
+    send_tx(
+        solana,
+        TokenSweepFeesInstruction {
+            group,
+            bank,
+            treasury,
+        },
+    )
+    .await
+    .unwrap();
+    let bank_after = solana.get_account::<Bank>(bank).await;
+    let swept = bank_before.unswept_fees_native().floor().to_num::<u64>();
+    assert!(swept >= 100);
+    assert_eq!(solana.token_account_balance(treasury).await, swept);
+    assert_eq!(
+        solana.token_account_balance(vault).await,
+        vault_before - swept
+    );
+    // the collected fees are kept, the swept amount is tracked separately
+    assert_eq!(
+        bank_after.collected_fees_native,
+        bank_before.collected_fees_native
+    );
+    assert_eq!(
+        bank_after.fees_withdrawn,
+        bank_before.fees_withdrawn + swept
+    );
+    assert!(bank_after.unswept_fees_native() < I80F48::ONE);
+    assert_eq!(
+        bank_after.native_deposits() - bank_after.native_borrows()
+            + bank_after.unswept_fees_native(),
+        I80F48::from(solana.token_account_balance(vault).await)
+    );
+
+    //
+    // TEST: Nothing left to sweep
+    //
+    let result = send_tx(
+        solana,
+        TokenSweepFeesInstruction {
+            group,
+            bank,
+            treasury,
+        },
+    )
+    .await;
+    assert_mango_error(
+        &result,
+        MangoError::NoFeesToSweep.into(),
+        "no fees to sweep".to_string(),
+    );
+
+    Ok(())
+}
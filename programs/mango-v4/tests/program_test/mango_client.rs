@@ -54,6 +54,17 @@ pub async fn send_tx<CI: ClientInstruction>(
     Ok(accounts)
 }
 
+/// Simulate a single instruction and return its return data
+pub async fn simulate_tx_return_data<CI: ClientInstruction>(
+    solana: &SolanaCookie,
+    ix: CI,
+) -> std::result::Result<Vec<u8>, TransportError> {
+    let (_, instruction) = ix.to_instruction(solana).await;
+    Ok(solana
+        .simulate_transaction_return_data(&[instruction])
+        .await?)
+}
+
 /// Build a transaction from multiple instructions
 pub struct ClientTransaction {
     solana: Arc<SolanaCookie>,
@@ -358,6 +369,17 @@ pub async fn account_init_health(solana: &SolanaCookie, account: Pubkey) -> f64
     health_data.init_health.to_num::<f64>()
 }
 
+pub async fn account_liquidation_priority(solana: &SolanaCookie, account: Pubkey) -> f64 {
+    send_tx(solana, ComputeLiquidationPriorityInstruction { account })
+        .await
+        .unwrap();
+    let priority = solana
+        .program_log_events::<mango_v4::events::MangoAccountLiquidationPriority>()
+        .pop()
+        .unwrap();
+    priority.score.to_num::<f64>()
+}
+
 // Verifies that the "post_health: ..." log emitted by the previous instruction
 // matches the init health of the account.
 pub async fn check_prev_instruction_post_health(solana: &SolanaCookie, account: Pubkey) {
@@ -1082,6 +1104,9 @@ fn token_edit_instruction_default() -> mango_v4::instruction::TokenEdit {
         reduce_only_opt: None,
         name_opt: None,
         force_close_opt: None,
+        liquidation_fee_mode_opt: None,
+        flat_liquidation_fee_quote_opt: None,
+        max_vault_discrepancy_opt: None,
     }
 }
 
@@ -1323,6 +1348,189 @@ impl ClientInstruction for TokenMakeReduceOnly {
     }
 }
 
+pub struct TokenEditLiquidationFee {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub mint: Pubkey,
+    pub liquidation_fee_mode: u8,
+    pub flat_liquidation_fee_quote: f32,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for TokenEditLiquidationFee {
+    type Accounts = mango_v4::accounts::TokenEdit;
+    type Instruction = mango_v4::instruction::TokenEdit;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let mint_info_key = Pubkey::find_program_address(
+            &[
+                b"MintInfo".as_ref(),
+                self.group.as_ref(),
+                self.mint.as_ref(),
+            ],
+            &program_id,
+        )
+        .0;
+        let mint_info: MintInfo = account_loader.load(&mint_info_key).await.unwrap();
+
+        let instruction = Self::Instruction {
+            liquidation_fee_mode_opt: Some(self.liquidation_fee_mode),
+            flat_liquidation_fee_quote_opt: Some(self.flat_liquidation_fee_quote),
+            ..token_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            mint_info: mint_info_key,
+            oracle: mint_info.oracle,
+        };
+
+        let mut instruction = make_instruction(program_id, &accounts, &instruction);
+        instruction
+            .accounts
+            .extend(mint_info.banks().iter().map(|&k| AccountMeta {
+                pubkey: k,
+                is_signer: false,
+                is_writable: true,
+            }));
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct TokenSetMaxVaultDiscrepancy {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub mint: Pubkey,
+    pub max_vault_discrepancy: u64,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for TokenSetMaxVaultDiscrepancy {
+    type Accounts = mango_v4::accounts::TokenEdit;
+    type Instruction = mango_v4::instruction::TokenEdit;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let mint_info_key = Pubkey::find_program_address(
+            &[
+                b"MintInfo".as_ref(),
+                self.group.as_ref(),
+                self.mint.as_ref(),
+            ],
+            &program_id,
+        )
+        .0;
+        let mint_info: MintInfo = account_loader.load(&mint_info_key).await.unwrap();
+
+        let instruction = Self::Instruction {
+            max_vault_discrepancy_opt: Some(self.max_vault_discrepancy),
+            ..token_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            mint_info: mint_info_key,
+            oracle: mint_info.oracle,
+        };
+
+        let mut instruction = make_instruction(program_id, &accounts, &instruction);
+        instruction
+            .accounts
+            .extend(mint_info.banks().iter().map(|&k| AccountMeta {
+                pubkey: k,
+                is_signer: false,
+                is_writable: true,
+            }));
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct TokenReconcileVaultInstruction {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub bank: Pubkey,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for TokenReconcileVaultInstruction {
+    type Accounts = mango_v4::accounts::TokenReconcileVault;
+    type Instruction = mango_v4::instruction::TokenReconcileVault;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+
+        let bank: Bank = account_loader.load(&self.bank).await.unwrap();
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            bank: self.bank,
+            vault: bank.vault,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct TokenSweepFeesInstruction {
+    pub group: Pubkey,
+    pub bank: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for TokenSweepFeesInstruction {
+    type Accounts = mango_v4::accounts::TokenSweepFees;
+    type Instruction = mango_v4::instruction::TokenSweepFees;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+
+        let bank: Bank = account_loader.load(&self.bank).await.unwrap();
+        let accounts = Self::Accounts {
+            group: self.group,
+            bank: self.bank,
+            vault: bank.vault,
+            treasury: self.treasury,
+            token_program: Token::id(),
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![]
+    }
+}
+
 pub struct StubOracleSetInstruction {
     pub mint: Pubkey,
     pub group: Pubkey,
@@ -1530,6 +1738,12 @@ pub fn group_edit_instruction_default() -> mango_v4::instruction::GroupEdit {
         buyback_fees_swap_mango_account_opt: None,
         mngo_token_index_opt: None,
         buyback_fees_expiry_interval_opt: None,
+        fees_treasury_opt: None,
+        max_liquidation_fee_opt: None,
+        batch_token_balance_logs_opt: None,
+        liquidation_fee_emergency_mode_opt: None,
+        min_liquidation_fee_quote_opt: None,
+        min_liquidation_notional_quote_opt: None,
     }
 }
 
@@ -1801,6 +2015,7 @@ impl ClientInstruction for AccountEditInstruction {
         let instruction = mango_v4::instruction::AccountEdit {
             name_opt: Option::from(self.name.to_string()),
             delegate_opt: Option::from(self.delegate),
+            liqor_max_liab_quote_opt: None,
         };
 
         let account = Pubkey::find_program_address(
@@ -2662,6 +2877,8 @@ impl ClientInstruction for TokenForceCloseBorrowsWithTokenInstruction {
             asset_token_index: self.asset_token_index,
             liab_token_index: self.liab_token_index,
             max_liab_transfer: self.max_liab_transfer,
+            reject_liqor_asset_netting: self.reject_liqor_asset_netting,
+            dust_liqor_asset: self.dust_liqor_asset,
         };
 
         let liqee = account_loader
@@ -2711,6 +2928,8 @@ pub struct TokenLiqWithTokenInstruction {
     pub liab_token_index: TokenIndex,
     pub liab_bank_index: usize,
     pub max_liab_transfer: I80F48,
+    pub reject_liqor_asset_netting: bool,
+    pub dust_liqor_asset: bool,
 }
 #[async_trait::async_trait(?Send)]
 impl ClientInstruction for TokenLiqWithTokenInstruction {
@@ -3015,6 +3234,62 @@ fn perp_edit_instruction_default() -> mango_v4::instruction::PerpEditMarket {
         positive_pnl_liquidation_fee_opt: None,
         name_opt: None,
         force_close_opt: None,
+        consume_events_reward_opt: None,
+        default_order_expiry_seconds_opt: None,
+        circuit_breaker_threshold_opt: None,
+        circuit_breaker_window_seconds_opt: None,
+        circuit_breaker_cooldown_seconds_opt: None,
+        min_equity_to_place_order_opt: None,
+        oracle_type_opt: None,
+        maker_rebate_cap_per_open_interest_opt: None,
+        maker_rebate_epoch_seconds_opt: None,
+        taker_fee_price_impact_factor_opt: None,
+        settle_token_index_opt: None,
+        min_order_rest_seconds_opt: None,
+        event_queue_halt_fraction_opt: None,
+        min_order_price_lots_opt: None,
+        max_order_price_lots_opt: None,
+        check_sample_accounts_health_opt: None,
+        reset_funding_opt: None,
+    }
+}
+
+pub struct PerpResetFunding {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpResetFunding {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            reset_funding_opt: Some(true),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
     }
 }
 
@@ -3139,16 +3414,15 @@ impl ClientInstruction for PerpMakeReduceOnly {
     }
 }
 
-pub struct PerpChangeWeights {
+pub struct PerpSetConsumeEventsReward {
     pub group: Pubkey,
     pub admin: TestKeypair,
     pub perp_market: Pubkey,
-    pub init_overall_asset_weight: f32,
-    pub maint_overall_asset_weight: f32,
+    pub consume_events_reward: f32,
 }
 
 #[async_trait::async_trait(?Send)]
-impl ClientInstruction for PerpChangeWeights {
+impl ClientInstruction for PerpSetConsumeEventsReward {
     type Accounts = mango_v4::accounts::PerpEditMarket;
     type Instruction = mango_v4::instruction::PerpEditMarket;
     async fn to_instruction(
@@ -3160,8 +3434,7 @@ impl ClientInstruction for PerpChangeWeights {
         let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
 
         let instruction = Self::Instruction {
-            init_overall_asset_weight_opt: Some(self.init_overall_asset_weight),
-            maint_overall_asset_weight_opt: Some(self.maint_overall_asset_weight),
+            consume_events_reward_opt: Some(self.consume_events_reward),
             ..perp_edit_instruction_default()
         };
 
@@ -3181,32 +3454,35 @@ impl ClientInstruction for PerpChangeWeights {
     }
 }
 
-pub struct PerpCloseMarketInstruction {
+pub struct PerpSetDefaultOrderExpiry {
+    pub group: Pubkey,
     pub admin: TestKeypair,
     pub perp_market: Pubkey,
-    pub sol_destination: Pubkey,
+    pub default_order_expiry_seconds: u16,
 }
+
 #[async_trait::async_trait(?Send)]
-impl ClientInstruction for PerpCloseMarketInstruction {
-    type Accounts = mango_v4::accounts::PerpCloseMarket;
-    type Instruction = mango_v4::instruction::PerpCloseMarket;
+impl ClientInstruction for PerpSetDefaultOrderExpiry {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
     async fn to_instruction(
         &self,
         account_loader: impl ClientAccountLoader + 'async_trait,
     ) -> (Self::Accounts, instruction::Instruction) {
         let program_id = mango_v4::id();
-        let instruction = Self::Instruction {};
+
         let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
 
+        let instruction = Self::Instruction {
+            default_order_expiry_seconds_opt: Some(self.default_order_expiry_seconds),
+            ..perp_edit_instruction_default()
+        };
+
         let accounts = Self::Accounts {
-            group: perp_market.group,
+            group: self.group,
             admin: self.admin.pubkey(),
             perp_market: self.perp_market,
-            bids: perp_market.bids,
-            asks: perp_market.asks,
-            event_queue: perp_market.event_queue,
-            token_program: Token::id(),
-            sol_destination: self.sol_destination,
+            oracle: perp_market.oracle,
         };
 
         let instruction = make_instruction(program_id, &accounts, &instruction);
@@ -3218,28 +3494,39 @@ impl ClientInstruction for PerpCloseMarketInstruction {
     }
 }
 
-pub struct PerpDeactivatePositionInstruction {
-    pub account: Pubkey,
+pub struct PerpSetCircuitBreaker {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
     pub perp_market: Pubkey,
-    pub owner: TestKeypair,
+    pub threshold: f32,
+    pub window_seconds: u32,
+    pub cooldown_seconds: u32,
 }
+
 #[async_trait::async_trait(?Send)]
-impl ClientInstruction for PerpDeactivatePositionInstruction {
-    type Accounts = mango_v4::accounts::PerpDeactivatePosition;
-    type Instruction = mango_v4::instruction::PerpDeactivatePosition;
+impl ClientInstruction for PerpSetCircuitBreaker {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
     async fn to_instruction(
         &self,
         account_loader: impl ClientAccountLoader + 'async_trait,
     ) -> (Self::Accounts, instruction::Instruction) {
         let program_id = mango_v4::id();
+
         let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
 
-        let instruction = Self::Instruction {};
+        let instruction = Self::Instruction {
+            circuit_breaker_threshold_opt: Some(self.threshold),
+            circuit_breaker_window_seconds_opt: Some(self.window_seconds),
+            circuit_breaker_cooldown_seconds_opt: Some(self.cooldown_seconds),
+            ..perp_edit_instruction_default()
+        };
+
         let accounts = Self::Accounts {
-            group: perp_market.group,
-            account: self.account,
+            group: self.group,
+            admin: self.admin.pubkey(),
             perp_market: self.perp_market,
-            owner: self.owner.pubkey(),
+            oracle: perp_market.oracle,
         };
 
         let instruction = make_instruction(program_id, &accounts, &instruction);
@@ -3247,12 +3534,469 @@ impl ClientInstruction for PerpDeactivatePositionInstruction {
     }
 
     fn signers(&self) -> Vec<TestKeypair> {
-        vec![self.owner]
+        vec![self.admin]
     }
 }
 
-pub struct PerpPlaceOrderInstruction {
-    pub account: Pubkey,
+pub struct PerpSetMinEquityToPlaceOrder {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub min_equity_to_place_order: u64,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetMinEquityToPlaceOrder {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            min_equity_to_place_order_opt: Some(self.min_equity_to_place_order),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetMinOrderRestTime {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub min_order_rest_seconds: u16,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetMinOrderRestTime {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            min_order_rest_seconds_opt: Some(self.min_order_rest_seconds),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetEventQueueHaltFraction {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub event_queue_halt_fraction: f32,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetEventQueueHaltFraction {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            event_queue_halt_fraction_opt: Some(self.event_queue_halt_fraction),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetOrderPriceRange {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub min_order_price_lots: i64,
+    pub max_order_price_lots: i64,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetOrderPriceRange {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            min_order_price_lots_opt: Some(self.min_order_price_lots),
+            max_order_price_lots_opt: Some(self.max_order_price_lots),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetMaintBaseLiabWeight {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub maint_base_liab_weight: f32,
+    /// If not empty, the edit is rejected if it brings any of these accounts below maint health
+    pub sample_accounts: Vec<Pubkey>,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetMaintBaseLiabWeight {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            maint_base_liab_weight_opt: Some(self.maint_base_liab_weight),
+            check_sample_accounts_health_opt: Some(!self.sample_accounts.is_empty()),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let mut instruction = make_instruction(program_id, &accounts, &instruction);
+        if let Some((first, others)) = self.sample_accounts.split_first() {
+            // Health accounts covering the token and perp positions of all sample accounts
+            let mut combined = account_loader.load_mango_account(first).await.unwrap();
+            for other in others {
+                let other = account_loader.load_mango_account(other).await.unwrap();
+                for token in other.active_token_positions() {
+                    combined.ensure_token_position(token.token_index).unwrap();
+                }
+                for perp in other.active_perp_positions() {
+                    let pm: PerpMarket = account_loader
+                        .load(&get_perp_market_address_by_index(
+                            self.group,
+                            perp.market_index,
+                        ))
+                        .await
+                        .unwrap();
+                    combined
+                        .ensure_perp_position(perp.market_index, pm.settle_token_index)
+                        .unwrap();
+                }
+            }
+            let health_check_metas = derive_health_check_remaining_account_metas(
+                &account_loader,
+                &combined,
+                None,
+                false,
+                None,
+            )
+            .await;
+
+            instruction
+                .accounts
+                .extend(self.sample_accounts.iter().map(|&pubkey| AccountMeta {
+                    pubkey,
+                    is_writable: false,
+                    is_signer: false,
+                }));
+            instruction.accounts.extend(health_check_metas.into_iter());
+        }
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetOracle {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub oracle: Pubkey,
+    pub oracle_type: OracleType,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetOracle {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        _account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let instruction = Self::Instruction {
+            oracle_opt: Some(self.oracle),
+            oracle_config_opt: Some(OracleConfigParams {
+                conf_filter: 0.1,
+                max_staleness_slots: None,
+            }),
+            oracle_type_opt: Some(self.oracle_type),
+            reset_stable_price: true,
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: self.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpSetSettleToken {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub settle_token_index: TokenIndex,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpSetSettleToken {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            settle_token_index_opt: Some(self.settle_token_index),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let settle_bank = Pubkey::find_program_address(
+            &[
+                b"Bank".as_ref(),
+                self.group.as_ref(),
+                &self.settle_token_index.to_le_bytes(),
+                &0u32.to_le_bytes(),
+            ],
+            &program_id,
+        )
+        .0;
+
+        let mut instruction = make_instruction(program_id, &accounts, &instruction);
+        instruction.accounts.push(AccountMeta {
+            pubkey: settle_bank,
+            is_signer: false,
+            is_writable: false,
+        });
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpChangeWeights {
+    pub group: Pubkey,
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub init_overall_asset_weight: f32,
+    pub maint_overall_asset_weight: f32,
+}
+
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpChangeWeights {
+    type Accounts = mango_v4::accounts::PerpEditMarket;
+    type Instruction = mango_v4::instruction::PerpEditMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {
+            init_overall_asset_weight_opt: Some(self.init_overall_asset_weight),
+            maint_overall_asset_weight_opt: Some(self.maint_overall_asset_weight),
+            ..perp_edit_instruction_default()
+        };
+
+        let accounts = Self::Accounts {
+            group: self.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpCloseMarketInstruction {
+    pub admin: TestKeypair,
+    pub perp_market: Pubkey,
+    pub sol_destination: Pubkey,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpCloseMarketInstruction {
+    type Accounts = mango_v4::accounts::PerpCloseMarket;
+    type Instruction = mango_v4::instruction::PerpCloseMarket;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let accounts = Self::Accounts {
+            group: perp_market.group,
+            admin: self.admin.pubkey(),
+            perp_market: self.perp_market,
+            bids: perp_market.bids,
+            asks: perp_market.asks,
+            event_queue: perp_market.event_queue,
+            token_program: Token::id(),
+            sol_destination: self.sol_destination,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.admin]
+    }
+}
+
+pub struct PerpDeactivatePositionInstruction {
+    pub account: Pubkey,
+    pub perp_market: Pubkey,
+    pub owner: TestKeypair,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpDeactivatePositionInstruction {
+    type Accounts = mango_v4::accounts::PerpDeactivatePosition;
+    type Instruction = mango_v4::instruction::PerpDeactivatePosition;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+
+        let instruction = Self::Instruction {};
+        let accounts = Self::Accounts {
+            group: perp_market.group,
+            account: self.account,
+            perp_market: self.perp_market,
+            owner: self.owner.pubkey(),
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![self.owner]
+    }
+}
+
+pub struct PerpPlaceOrderInstruction {
+    pub account: Pubkey,
     pub perp_market: Pubkey,
     pub owner: TestKeypair,
     pub side: Side,
@@ -3517,6 +4261,8 @@ impl ClientInstruction for PerpCancelAllOrdersInstruction {
 pub struct PerpConsumeEventsInstruction {
     pub perp_market: Pubkey,
     pub mango_accounts: Vec<Pubkey>,
+    /// The keeper account that receives the reward and its owner
+    pub keeper: Option<(Pubkey, TestKeypair)>,
 }
 #[async_trait::async_trait(?Send)]
 impl ClientInstruction for PerpConsumeEventsInstruction {
@@ -3544,11 +4290,23 @@ impl ClientInstruction for PerpConsumeEventsInstruction {
                 is_signer: false,
                 is_writable: true,
             }));
+        if let Some((keeper, keeper_owner)) = self.keeper {
+            instruction.accounts.push(AccountMeta {
+                pubkey: keeper,
+                is_signer: false,
+                is_writable: true,
+            });
+            instruction.accounts.push(AccountMeta {
+                pubkey: keeper_owner.pubkey(),
+                is_signer: true,
+                is_writable: false,
+            });
+        }
         (accounts, instruction)
     }
 
     fn signers(&self) -> Vec<TestKeypair> {
-        vec![]
+        self.keeper.iter().map(|(_, owner)| *owner).collect()
     }
 }
 
@@ -3585,6 +4343,104 @@ impl ClientInstruction for PerpUpdateFundingInstruction {
     }
 }
 
+pub struct PerpLogSpreadInstruction {
+    pub perp_market: Pubkey,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpLogSpreadInstruction {
+    type Accounts = mango_v4::accounts::PerpLogSpread;
+    type Instruction = mango_v4::instruction::PerpLogSpread;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+        let accounts = Self::Accounts {
+            group: perp_market.group,
+            perp_market: self.perp_market,
+            bids: perp_market.bids,
+            asks: perp_market.asks,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![]
+    }
+}
+
+pub struct PerpOrderbookViewInstruction {
+    pub perp_market: Pubkey,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpOrderbookViewInstruction {
+    type Accounts = mango_v4::accounts::PerpOrderbookView;
+    type Instruction = mango_v4::instruction::PerpOrderbookView;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+        let accounts = Self::Accounts {
+            group: perp_market.group,
+            perp_market: self.perp_market,
+            bids: perp_market.bids,
+            asks: perp_market.asks,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![]
+    }
+}
+
+pub struct PerpOrderSnapshotsInstruction {
+    pub perp_market: Pubkey,
+    pub side: Side,
+    pub start_index: u32,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for PerpOrderSnapshotsInstruction {
+    type Accounts = mango_v4::accounts::PerpOrderSnapshots;
+    type Instruction = mango_v4::instruction::PerpOrderSnapshots;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {
+            side: self.side,
+            start_index: self.start_index,
+        };
+        let perp_market: PerpMarket = account_loader.load(&self.perp_market).await.unwrap();
+        let accounts = Self::Accounts {
+            group: perp_market.group,
+            perp_market: self.perp_market,
+            bids: perp_market.bids,
+            asks: perp_market.asks,
+            oracle: perp_market.oracle,
+        };
+
+        let instruction = make_instruction(program_id, &accounts, &instruction);
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![]
+    }
+}
+
 pub struct PerpSettlePnlInstruction {
     pub settler: Pubkey,
     pub settler_owner: TestKeypair,
@@ -4043,6 +4899,50 @@ impl ClientInstruction for ComputeAccountDataInstruction {
     }
 }
 
+pub struct ComputeLiquidationPriorityInstruction {
+    pub account: Pubkey,
+}
+#[async_trait::async_trait(?Send)]
+impl ClientInstruction for ComputeLiquidationPriorityInstruction {
+    type Accounts = mango_v4::accounts::ComputeAccountData;
+    type Instruction = mango_v4::instruction::ComputeLiquidationPriority;
+    async fn to_instruction(
+        &self,
+        account_loader: impl ClientAccountLoader + 'async_trait,
+    ) -> (Self::Accounts, instruction::Instruction) {
+        let program_id = mango_v4::id();
+        let instruction = Self::Instruction {};
+
+        let account = account_loader
+            .load_mango_account(&self.account)
+            .await
+            .unwrap();
+
+        let health_check_metas = derive_health_check_remaining_account_metas(
+            &account_loader,
+            &account,
+            None,
+            false,
+            None,
+        )
+        .await;
+
+        let accounts = Self::Accounts {
+            group: account.fixed.group,
+            account: self.account,
+        };
+
+        let mut instruction = make_instruction(program_id, &accounts, &instruction);
+        instruction.accounts.extend(health_check_metas.into_iter());
+
+        (accounts, instruction)
+    }
+
+    fn signers(&self) -> Vec<TestKeypair> {
+        vec![]
+    }
+}
+
 pub struct HealthRegionBeginInstruction {
     pub account: Pubkey,
 }
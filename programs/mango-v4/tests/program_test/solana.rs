@@ -82,6 +82,32 @@ impl SolanaCookie {
         result
     }
 
+    /// Simulates the transaction without committing it and returns the return data
+    /// it produced, empty if there was none.
+    pub async fn simulate_transaction_return_data(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<Vec<u8>, BanksClientError> {
+        let mut context = self.context.borrow_mut();
+
+        let mut transaction =
+            Transaction::new_with_payer(instructions, Some(&context.payer.pubkey()));
+        transaction.sign(&[&context.payer], context.last_blockhash);
+
+        let simulation = context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await?;
+        if let Some(Err(err)) = simulation.result {
+            return Err(BanksClientError::TransactionError(err));
+        }
+        Ok(simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+            .unwrap_or_default())
+    }
+
     pub async fn get_clock(&self) -> solana_program::clock::Clock {
         self.context
             .borrow_mut()
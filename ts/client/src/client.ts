@@ -179,6 +179,12 @@ export class MangoClient {
     feesSwapMangoAccount?: PublicKey,
     feesMngoTokenIndex?: TokenIndex,
     feesExpiryInterval?: BN,
+    feesTreasury?: PublicKey,
+    maxLiquidationFee?: number,
+    batchTokenBalanceLogs?: boolean,
+    liquidationFeeEmergencyMode?: boolean,
+    minLiquidationFeeQuote?: number,
+    minLiquidationNotionalQuote?: number,
   ): Promise<TransactionSignature> {
     const ix = await this.program.methods
       .groupEdit(
@@ -193,6 +199,12 @@ export class MangoClient {
         feesSwapMangoAccount ?? null,
         feesMngoTokenIndex ?? null,
         feesExpiryInterval ?? null,
+        feesTreasury ?? null,
+        maxLiquidationFee ?? null,
+        batchTokenBalanceLogs ?? null,
+        liquidationFeeEmergencyMode ?? null,
+        minLiquidationFeeQuote ?? null,
+        minLiquidationNotionalQuote ?? null,
       )
       .accounts({
         group: group.publicKey,
@@ -402,6 +414,11 @@ export class MangoClient {
         params.reduceOnly,
         params.name,
         params.forceClose,
+        params.liquidationFeeMode,
+        params.flatLiquidationFeeQuote,
+        params.maxVaultDiscrepancy !== null
+          ? new BN(params.maxVaultDiscrepancy)
+          : null,
       )
       .accounts({
         group: group.publicKey,
@@ -751,9 +768,10 @@ export class MangoClient {
     mangoAccount: MangoAccount,
     name?: string,
     delegate?: PublicKey,
+    liqorMaxLiabQuote?: BN,
   ): Promise<TransactionSignature> {
     const ix = await this.program.methods
-      .accountEdit(name ?? null, delegate ?? null)
+      .accountEdit(name ?? null, delegate ?? null, liqorMaxLiabQuote ?? null)
       .accounts({
         group: group.publicKey,
         account: mangoAccount.publicKey,
@@ -2076,6 +2094,20 @@ export class MangoClient {
   ): Promise<TransactionSignature> {
     const perpMarket = group.getPerpMarketByMarketIndex(perpMarketIndex);
 
+    // changing the settle token requires passing the new settle token's bank
+    const settleBankAccounts: AccountMeta[] =
+      params.settleTokenIndex !== null
+        ? [
+            {
+              pubkey: group.getFirstBankByTokenIndex(
+                params.settleTokenIndex as TokenIndex,
+              ).publicKey,
+              isWritable: false,
+              isSigner: false,
+            },
+          ]
+        : [];
+
     const ix = await this.program.methods
       .perpEditMarket(
         params.oracle,
@@ -2110,6 +2142,29 @@ export class MangoClient {
         params.positivePnlLiquidationFee,
         params.name,
         params.forceClose,
+        params.consumeEventsReward,
+        params.defaultOrderExpirySeconds,
+        params.circuitBreakerThreshold,
+        params.circuitBreakerWindowSeconds,
+        params.circuitBreakerCooldownSeconds,
+        params.minEquityToPlaceOrder !== null
+          ? new BN(params.minEquityToPlaceOrder)
+          : null,
+        params.oracleType,
+        params.makerRebateCapPerOpenInterest,
+        params.makerRebateEpochSeconds,
+        params.takerFeePriceImpactFactor,
+        params.settleTokenIndex,
+        params.minOrderRestSeconds,
+        params.eventQueueHaltFraction,
+        params.minOrderPriceLots !== null
+          ? new BN(params.minOrderPriceLots)
+          : null,
+        params.maxOrderPriceLots !== null
+          ? new BN(params.maxOrderPriceLots)
+          : null,
+        params.checkSampleAccountsHealth,
+        params.resetFunding,
       )
       .accounts({
         group: group.publicKey,
@@ -2117,6 +2172,7 @@ export class MangoClient {
         admin: (this.program.provider as AnchorProvider).wallet.publicKey,
         perpMarket: perpMarket.publicKey,
       })
+      .remainingAccounts(settleBankAccounts)
       .instruction();
     return await this.sendAndConfirmTransactionForGroup(group, [ix]);
   }
@@ -2907,6 +2963,8 @@ export class MangoClient {
     assetMintPk: PublicKey,
     liabMintPk: PublicKey,
     maxLiabTransfer: number,
+    rejectLiqorAssetNetting = false,
+    dustLiqorAsset = false,
   ): Promise<TransactionSignature> {
     const assetBank: Bank = group.getFirstBankByMint(assetMintPk);
     const liabBank: Bank = group.getFirstBankByMint(liabMintPk);
@@ -2932,9 +2990,15 @@ export class MangoClient {
     );
 
     const ix = await this.program.methods
-      .liqTokenWithToken(assetBank.tokenIndex, liabBank.tokenIndex, {
-        val: I80F48.fromNumber(maxLiabTransfer).getData(),
-      })
+      .tokenLiqWithToken(
+        assetBank.tokenIndex,
+        liabBank.tokenIndex,
+        {
+          val: I80F48.fromNumber(maxLiabTransfer).getData(),
+        },
+        rejectLiqorAssetNetting,
+        dustLiqorAsset,
+      )
       .accounts({
         group: group.publicKey,
         liqor: liqor.publicKey,
@@ -1,6 +1,6 @@
 import { BN } from '@coral-xyz/anchor';
 import { PublicKey } from '@solana/web3.js';
-import { InterestRateParams, OracleConfigParams } from './types';
+import { InterestRateParams, OracleConfigParams, OracleType } from './types';
 
 export interface TokenEditParams {
   oracle: PublicKey | null;
@@ -27,6 +27,9 @@ export interface TokenEditParams {
   reduceOnly: number | null;
   name: string | null;
   forceClose: boolean | null;
+  liquidationFeeMode: number | null;
+  flatLiquidationFeeQuote: number | null;
+  maxVaultDiscrepancy: number | null;
 }
 
 export const NullTokenEditParams: TokenEditParams = {
@@ -54,6 +57,9 @@ export const NullTokenEditParams: TokenEditParams = {
   reduceOnly: null,
   name: null,
   forceClose: null,
+  liquidationFeeMode: null,
+  flatLiquidationFeeQuote: null,
+  maxVaultDiscrepancy: null,
 };
 
 export interface PerpEditParams {
@@ -87,6 +93,23 @@ export interface PerpEditParams {
   positivePnlLiquidationFee: number | null;
   name: string | null;
   forceClose: boolean | null;
+  consumeEventsReward: number | null;
+  defaultOrderExpirySeconds: number | null;
+  circuitBreakerThreshold: number | null;
+  circuitBreakerWindowSeconds: number | null;
+  circuitBreakerCooldownSeconds: number | null;
+  minEquityToPlaceOrder: number | null;
+  oracleType: OracleType | null;
+  makerRebateCapPerOpenInterest: number | null;
+  makerRebateEpochSeconds: number | null;
+  takerFeePriceImpactFactor: number | null;
+  settleTokenIndex: number | null;
+  minOrderRestSeconds: number | null;
+  eventQueueHaltFraction: number | null;
+  minOrderPriceLots: number | null;
+  maxOrderPriceLots: number | null;
+  checkSampleAccountsHealth: boolean | null;
+  resetFunding: boolean | null;
 }
 
 export const NullPerpEditParams: PerpEditParams = {
@@ -120,6 +143,23 @@ export const NullPerpEditParams: PerpEditParams = {
   positivePnlLiquidationFee: null,
   name: null,
   forceClose: null,
+  consumeEventsReward: null,
+  defaultOrderExpirySeconds: null,
+  circuitBreakerThreshold: null,
+  circuitBreakerWindowSeconds: null,
+  circuitBreakerCooldownSeconds: null,
+  minEquityToPlaceOrder: null,
+  oracleType: null,
+  makerRebateCapPerOpenInterest: null,
+  makerRebateEpochSeconds: null,
+  takerFeePriceImpactFactor: null,
+  settleTokenIndex: null,
+  minOrderRestSeconds: null,
+  eventQueueHaltFraction: null,
+  minOrderPriceLots: null,
+  maxOrderPriceLots: null,
+  checkSampleAccountsHealth: null,
+  resetFunding: null,
 };
 
 // Use with TrueIxGateParams and buildIxGate
@@ -179,6 +219,8 @@ export interface IxGateParams {
   TokenForceCloseBorrowsWithToken: boolean;
   PerpForceClosePosition: boolean;
   GroupWithdrawInsuranceFund: boolean;
+  PerpBookDefragment: boolean;
+  TokenSweepFees: boolean;
 }
 
 // Default with all ixs enabled, use with buildIxGate
@@ -238,6 +280,8 @@ export const TrueIxGateParams: IxGateParams = {
   TokenForceCloseBorrowsWithToken: true,
   PerpForceClosePosition: true,
   GroupWithdrawInsuranceFund: true,
+  PerpBookDefragment: true,
+  TokenSweepFees: true,
 };
 
 // build ix gate e.g. buildIxGate(Builder(TrueIxGateParams).TokenDeposit(false).build()).toNumber(),
@@ -307,6 +351,8 @@ export function buildIxGate(p: IxGateParams): BN {
   toggleIx(ixGate, p, 'TokenForceCloseBorrowsWithToken', 49);
   toggleIx(ixGate, p, 'PerpForceClosePosition', 50);
   toggleIx(ixGate, p, 'GroupWithdrawInsuranceFund', 51);
+  toggleIx(ixGate, p, 'PerpBookDefragment', 52);
+  toggleIx(ixGate, p, 'TokenSweepFees', 53);
 
   return ixGate;
 }
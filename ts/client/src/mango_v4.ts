@@ -177,6 +177,42 @@ export type MangoV4 = {
           "type": {
             "option": "u64"
           }
+        },
+        {
+          "name": "feesTreasuryOpt",
+          "type": {
+            "option": "publicKey"
+          }
+        },
+        {
+          "name": "maxLiquidationFeeOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "batchTokenBalanceLogsOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "liquidationFeeEmergencyModeOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "minLiquidationFeeQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "minLiquidationNotionalQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
         }
       ]
     },
@@ -782,6 +818,24 @@ export type MangoV4 = {
           "type": {
             "option": "bool"
           }
+        },
+        {
+          "name": "liquidationFeeModeOpt",
+          "type": {
+            "option": "u8"
+          }
+        },
+        {
+          "name": "flatLiquidationFeeQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "maxVaultDiscrepancyOpt",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
@@ -986,6 +1040,74 @@ export type MangoV4 = {
       ],
       "args": []
     },
+    {
+      "name": "tokenReconcileVault",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "admin"
+          ]
+        },
+        {
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "bank",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "vault"
+          ]
+        },
+        {
+          "name": "vault",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "tokenSweepFees",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "bank",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "vault"
+          ]
+        },
+        {
+          "name": "vault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
     {
       "name": "accountCreate",
       "accounts": [
@@ -1153,6 +1275,12 @@ export type MangoV4 = {
           "type": {
             "option": "publicKey"
           }
+        },
+        {
+          "name": "liqorMaxLiabQuoteOpt",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
@@ -2886,6 +3014,14 @@ export type MangoV4 = {
           "type": {
             "defined": "I80F48"
           }
+        },
+        {
+          "name": "rejectLiqorAssetNetting",
+          "type": "bool"
+        },
+        {
+          "name": "dustLiqorAsset",
+          "type": "bool"
         }
       ]
     },
@@ -3218,7 +3354,8 @@ export type MangoV4 = {
           "isMut": false,
           "isSigner": false,
           "docs": [
-            "The oracle account is optional and only used when reset_stable_price is set.",
+            "The oracle account is optional and only used when reset_stable_price or",
+            "oracle_type_opt is set.",
             ""
           ]
         }
@@ -3403,6 +3540,110 @@ export type MangoV4 = {
           "type": {
             "option": "bool"
           }
+        },
+        {
+          "name": "consumeEventsRewardOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "defaultOrderExpirySecondsOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "circuitBreakerThresholdOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "circuitBreakerWindowSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "circuitBreakerCooldownSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "minEquityToPlaceOrderOpt",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "oracleTypeOpt",
+          "type": {
+            "option": {
+              "defined": "OracleType"
+            }
+          }
+        },
+        {
+          "name": "makerRebateCapPerOpenInterestOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "makerRebateEpochSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "takerFeePriceImpactFactorOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "settleTokenIndexOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "minOrderRestSecondsOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "eventQueueHaltFractionOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "minOrderPriceLotsOpt",
+          "type": {
+            "option": "i64"
+          }
+        },
+        {
+          "name": "maxOrderPriceLotsOpt",
+          "type": {
+            "option": "i64"
+          }
+        },
+        {
+          "name": "checkSampleAccountsHealthOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "resetFundingOpt",
+          "type": {
+            "option": "bool"
+          }
         }
       ]
     },
@@ -4156,7 +4397,7 @@ export type MangoV4 = {
       ]
     },
     {
-      "name": "perpUpdateFunding",
+      "name": "perpBookDefragment",
       "accounts": [
         {
           "name": "group",
@@ -4165,13 +4406,12 @@ export type MangoV4 = {
         },
         {
           "name": "perpMarket",
-          "isMut": true,
+          "isMut": false,
           "isSigner": false,
           "relations": [
             "group",
             "bids",
-            "asks",
-            "oracle"
+            "asks"
           ]
         },
         {
@@ -4183,17 +4423,12 @@ export type MangoV4 = {
           "name": "asks",
           "isMut": true,
           "isSigner": false
-        },
-        {
-          "name": "oracle",
-          "isMut": false,
-          "isSigner": false
         }
       ],
       "args": []
     },
     {
-      "name": "perpSettlePnl",
+      "name": "perpUpdateFunding",
       "accounts": [
         {
           "name": "group",
@@ -4201,17 +4436,41 @@ export type MangoV4 = {
           "isSigner": false
         },
         {
-          "name": "settler",
+          "name": "perpMarket",
           "isMut": true,
           "isSigner": false,
           "relations": [
-            "group"
+            "group",
+            "bids",
+            "asks",
+            "oracle"
           ]
         },
         {
-          "name": "settlerOwner",
+          "name": "bids",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "asks",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "oracle",
           "isMut": false,
-          "isSigner": true
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpLogSpread",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
         },
         {
           "name": "perpMarket",
@@ -4219,14 +4478,154 @@ export type MangoV4 = {
           "isSigner": false,
           "relations": [
             "group",
+            "bids",
+            "asks",
             "oracle"
           ]
         },
         {
-          "name": "accountA",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "asks",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpOrderbookView",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "perpMarket",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "bids",
+            "asks",
+            "oracle"
+          ]
+        },
+        {
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "asks",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpOrderSnapshots",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "perpMarket",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "bids",
+            "asks",
+            "oracle"
+          ]
+        },
+        {
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "asks",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "side",
+          "type": {
+            "defined": "Side"
+          }
+        },
+        {
+          "name": "startIndex",
+          "type": "u32"
+        }
+      ],
+      "returns": {
+        "vec": {
+          "defined": "OrderSnapshot"
+        }
+      }
+    },
+    {
+      "name": "perpSettlePnl",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "settler",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "settlerOwner",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "perpMarket",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "oracle"
+          ]
+        },
+        {
+          "name": "accountA",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
             "group"
           ]
         },
@@ -4738,6 +5137,25 @@ export type MangoV4 = {
       ],
       "args": []
     },
+    {
+      "name": "computeLiquidationPriority",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "account",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        }
+      ],
+      "args": []
+    },
     {
       "name": "benchmark",
       "docs": [
@@ -5033,12 +5451,54 @@ export type MangoV4 = {
             "name": "forceClose",
             "type": "u8"
           },
+          {
+            "name": "liquidationFeeMode",
+            "docs": [
+              "How liquidation fees are charged when this token is the liability",
+              "0 - Factor: the liqor receives (1 + liquidation_fee) times the liab value in assets",
+              "1 - FlatQuote: the liqor receives the liab value plus flat_liquidation_fee_quote in assets,",
+              "once per liquidation of an account; force closes and bankruptcies pay no fee"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": [
+                "u8",
+                1
+              ]
+            }
+          },
+          {
+            "name": "flatLiquidationFeeQuote",
+            "docs": [
+              "Fee in native quote charged per liquidation when liquidation_fee_mode is FlatQuote"
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "maxVaultDiscrepancy",
+            "docs": [
+              "Largest vault discrepancy, in native tokens, that token_reconcile_vault may book"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "feesWithdrawn",
+            "docs": [
+              "Collected fees that were swept to the fees treasury, in native tokens",
+              "",
+              "The fees that can still be swept are collected_fees_native - fees_withdrawn."
+            ],
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                2118
+                2096
               ]
             }
           }
@@ -5144,12 +5604,73 @@ export type MangoV4 = {
             ],
             "type": "u64"
           },
+          {
+            "name": "feesTreasury",
+            "docs": [
+              "Owner of the token accounts that TokenSweepFees sends collected bank fees to.",
+              "",
+              "Sweeping is disabled while this is the default pubkey."
+            ],
+            "type": "publicKey"
+          },
+          {
+            "name": "maxLiquidationFee",
+            "docs": [
+              "Upper bound for bank liquidation fees, enforced in token_edit and when liquidating.",
+              "",
+              "Set to 0 to disable the bound."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "batchTokenBalanceLogs",
+            "docs": [
+              "If 1, instructions that change several token balances at once emit a single",
+              "TokenBalanceBatchLog instead of one TokenBalanceLog per balance."
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "liquidationFeeEmergencyMode",
+            "docs": [
+              "If 1, max_liquidation_fee is not enforced, allowing banks to pay liqors a",
+              "higher fee in emergencies."
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding2",
+            "type": {
+              "array": [
+                "u8",
+                2
+              ]
+            }
+          },
+          {
+            "name": "minLiquidationFeeQuote",
+            "docs": [
+              "Minimum fee in native quote that bank liquidation fees must pay the liqor on a",
+              "liquidation of min_liquidation_notional_quote, so that liquidations stay worth",
+              "the transaction costs. Enforced in token_edit when liquidation fees change.",
+              "",
+              "Set to 0 to disable the floor."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "minLiquidationNotionalQuote",
+            "docs": [
+              "Liquidation size in native quote that min_liquidation_fee_quote applies to."
+            ],
+            "type": "f32"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                1824
+                1776
               ]
             }
           }
@@ -5215,13 +5736,13 @@ export type MangoV4 = {
             "type": "u8"
           },
           {
-            "name": "padding",
-            "type": {
-              "array": [
-                "u8",
-                1
-              ]
-            }
+            "name": "flatLiquidationFeePaid",
+            "docs": [
+              "Set once a flat quote liquidation fee was paid during the current liquidation.",
+              "",
+              "Reset when the account stops being liquidated."
+            ],
+            "type": "u8"
           },
           {
             "name": "netDeposits",
@@ -5264,12 +5785,22 @@ export type MangoV4 = {
             ],
             "type": "u64"
           },
+          {
+            "name": "liqorMaxLiabQuote",
+            "docs": [
+              "When acting as a liqor, take on at most this much liab per token liquidation,",
+              "in native quote. Applies on top of the max_liab_transfer argument.",
+              "",
+              "Set to 0 to disable the cap."
+            ],
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                208
+                200
               ]
             }
           },
@@ -5534,7 +6065,9 @@ export type MangoV4 = {
               "Token index that settlements happen in.",
               "",
               "Currently required to be 0, USDC. In the future settlement",
-              "may be allowed to happen in other tokens."
+              "may be allowed to happen in other tokens.",
+              "",
+              "Can only be changed while the market has never been traded on."
             ],
             "type": "u16"
           },
@@ -5910,18 +6443,225 @@ export type MangoV4 = {
             }
           },
           {
-            "name": "reserved",
-            "type": {
-              "array": [
-                "u8",
-                1888
-              ]
-            }
-          }
-        ]
-      }
-    },
-    {
+            "name": "consumeEventsReward",
+            "docs": [
+              "In native units of settlement token, given to the keeper for each event",
+              "processed by perp_consume_events. Paid out of fees_accrued."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "defaultOrderExpirySeconds",
+            "docs": [
+              "Time in force in seconds for orders placed without an expiry timestamp.",
+              "",
+              "Zero means such orders never expire."
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "padding5",
+            "type": {
+              "array": [
+                "u8",
+                2
+              ]
+            }
+          },
+          {
+            "name": "circuitBreakerThreshold",
+            "docs": [
+              "Circuit breaker: halt order placement when the oracle price moves by more than",
+              "this fraction within circuit_breaker_window_seconds. Zero disables it."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "circuitBreakerWindowSeconds",
+            "type": "u32"
+          },
+          {
+            "name": "circuitBreakerCooldownSeconds",
+            "docs": [
+              "How long order placement stays halted after the breaker trips"
+            ],
+            "type": "u32"
+          },
+          {
+            "name": "padding6",
+            "type": {
+              "array": [
+                "u8",
+                4
+              ]
+            }
+          },
+          {
+            "name": "circuitBreakerWindowLowPrice",
+            "docs": [
+              "Lowest and highest oracle price seen in the current circuit breaker window.",
+              "Zero if no window was started yet."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "circuitBreakerWindowHighPrice",
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "circuitBreakerWindowStartTs",
+            "docs": [
+              "Start of the current circuit breaker window"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "circuitBreakerHaltedUntil",
+            "docs": [
+              "Order placement is halted until this timestamp"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "minEquityToPlaceOrder",
+            "docs": [
+              "Accounts need at least this much equity, in native quote, to place orders.",
+              "Zero disables the check."
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "makerRebateCapPerOpenInterest",
+            "docs": [
+              "Maker rebate cap: per epoch, makers receive at most this much rebate (in native",
+              "quote) per base lot of open interest. Fills beyond the cap get no rebate."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "makerRebateEpochSeconds",
+            "docs": [
+              "Length of a maker rebate cap epoch. Zero disables the cap."
+            ],
+            "type": "u32"
+          },
+          {
+            "name": "makerRebateEpochStartTs",
+            "type": "u64"
+          },
+          {
+            "name": "makerRebatesPaidInEpoch",
+            "docs": [
+              "Maker rebates granted in the current epoch, in native quote"
+            ],
+            "type": "f64"
+          },
+          {
+            "name": "takerFeePriceImpactFactor",
+            "docs": [
+              "Extra taker fee per unit of price impact: a taker order whose average fill price",
+              "over the consumed book depth is 2% away from the oracle price pays an additional",
+              "0.02 * factor taker fee. Zero disables it."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "minOrderRestSeconds",
+            "docs": [
+              "Orders can't be cancelled until they have rested on the book for this long,",
+              "unless the market is halted. Zero disables the check."
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "padding7",
+            "type": {
+              "array": [
+                "u8",
+                2
+              ]
+            }
+          },
+          {
+            "name": "eventQueueHaltFraction",
+            "docs": [
+              "Order placement is halted while the event queue is filled to at least this",
+              "fraction of its capacity, so fills can't pile up when event consumption lags.",
+              "Zero disables the check."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "padding8",
+            "type": {
+              "array": [
+                "u8",
+                4
+              ]
+            }
+          },
+          {
+            "name": "minOrderPriceLots",
+            "docs": [
+              "Orders that can rest on the book must have a price of at least this many lots.",
+              "Zero disables the check."
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "maxOrderPriceLots",
+            "docs": [
+              "Orders that can rest on the book must have a price of at most this many lots.",
+              "Zero disables the check."
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "fillPriceCumulative",
+            "docs": [
+              "Accumulated fill price over time, in price lots * seconds.",
+              "",
+              "Whenever a fill happens, the previous fill price is added for the time since",
+              "the previous fill. The difference between two observations divided by the time",
+              "between them is the time-weighted average fill price.",
+              "",
+              "Wraps around on overflow: only the difference between two observations matters."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "lastFillPriceLots",
+            "docs": [
+              "Price of the most recent fill, in lots."
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "lastFillTs",
+            "docs": [
+              "Timestamp of the most recent fill, zero if there was none yet."
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                1720
+              ]
+            }
+          }
+        ]
+      }
+    },
+    {
       "name": "serum3Market",
       "type": {
         "kind": "struct",
@@ -6447,6 +7187,34 @@ export type MangoV4 = {
         ]
       }
     },
+    {
+      "name": "TokenBalanceDetail",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mangoAccount",
+            "type": "publicKey"
+          },
+          {
+            "name": "tokenIndex",
+            "type": "u16"
+          },
+          {
+            "name": "indexedPosition",
+            "type": "i128"
+          },
+          {
+            "name": "depositIndex",
+            "type": "i128"
+          },
+          {
+            "name": "borrowIndex",
+            "type": "i128"
+          }
+        ]
+      }
+    },
     {
       "name": "TokenPosition",
       "type": {
@@ -6900,13 +7668,8 @@ export type MangoV4 = {
             "type": "u8"
           },
           {
-            "name": "padding",
-            "type": {
-              "array": [
-                "u8",
-                1
-              ]
-            }
+            "name": "flatLiquidationFeePaid",
+            "type": "u8"
           },
           {
             "name": "netDeposits",
@@ -6936,12 +7699,16 @@ export type MangoV4 = {
             "name": "buybackFeesExpiryTimestamp",
             "type": "u64"
           },
+          {
+            "name": "liqorMaxLiabQuote",
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                208
+                200
               ]
             }
           }
@@ -6994,81 +7761,154 @@ export type MangoV4 = {
       }
     },
     {
-      "name": "InnerNode",
+      "name": "OrderSnapshot",
       "docs": [
-        "InnerNodes and LeafNodes compose the binary tree of orders.",
+        "A resting order with everything needed to replay matching against it off-chain",
         "",
-        "Each InnerNode has exactly two children, which are either InnerNodes themselves,",
-        "or LeafNodes. The children share the top `prefix_len` bits of `key`. The left",
-        "child has a 0 in the next bit, and the right a 1."
+        "Built by BookSide::order_snapshots() from the BookSide accounts clients already",
+        "hold. A full book is far larger than the transaction return data limit, so no",
+        "instruction returns these."
       ],
       "type": {
         "kind": "struct",
         "fields": [
           {
-            "name": "tag",
-            "type": "u8"
+            "name": "side",
+            "type": {
+              "defined": "Side"
+            }
           },
           {
-            "name": "padding",
+            "name": "orderTree",
             "type": {
-              "array": [
-                "u8",
-                3
-              ]
+              "defined": "BookSideOrderTree"
             }
           },
           {
-            "name": "prefixLen",
+            "name": "isValid",
             "docs": [
-              "number of highest `key` bits that all children share",
-              "e.g. if it's 2, the two highest bits of `key` will be the same on all children"
+              "False for expired orders and oracle pegged orders beyond their peg limit"
             ],
-            "type": "u32"
+            "type": "bool"
           },
           {
             "name": "key",
-            "docs": [
-              "only the top `prefix_len` bits of `key` are relevant"
-            ],
             "type": "u128"
           },
           {
-            "name": "children",
-            "docs": [
-              "indexes into `BookSide::nodes`"
-            ],
-            "type": {
-              "array": [
-                "u32",
-                2
-              ]
-            }
+            "name": "owner",
+            "type": "publicKey"
           },
           {
-            "name": "childEarliestExpiry",
+            "name": "ownerSlot",
+            "type": "u8"
+          },
+          {
+            "name": "priceLots",
             "docs": [
-              "The earliest expiry timestamp for the left and right subtrees.",
-              "",
-              "Needed to be able to find and remove expired orders without having to",
-              "iterate through the whole bookside."
+              "Price at the oracle price the snapshot was taken at"
             ],
-            "type": {
-              "array": [
-                "u64",
-                2
-              ]
-            }
+            "type": "i64"
           },
           {
-            "name": "reserved",
-            "type": {
-              "array": [
-                "u8",
-                72
-              ]
-            }
-          }
+            "name": "quantity",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          },
+          {
+            "name": "timeInForce",
+            "type": "u16"
+          },
+          {
+            "name": "pegLimit",
+            "type": "i64"
+          },
+          {
+            "name": "clientOrderId",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "InnerNode",
+      "docs": [
+        "InnerNodes and LeafNodes compose the binary tree of orders.",
+        "",
+        "Each InnerNode has exactly two children, which are either InnerNodes themselves,",
+        "or LeafNodes. The children share the top `prefix_len` bits of `key`. The left",
+        "child has a 0 in the next bit, and the right a 1."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "tag",
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": [
+                "u8",
+                3
+              ]
+            }
+          },
+          {
+            "name": "prefixLen",
+            "docs": [
+              "number of highest `key` bits that all children share",
+              "e.g. if it's 2, the two highest bits of `key` will be the same on all children"
+            ],
+            "type": "u32"
+          },
+          {
+            "name": "key",
+            "docs": [
+              "only the top `prefix_len` bits of `key` are relevant"
+            ],
+            "type": "u128"
+          },
+          {
+            "name": "children",
+            "docs": [
+              "indexes into `BookSide::nodes`"
+            ],
+            "type": {
+              "array": [
+                "u32",
+                2
+              ]
+            }
+          },
+          {
+            "name": "childEarliestExpiry",
+            "docs": [
+              "The earliest expiry timestamp for the left and right subtrees.",
+              "",
+              "Needed to be able to find and remove expired orders without having to",
+              "iterate through the whole bookside."
+            ],
+            "type": {
+              "array": [
+                "u64",
+                2
+              ]
+            }
+          },
+          {
+            "name": "reserved",
+            "type": {
+              "array": [
+                "u8",
+                72
+              ]
+            }
+          }
         ]
       }
     },
@@ -7119,13 +7959,12 @@ export type MangoV4 = {
             "type": "u16"
           },
           {
-            "name": "padding2",
-            "type": {
-              "array": [
-                "u8",
-                2
-              ]
-            }
+            "name": "minRestSeconds",
+            "docs": [
+              "Seconds after `timestamp` before the order can be cancelled.",
+              "Copied from the perp market when the order is placed."
+            ],
+            "type": "u16"
           },
           {
             "name": "key",
@@ -7951,6 +8790,12 @@ export type MangoV4 = {
           },
           {
             "name": "GroupWithdrawInsuranceFund"
+          },
+          {
+            "name": "PerpBookDefragment"
+          },
+          {
+            "name": "TokenSweepFees"
           }
         ]
       }
@@ -8269,6 +9114,30 @@ export type MangoV4 = {
         }
       ]
     },
+    {
+      "name": "MangoAccountLiquidationPriority",
+      "fields": [
+        {
+          "name": "mangoAccount",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "initHealth",
+          "type": {
+            "defined": "I80F48"
+          },
+          "index": false
+        },
+        {
+          "name": "score",
+          "type": {
+            "defined": "I80F48"
+          },
+          "index": false
+        }
+      ]
+    },
     {
       "name": "PerpBalanceLog",
       "fields": [
@@ -9708,58 +10577,268 @@ export type MangoV4 = {
           "index": false
         }
       ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "SomeError",
-      "msg": ""
-    },
-    {
-      "code": 6001,
-      "name": "NotImplementedError",
-      "msg": ""
-    },
-    {
-      "code": 6002,
-      "name": "MathError",
-      "msg": "checked math error"
-    },
-    {
-      "code": 6003,
-      "name": "UnexpectedOracle",
-      "msg": ""
-    },
-    {
-      "code": 6004,
-      "name": "UnknownOracleType",
-      "msg": "oracle type cannot be determined"
-    },
-    {
-      "code": 6005,
-      "name": "InvalidFlashLoanTargetCpiProgram",
-      "msg": ""
-    },
-    {
-      "code": 6006,
-      "name": "HealthMustBePositive",
-      "msg": "health must be positive"
     },
     {
-      "code": 6007,
-      "name": "HealthMustBePositiveOrIncrease",
-      "msg": "health must be positive or increase"
+      "name": "TokenReconcileVaultLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "vaultAmount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "discrepancy",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "collectedFees",
+          "type": "i128",
+          "index": false
+        }
+      ]
     },
     {
-      "code": 6008,
-      "name": "HealthMustBeNegative",
-      "msg": "health must be negative"
+      "name": "PerpForceCancelOrdersLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "mangoAccount",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "ordersCancelled",
+          "type": "u8",
+          "index": false
+        }
+      ]
     },
     {
-      "code": 6009,
-      "name": "IsBankrupt",
-      "msg": "the account is bankrupt"
+      "name": "TokenSweepFeesLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "treasury",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "collectedFees",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "feesWithdrawn",
+          "type": "u64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PerpSpreadLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "oraclePrice",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "bestBidLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "bestAskLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "midPrice",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        },
+        {
+          "name": "spreadLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PerpOrderbookViewLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "oraclePrice",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "bestBidLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "bestAskLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "impactQuantity",
+          "type": "i64",
+          "index": false
+        },
+        {
+          "name": "bidImpactPriceLots",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        },
+        {
+          "name": "askImpactPriceLots",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "TokenBalanceBatchLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "balances",
+          "type": {
+            "vec": {
+              "defined": "TokenBalanceDetail"
+            }
+          },
+          "index": false
+        }
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "SomeError",
+      "msg": ""
+    },
+    {
+      "code": 6001,
+      "name": "NotImplementedError",
+      "msg": ""
+    },
+    {
+      "code": 6002,
+      "name": "MathError",
+      "msg": "checked math error"
+    },
+    {
+      "code": 6003,
+      "name": "UnexpectedOracle",
+      "msg": ""
+    },
+    {
+      "code": 6004,
+      "name": "UnknownOracleType",
+      "msg": "oracle type cannot be determined"
+    },
+    {
+      "code": 6005,
+      "name": "InvalidFlashLoanTargetCpiProgram",
+      "msg": ""
+    },
+    {
+      "code": 6006,
+      "name": "HealthMustBePositive",
+      "msg": "health must be positive"
+    },
+    {
+      "code": 6007,
+      "name": "HealthMustBePositiveOrIncrease",
+      "msg": "health must be positive or increase"
+    },
+    {
+      "code": 6008,
+      "name": "HealthMustBeNegative",
+      "msg": "health must be negative"
+    },
+    {
+      "code": 6009,
+      "name": "IsBankrupt",
+      "msg": "the account is bankrupt"
     },
     {
       "code": 6010,
@@ -9955,6 +11034,101 @@ export type MangoV4 = {
       "code": 6048,
       "name": "WouldSelfTrade",
       "msg": "would self trade"
+    },
+    {
+      "code": 6049,
+      "name": "LiquidationTooSmall",
+      "msg": "liquidation transfer is too small"
+    },
+    {
+      "code": 6050,
+      "name": "LiquidationSameToken",
+      "msg": "liquidation asset and liab token must be different"
+    },
+    {
+      "code": 6051,
+      "name": "LiquidationAssetNotPositive",
+      "msg": "liqee asset position must be positive"
+    },
+    {
+      "code": 6052,
+      "name": "LiquidationLiabNotNegative",
+      "msg": "liqee liab position must be negative"
+    },
+    {
+      "code": 6053,
+      "name": "LiquidationFeeTooHigh",
+      "msg": "liquidation fee exceeds the group's maximum"
+    },
+    {
+      "code": 6054,
+      "name": "PerpCircuitBreakerHalted",
+      "msg": "perp market order placement is halted by the circuit breaker"
+    },
+    {
+      "code": 6055,
+      "name": "PerpOrderEquityTooLow",
+      "msg": "account equity is below the market's minimum for placing orders"
+    },
+    {
+      "code": 6056,
+      "name": "LiquidationLiqorAssetNetting",
+      "msg": "liquidation would repay the liqor's existing asset borrow"
+    },
+    {
+      "code": 6057,
+      "name": "OracleTypeMismatch",
+      "msg": "oracle account does not match the expected oracle type"
+    },
+    {
+      "code": 6058,
+      "name": "PerpSettleTokenChangeNotAllowed",
+      "msg": "the perp market has been used and can't change its settle token"
+    },
+    {
+      "code": 6059,
+      "name": "PerpOrderMinRestTime",
+      "msg": "the perp order has not rested on the book for the minimum time yet"
+    },
+    {
+      "code": 6060,
+      "name": "PerpEventQueueCongested",
+      "msg": "the perp event queue is too full to place orders, events need to be consumed"
+    },
+    {
+      "code": 6061,
+      "name": "PerpOrderPriceOutOfRange",
+      "msg": "the perp order price is outside the market's allowed order price range"
+    },
+    {
+      "code": 6062,
+      "name": "LiquidationFeeTooLow",
+      "msg": "liquidation fee is below the group's minimum"
+    },
+    {
+      "code": 6063,
+      "name": "PerpMarketEditUnsafe",
+      "msg": "the perp market edit would bring a sample account below maintenance health"
+    },
+    {
+      "code": 6064,
+      "name": "TokenVaultDiscrepancyTooLarge",
+      "msg": "the vault discrepancy exceeds the bank's max_vault_discrepancy"
+    },
+    {
+      "code": 6065,
+      "name": "TokenVaultReconcileNegativeFees",
+      "msg": "reconciling the vault would make the bank's collected fees negative"
+    },
+    {
+      "code": 6066,
+      "name": "FeesTreasuryMismatch",
+      "msg": "the token account is not owned by the group's fees treasury"
+    },
+    {
+      "code": 6067,
+      "name": "NoFeesToSweep",
+      "msg": "the bank has no collected fees to sweep"
     }
   ]
 };
@@ -10138,6 +11312,42 @@ export const IDL: MangoV4 = {
           "type": {
             "option": "u64"
           }
+        },
+        {
+          "name": "feesTreasuryOpt",
+          "type": {
+            "option": "publicKey"
+          }
+        },
+        {
+          "name": "maxLiquidationFeeOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "batchTokenBalanceLogsOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "liquidationFeeEmergencyModeOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "minLiquidationFeeQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "minLiquidationNotionalQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
         }
       ]
     },
@@ -10743,6 +11953,24 @@ export const IDL: MangoV4 = {
           "type": {
             "option": "bool"
           }
+        },
+        {
+          "name": "liquidationFeeModeOpt",
+          "type": {
+            "option": "u8"
+          }
+        },
+        {
+          "name": "flatLiquidationFeeQuoteOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "maxVaultDiscrepancyOpt",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
@@ -10948,21 +12176,89 @@ export const IDL: MangoV4 = {
       "args": []
     },
     {
-      "name": "accountCreate",
+      "name": "tokenReconcileVault",
       "accounts": [
         {
           "name": "group",
           "isMut": false,
-          "isSigner": false
+          "isSigner": false,
+          "relations": [
+            "admin"
+          ]
         },
         {
-          "name": "account",
+          "name": "admin",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "bank",
           "isMut": true,
           "isSigner": false,
-          "pda": {
-            "seeds": [
-              {
-                "kind": "const",
+          "relations": [
+            "group",
+            "vault"
+          ]
+        },
+        {
+          "name": "vault",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "tokenSweepFees",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "bank",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "vault"
+          ]
+        },
+        {
+          "name": "vault",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "treasury",
+          "isMut": true,
+          "isSigner": false
+        },
+        {
+          "name": "tokenProgram",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "accountCreate",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "account",
+          "isMut": true,
+          "isSigner": false,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "const",
                 "type": "string",
                 "value": "MangoAccount"
               },
@@ -11114,6 +12410,12 @@ export const IDL: MangoV4 = {
           "type": {
             "option": "publicKey"
           }
+        },
+        {
+          "name": "liqorMaxLiabQuoteOpt",
+          "type": {
+            "option": "u64"
+          }
         }
       ]
     },
@@ -12847,6 +14149,14 @@ export const IDL: MangoV4 = {
           "type": {
             "defined": "I80F48"
           }
+        },
+        {
+          "name": "rejectLiqorAssetNetting",
+          "type": "bool"
+        },
+        {
+          "name": "dustLiqorAsset",
+          "type": "bool"
         }
       ]
     },
@@ -13179,7 +14489,8 @@ export const IDL: MangoV4 = {
           "isMut": false,
           "isSigner": false,
           "docs": [
-            "The oracle account is optional and only used when reset_stable_price is set.",
+            "The oracle account is optional and only used when reset_stable_price or",
+            "oracle_type_opt is set.",
             ""
           ]
         }
@@ -13364,6 +14675,110 @@ export const IDL: MangoV4 = {
           "type": {
             "option": "bool"
           }
+        },
+        {
+          "name": "consumeEventsRewardOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "defaultOrderExpirySecondsOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "circuitBreakerThresholdOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "circuitBreakerWindowSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "circuitBreakerCooldownSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "minEquityToPlaceOrderOpt",
+          "type": {
+            "option": "u64"
+          }
+        },
+        {
+          "name": "oracleTypeOpt",
+          "type": {
+            "option": {
+              "defined": "OracleType"
+            }
+          }
+        },
+        {
+          "name": "makerRebateCapPerOpenInterestOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "makerRebateEpochSecondsOpt",
+          "type": {
+            "option": "u32"
+          }
+        },
+        {
+          "name": "takerFeePriceImpactFactorOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "settleTokenIndexOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "minOrderRestSecondsOpt",
+          "type": {
+            "option": "u16"
+          }
+        },
+        {
+          "name": "eventQueueHaltFractionOpt",
+          "type": {
+            "option": "f32"
+          }
+        },
+        {
+          "name": "minOrderPriceLotsOpt",
+          "type": {
+            "option": "i64"
+          }
+        },
+        {
+          "name": "maxOrderPriceLotsOpt",
+          "type": {
+            "option": "i64"
+          }
+        },
+        {
+          "name": "checkSampleAccountsHealthOpt",
+          "type": {
+            "option": "bool"
+          }
+        },
+        {
+          "name": "resetFundingOpt",
+          "type": {
+            "option": "bool"
+          }
         }
       ]
     },
@@ -14117,7 +15532,7 @@ export const IDL: MangoV4 = {
       ]
     },
     {
-      "name": "perpUpdateFunding",
+      "name": "perpBookDefragment",
       "accounts": [
         {
           "name": "group",
@@ -14126,13 +15541,12 @@ export const IDL: MangoV4 = {
         },
         {
           "name": "perpMarket",
-          "isMut": true,
+          "isMut": false,
           "isSigner": false,
           "relations": [
             "group",
             "bids",
-            "asks",
-            "oracle"
+            "asks"
           ]
         },
         {
@@ -14144,76 +15558,41 @@ export const IDL: MangoV4 = {
           "name": "asks",
           "isMut": true,
           "isSigner": false
-        },
-        {
-          "name": "oracle",
-          "isMut": false,
-          "isSigner": false
         }
       ],
       "args": []
     },
     {
-      "name": "perpSettlePnl",
+      "name": "perpUpdateFunding",
       "accounts": [
         {
           "name": "group",
           "isMut": false,
           "isSigner": false
         },
-        {
-          "name": "settler",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
-        },
-        {
-          "name": "settlerOwner",
-          "isMut": false,
-          "isSigner": true
-        },
         {
           "name": "perpMarket",
-          "isMut": false,
+          "isMut": true,
           "isSigner": false,
           "relations": [
             "group",
+            "bids",
+            "asks",
             "oracle"
           ]
         },
         {
-          "name": "accountA",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
-        },
-        {
-          "name": "accountB",
+          "name": "bids",
           "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
-        },
-        {
-          "name": "oracle",
-          "isMut": false,
           "isSigner": false
         },
         {
-          "name": "settleBank",
+          "name": "asks",
           "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
+          "isSigner": false
         },
         {
-          "name": "settleOracle",
+          "name": "oracle",
           "isMut": false,
           "isSigner": false
         }
@@ -14221,7 +15600,7 @@ export const IDL: MangoV4 = {
       "args": []
     },
     {
-      "name": "perpForceClosePosition",
+      "name": "perpLogSpread",
       "accounts": [
         {
           "name": "group",
@@ -14230,28 +15609,24 @@ export const IDL: MangoV4 = {
         },
         {
           "name": "perpMarket",
-          "isMut": true,
+          "isMut": false,
           "isSigner": false,
           "relations": [
             "group",
+            "bids",
+            "asks",
             "oracle"
           ]
         },
         {
-          "name": "accountA",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
         },
         {
-          "name": "accountB",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
+          "name": "asks",
+          "isMut": false,
+          "isSigner": false
         },
         {
           "name": "oracle",
@@ -14262,7 +15637,7 @@ export const IDL: MangoV4 = {
       "args": []
     },
     {
-      "name": "perpSettleFees",
+      "name": "perpOrderbookView",
       "accounts": [
         {
           "name": "group",
@@ -14271,29 +15646,227 @@ export const IDL: MangoV4 = {
         },
         {
           "name": "perpMarket",
-          "isMut": true,
+          "isMut": false,
           "isSigner": false,
           "relations": [
             "group",
+            "bids",
+            "asks",
             "oracle"
           ]
         },
         {
-          "name": "account",
-          "isMut": true,
-          "isSigner": false,
-          "relations": [
-            "group"
-          ]
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
         },
         {
-          "name": "oracle",
+          "name": "asks",
           "isMut": false,
           "isSigner": false
         },
         {
-          "name": "settleBank",
-          "isMut": true,
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpOrderSnapshots",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "perpMarket",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "bids",
+            "asks",
+            "oracle"
+          ]
+        },
+        {
+          "name": "bids",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "asks",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": [
+        {
+          "name": "side",
+          "type": {
+            "defined": "Side"
+          }
+        },
+        {
+          "name": "startIndex",
+          "type": "u32"
+        }
+      ],
+      "returns": {
+        "vec": {
+          "defined": "OrderSnapshot"
+        }
+      }
+    },
+    {
+      "name": "perpSettlePnl",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "settler",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "settlerOwner",
+          "isMut": false,
+          "isSigner": true
+        },
+        {
+          "name": "perpMarket",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "oracle"
+          ]
+        },
+        {
+          "name": "accountA",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "accountB",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "settleBank",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "settleOracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpForceClosePosition",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "perpMarket",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "oracle"
+          ]
+        },
+        {
+          "name": "accountA",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "accountB",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "perpSettleFees",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "perpMarket",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group",
+            "oracle"
+          ]
+        },
+        {
+          "name": "account",
+          "isMut": true,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        },
+        {
+          "name": "oracle",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "settleBank",
+          "isMut": true,
           "isSigner": false,
           "relations": [
             "group"
@@ -14699,6 +16272,25 @@ export const IDL: MangoV4 = {
       ],
       "args": []
     },
+    {
+      "name": "computeLiquidationPriority",
+      "accounts": [
+        {
+          "name": "group",
+          "isMut": false,
+          "isSigner": false
+        },
+        {
+          "name": "account",
+          "isMut": false,
+          "isSigner": false,
+          "relations": [
+            "group"
+          ]
+        }
+      ],
+      "args": []
+    },
     {
       "name": "benchmark",
       "docs": [
@@ -14994,12 +16586,54 @@ export const IDL: MangoV4 = {
             "name": "forceClose",
             "type": "u8"
           },
+          {
+            "name": "liquidationFeeMode",
+            "docs": [
+              "How liquidation fees are charged when this token is the liability",
+              "0 - Factor: the liqor receives (1 + liquidation_fee) times the liab value in assets",
+              "1 - FlatQuote: the liqor receives the liab value plus flat_liquidation_fee_quote in assets,",
+              "once per liquidation of an account; force closes and bankruptcies pay no fee"
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding",
+            "type": {
+              "array": [
+                "u8",
+                1
+              ]
+            }
+          },
+          {
+            "name": "flatLiquidationFeeQuote",
+            "docs": [
+              "Fee in native quote charged per liquidation when liquidation_fee_mode is FlatQuote"
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "maxVaultDiscrepancy",
+            "docs": [
+              "Largest vault discrepancy, in native tokens, that token_reconcile_vault may book"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "feesWithdrawn",
+            "docs": [
+              "Collected fees that were swept to the fees treasury, in native tokens",
+              "",
+              "The fees that can still be swept are collected_fees_native - fees_withdrawn."
+            ],
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                2118
+                2096
               ]
             }
           }
@@ -15105,12 +16739,73 @@ export const IDL: MangoV4 = {
             ],
             "type": "u64"
           },
+          {
+            "name": "feesTreasury",
+            "docs": [
+              "Owner of the token accounts that TokenSweepFees sends collected bank fees to.",
+              "",
+              "Sweeping is disabled while this is the default pubkey."
+            ],
+            "type": "publicKey"
+          },
+          {
+            "name": "maxLiquidationFee",
+            "docs": [
+              "Upper bound for bank liquidation fees, enforced in token_edit and when liquidating.",
+              "",
+              "Set to 0 to disable the bound."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "batchTokenBalanceLogs",
+            "docs": [
+              "If 1, instructions that change several token balances at once emit a single",
+              "TokenBalanceBatchLog instead of one TokenBalanceLog per balance."
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "liquidationFeeEmergencyMode",
+            "docs": [
+              "If 1, max_liquidation_fee is not enforced, allowing banks to pay liqors a",
+              "higher fee in emergencies."
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "padding2",
+            "type": {
+              "array": [
+                "u8",
+                2
+              ]
+            }
+          },
+          {
+            "name": "minLiquidationFeeQuote",
+            "docs": [
+              "Minimum fee in native quote that bank liquidation fees must pay the liqor on a",
+              "liquidation of min_liquidation_notional_quote, so that liquidations stay worth",
+              "the transaction costs. Enforced in token_edit when liquidation fees change.",
+              "",
+              "Set to 0 to disable the floor."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "minLiquidationNotionalQuote",
+            "docs": [
+              "Liquidation size in native quote that min_liquidation_fee_quote applies to."
+            ],
+            "type": "f32"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                1824
+                1776
               ]
             }
           }
@@ -15176,13 +16871,13 @@ export const IDL: MangoV4 = {
             "type": "u8"
           },
           {
-            "name": "padding",
-            "type": {
-              "array": [
-                "u8",
-                1
-              ]
-            }
+            "name": "flatLiquidationFeePaid",
+            "docs": [
+              "Set once a flat quote liquidation fee was paid during the current liquidation.",
+              "",
+              "Reset when the account stops being liquidated."
+            ],
+            "type": "u8"
           },
           {
             "name": "netDeposits",
@@ -15225,12 +16920,22 @@ export const IDL: MangoV4 = {
             ],
             "type": "u64"
           },
+          {
+            "name": "liqorMaxLiabQuote",
+            "docs": [
+              "When acting as a liqor, take on at most this much liab per token liquidation,",
+              "in native quote. Applies on top of the max_liab_transfer argument.",
+              "",
+              "Set to 0 to disable the cap."
+            ],
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                208
+                200
               ]
             }
           },
@@ -15495,7 +17200,9 @@ export const IDL: MangoV4 = {
               "Token index that settlements happen in.",
               "",
               "Currently required to be 0, USDC. In the future settlement",
-              "may be allowed to happen in other tokens."
+              "may be allowed to happen in other tokens.",
+              "",
+              "Can only be changed while the market has never been traded on."
             ],
             "type": "u16"
           },
@@ -15685,197 +17392,404 @@ export const IDL: MangoV4 = {
             }
           },
           {
-            "name": "impactQuantity",
+            "name": "impactQuantity",
+            "docs": [
+              "For funding, get the impact price this many base lots deep into the book."
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "longFunding",
+            "docs": [
+              "Current long funding value. Increasing it means that every long base lot",
+              "needs to pay that amount of quote native in funding.",
+              "",
+              "PerpPosition uses and tracks it settle funding. Updated by the perp",
+              "keeper instruction."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "shortFunding",
+            "docs": [
+              "See long_funding."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "fundingLastUpdated",
+            "docs": [
+              "timestamp that funding was last updated in"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "baseLiquidationFee",
+            "docs": [
+              "Fees",
+              "Fee for base position liquidation"
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "makerFee",
+            "docs": [
+              "Fee when matching maker orders. May be negative."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "takerFee",
+            "docs": [
+              "Fee for taker orders, may not be negative."
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "feesAccrued",
+            "docs": [
+              "Fees accrued in native quote currency"
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "feesSettled",
+            "docs": [
+              "Fees settled in native quote currency"
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "feePenalty",
+            "docs": [
+              "Fee (in quote native) to charge for ioc orders"
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "settleFeeFlat",
+            "docs": [
+              "In native units of settlement token, given to each settle call above the",
+              "settle_fee_amount_threshold."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "settleFeeAmountThreshold",
+            "docs": [
+              "Pnl settlement amount needed to be eligible for the flat fee."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "settleFeeFractionLowHealth",
+            "docs": [
+              "Fraction of pnl to pay out as fee if +pnl account has low health."
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "settlePnlLimitFactor",
+            "docs": [
+              "Controls the strictness of the settle limit.",
+              "Set to a negative value to disable the limit.",
+              "",
+              "This factor applies to the settle limit in two ways",
+              "- for the unrealized pnl settle limit, the factor is multiplied with the stable perp base value",
+              "(i.e. limit_factor * base_native * stable_price)",
+              "- when increasing the realized pnl settle limit (stored per PerpPosition), the factor is",
+              "multiplied with the stable value of the perp pnl being realized",
+              "(i.e. limit_factor * reduced_native * stable_price)",
+              "",
+              "See also PerpPosition::settle_pnl_limit_realized_trade"
+            ],
+            "type": "f32"
+          },
+          {
+            "name": "padding3",
+            "type": {
+              "array": [
+                "u8",
+                4
+              ]
+            }
+          },
+          {
+            "name": "settlePnlLimitWindowSizeTs",
+            "docs": [
+              "Window size in seconds for the perp settlement limit"
+            ],
+            "type": "u64"
+          },
+          {
+            "name": "reduceOnly",
+            "docs": [
+              "If true, users may no longer increase their market exposure. Only actions",
+              "that reduce their position are still allowed."
+            ],
+            "type": "u8"
+          },
+          {
+            "name": "forceClose",
+            "type": "u8"
+          },
+          {
+            "name": "padding4",
+            "type": {
+              "array": [
+                "u8",
+                6
+              ]
+            }
+          },
+          {
+            "name": "maintOverallAssetWeight",
+            "docs": [
+              "Weights for full perp market health, if positive"
+            ],
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "initOverallAssetWeight",
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "positivePnlLiquidationFee",
+            "type": {
+              "defined": "I80F48"
+            }
+          },
+          {
+            "name": "consumeEventsReward",
             "docs": [
-              "For funding, get the impact price this many base lots deep into the book."
+              "In native units of settlement token, given to the keeper for each event",
+              "processed by perp_consume_events. Paid out of fees_accrued."
             ],
-            "type": "i64"
+            "type": "f32"
           },
           {
-            "name": "longFunding",
+            "name": "defaultOrderExpirySeconds",
             "docs": [
-              "Current long funding value. Increasing it means that every long base lot",
-              "needs to pay that amount of quote native in funding.",
+              "Time in force in seconds for orders placed without an expiry timestamp.",
               "",
-              "PerpPosition uses and tracks it settle funding. Updated by the perp",
-              "keeper instruction."
+              "Zero means such orders never expire."
             ],
-            "type": {
-              "defined": "I80F48"
-            }
+            "type": "u16"
           },
           {
-            "name": "shortFunding",
-            "docs": [
-              "See long_funding."
-            ],
+            "name": "padding5",
             "type": {
-              "defined": "I80F48"
+              "array": [
+                "u8",
+                2
+              ]
             }
           },
           {
-            "name": "fundingLastUpdated",
+            "name": "circuitBreakerThreshold",
             "docs": [
-              "timestamp that funding was last updated in"
+              "Circuit breaker: halt order placement when the oracle price moves by more than",
+              "this fraction within circuit_breaker_window_seconds. Zero disables it."
             ],
-            "type": "u64"
+            "type": "f32"
           },
           {
-            "name": "baseLiquidationFee",
+            "name": "circuitBreakerWindowSeconds",
+            "type": "u32"
+          },
+          {
+            "name": "circuitBreakerCooldownSeconds",
             "docs": [
-              "Fees",
-              "Fee for base position liquidation"
+              "How long order placement stays halted after the breaker trips"
             ],
+            "type": "u32"
+          },
+          {
+            "name": "padding6",
             "type": {
-              "defined": "I80F48"
+              "array": [
+                "u8",
+                4
+              ]
             }
           },
           {
-            "name": "makerFee",
+            "name": "circuitBreakerWindowLowPrice",
             "docs": [
-              "Fee when matching maker orders. May be negative."
+              "Lowest and highest oracle price seen in the current circuit breaker window.",
+              "Zero if no window was started yet."
             ],
             "type": {
               "defined": "I80F48"
             }
           },
           {
-            "name": "takerFee",
-            "docs": [
-              "Fee for taker orders, may not be negative."
-            ],
+            "name": "circuitBreakerWindowHighPrice",
             "type": {
               "defined": "I80F48"
             }
           },
           {
-            "name": "feesAccrued",
+            "name": "circuitBreakerWindowStartTs",
             "docs": [
-              "Fees accrued in native quote currency"
+              "Start of the current circuit breaker window"
             ],
-            "type": {
-              "defined": "I80F48"
-            }
+            "type": "u64"
           },
           {
-            "name": "feesSettled",
+            "name": "circuitBreakerHaltedUntil",
             "docs": [
-              "Fees settled in native quote currency"
+              "Order placement is halted until this timestamp"
             ],
-            "type": {
-              "defined": "I80F48"
-            }
+            "type": "u64"
           },
           {
-            "name": "feePenalty",
+            "name": "minEquityToPlaceOrder",
             "docs": [
-              "Fee (in quote native) to charge for ioc orders"
+              "Accounts need at least this much equity, in native quote, to place orders.",
+              "Zero disables the check."
             ],
-            "type": "f32"
+            "type": "u64"
           },
           {
-            "name": "settleFeeFlat",
+            "name": "makerRebateCapPerOpenInterest",
             "docs": [
-              "In native units of settlement token, given to each settle call above the",
-              "settle_fee_amount_threshold."
+              "Maker rebate cap: per epoch, makers receive at most this much rebate (in native",
+              "quote) per base lot of open interest. Fills beyond the cap get no rebate."
             ],
             "type": "f32"
           },
           {
-            "name": "settleFeeAmountThreshold",
+            "name": "makerRebateEpochSeconds",
             "docs": [
-              "Pnl settlement amount needed to be eligible for the flat fee."
+              "Length of a maker rebate cap epoch. Zero disables the cap."
             ],
-            "type": "f32"
+            "type": "u32"
           },
           {
-            "name": "settleFeeFractionLowHealth",
+            "name": "makerRebateEpochStartTs",
+            "type": "u64"
+          },
+          {
+            "name": "makerRebatesPaidInEpoch",
             "docs": [
-              "Fraction of pnl to pay out as fee if +pnl account has low health."
+              "Maker rebates granted in the current epoch, in native quote"
             ],
-            "type": "f32"
+            "type": "f64"
           },
           {
-            "name": "settlePnlLimitFactor",
+            "name": "takerFeePriceImpactFactor",
             "docs": [
-              "Controls the strictness of the settle limit.",
-              "Set to a negative value to disable the limit.",
-              "",
-              "This factor applies to the settle limit in two ways",
-              "- for the unrealized pnl settle limit, the factor is multiplied with the stable perp base value",
-              "(i.e. limit_factor * base_native * stable_price)",
-              "- when increasing the realized pnl settle limit (stored per PerpPosition), the factor is",
-              "multiplied with the stable value of the perp pnl being realized",
-              "(i.e. limit_factor * reduced_native * stable_price)",
-              "",
-              "See also PerpPosition::settle_pnl_limit_realized_trade"
+              "Extra taker fee per unit of price impact: a taker order whose average fill price",
+              "over the consumed book depth is 2% away from the oracle price pays an additional",
+              "0.02 * factor taker fee. Zero disables it."
             ],
             "type": "f32"
           },
           {
-            "name": "padding3",
+            "name": "minOrderRestSeconds",
+            "docs": [
+              "Orders can't be cancelled until they have rested on the book for this long,",
+              "unless the market is halted. Zero disables the check."
+            ],
+            "type": "u16"
+          },
+          {
+            "name": "padding7",
             "type": {
               "array": [
                 "u8",
-                4
+                2
               ]
             }
           },
           {
-            "name": "settlePnlLimitWindowSizeTs",
-            "docs": [
-              "Window size in seconds for the perp settlement limit"
-            ],
-            "type": "u64"
-          },
-          {
-            "name": "reduceOnly",
+            "name": "eventQueueHaltFraction",
             "docs": [
-              "If true, users may no longer increase their market exposure. Only actions",
-              "that reduce their position are still allowed."
+              "Order placement is halted while the event queue is filled to at least this",
+              "fraction of its capacity, so fills can't pile up when event consumption lags.",
+              "Zero disables the check."
             ],
-            "type": "u8"
-          },
-          {
-            "name": "forceClose",
-            "type": "u8"
+            "type": "f32"
           },
           {
-            "name": "padding4",
+            "name": "padding8",
             "type": {
               "array": [
                 "u8",
-                6
+                4
               ]
             }
           },
           {
-            "name": "maintOverallAssetWeight",
+            "name": "minOrderPriceLots",
             "docs": [
-              "Weights for full perp market health, if positive"
+              "Orders that can rest on the book must have a price of at least this many lots.",
+              "Zero disables the check."
             ],
-            "type": {
-              "defined": "I80F48"
-            }
+            "type": "i64"
           },
           {
-            "name": "initOverallAssetWeight",
-            "type": {
-              "defined": "I80F48"
-            }
+            "name": "maxOrderPriceLots",
+            "docs": [
+              "Orders that can rest on the book must have a price of at most this many lots.",
+              "Zero disables the check."
+            ],
+            "type": "i64"
           },
           {
-            "name": "positivePnlLiquidationFee",
+            "name": "fillPriceCumulative",
+            "docs": [
+              "Accumulated fill price over time, in price lots * seconds.",
+              "",
+              "Whenever a fill happens, the previous fill price is added for the time since",
+              "the previous fill. The difference between two observations divided by the time",
+              "between them is the time-weighted average fill price.",
+              "",
+              "Wraps around on overflow: only the difference between two observations matters."
+            ],
             "type": {
               "defined": "I80F48"
             }
           },
+          {
+            "name": "lastFillPriceLots",
+            "docs": [
+              "Price of the most recent fill, in lots."
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "lastFillTs",
+            "docs": [
+              "Timestamp of the most recent fill, zero if there was none yet."
+            ],
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                1888
+                1720
               ]
             }
           }
@@ -16408,6 +18322,34 @@ export const IDL: MangoV4 = {
         ]
       }
     },
+    {
+      "name": "TokenBalanceDetail",
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mangoAccount",
+            "type": "publicKey"
+          },
+          {
+            "name": "tokenIndex",
+            "type": "u16"
+          },
+          {
+            "name": "indexedPosition",
+            "type": "i128"
+          },
+          {
+            "name": "depositIndex",
+            "type": "i128"
+          },
+          {
+            "name": "borrowIndex",
+            "type": "i128"
+          }
+        ]
+      }
+    },
     {
       "name": "TokenPosition",
       "type": {
@@ -16861,13 +18803,8 @@ export const IDL: MangoV4 = {
             "type": "u8"
           },
           {
-            "name": "padding",
-            "type": {
-              "array": [
-                "u8",
-                1
-              ]
-            }
+            "name": "flatLiquidationFeePaid",
+            "type": "u8"
           },
           {
             "name": "netDeposits",
@@ -16897,12 +18834,16 @@ export const IDL: MangoV4 = {
             "name": "buybackFeesExpiryTimestamp",
             "type": "u64"
           },
+          {
+            "name": "liqorMaxLiabQuote",
+            "type": "u64"
+          },
           {
             "name": "reserved",
             "type": {
               "array": [
                 "u8",
-                208
+                200
               ]
             }
           }
@@ -16954,6 +18895,79 @@ export const IDL: MangoV4 = {
         ]
       }
     },
+    {
+      "name": "OrderSnapshot",
+      "docs": [
+        "A resting order with everything needed to replay matching against it off-chain",
+        "",
+        "Built by BookSide::order_snapshots() from the BookSide accounts clients already",
+        "hold. A full book is far larger than the transaction return data limit, so no",
+        "instruction returns these."
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "side",
+            "type": {
+              "defined": "Side"
+            }
+          },
+          {
+            "name": "orderTree",
+            "type": {
+              "defined": "BookSideOrderTree"
+            }
+          },
+          {
+            "name": "isValid",
+            "docs": [
+              "False for expired orders and oracle pegged orders beyond their peg limit"
+            ],
+            "type": "bool"
+          },
+          {
+            "name": "key",
+            "type": "u128"
+          },
+          {
+            "name": "owner",
+            "type": "publicKey"
+          },
+          {
+            "name": "ownerSlot",
+            "type": "u8"
+          },
+          {
+            "name": "priceLots",
+            "docs": [
+              "Price at the oracle price the snapshot was taken at"
+            ],
+            "type": "i64"
+          },
+          {
+            "name": "quantity",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "u64"
+          },
+          {
+            "name": "timeInForce",
+            "type": "u16"
+          },
+          {
+            "name": "pegLimit",
+            "type": "i64"
+          },
+          {
+            "name": "clientOrderId",
+            "type": "u64"
+          }
+        ]
+      }
+    },
     {
       "name": "InnerNode",
       "docs": [
@@ -17077,16 +19091,15 @@ export const IDL: MangoV4 = {
               "Time in seconds after `timestamp` at which the order expires.",
               "A value of 0 means no expiry."
             ],
-            "type": "u16"
-          },
-          {
-            "name": "padding2",
-            "type": {
-              "array": [
-                "u8",
-                2
-              ]
-            }
+            "type": "u16"
+          },
+          {
+            "name": "minRestSeconds",
+            "docs": [
+              "Seconds after `timestamp` before the order can be cancelled.",
+              "Copied from the perp market when the order is placed."
+            ],
+            "type": "u16"
           },
           {
             "name": "key",
@@ -17912,6 +19925,12 @@ export const IDL: MangoV4 = {
           },
           {
             "name": "GroupWithdrawInsuranceFund"
+          },
+          {
+            "name": "PerpBookDefragment"
+          },
+          {
+            "name": "TokenSweepFees"
           }
         ]
       }
@@ -18230,6 +20249,30 @@ export const IDL: MangoV4 = {
         }
       ]
     },
+    {
+      "name": "MangoAccountLiquidationPriority",
+      "fields": [
+        {
+          "name": "mangoAccount",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "initHealth",
+          "type": {
+            "defined": "I80F48"
+          },
+          "index": false
+        },
+        {
+          "name": "score",
+          "type": {
+            "defined": "I80F48"
+          },
+          "index": false
+        }
+      ]
+    },
     {
       "name": "PerpBalanceLog",
       "fields": [
@@ -19669,6 +21712,216 @@ export const IDL: MangoV4 = {
           "index": false
         }
       ]
+    },
+    {
+      "name": "TokenReconcileVaultLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "vaultAmount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "discrepancy",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "collectedFees",
+          "type": "i128",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PerpForceCancelOrdersLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "mangoAccount",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "ordersCancelled",
+          "type": "u8",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "TokenSweepFeesLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "tokenIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "treasury",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "amount",
+          "type": "u64",
+          "index": false
+        },
+        {
+          "name": "collectedFees",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "feesWithdrawn",
+          "type": "u64",
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PerpSpreadLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "oraclePrice",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "bestBidLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "bestAskLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "midPrice",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        },
+        {
+          "name": "spreadLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "PerpOrderbookViewLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "perpMarketIndex",
+          "type": "u16",
+          "index": false
+        },
+        {
+          "name": "oraclePrice",
+          "type": "i128",
+          "index": false
+        },
+        {
+          "name": "bestBidLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "bestAskLots",
+          "type": {
+            "option": "i64"
+          },
+          "index": false
+        },
+        {
+          "name": "impactQuantity",
+          "type": "i64",
+          "index": false
+        },
+        {
+          "name": "bidImpactPriceLots",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        },
+        {
+          "name": "askImpactPriceLots",
+          "type": {
+            "option": "i128"
+          },
+          "index": false
+        }
+      ]
+    },
+    {
+      "name": "TokenBalanceBatchLog",
+      "fields": [
+        {
+          "name": "mangoGroup",
+          "type": "publicKey",
+          "index": false
+        },
+        {
+          "name": "balances",
+          "type": {
+            "vec": {
+              "defined": "TokenBalanceDetail"
+            }
+          },
+          "index": false
+        }
+      ]
     }
   ],
   "errors": [
@@ -19916,6 +22169,101 @@ export const IDL: MangoV4 = {
       "code": 6048,
       "name": "WouldSelfTrade",
       "msg": "would self trade"
+    },
+    {
+      "code": 6049,
+      "name": "LiquidationTooSmall",
+      "msg": "liquidation transfer is too small"
+    },
+    {
+      "code": 6050,
+      "name": "LiquidationSameToken",
+      "msg": "liquidation asset and liab token must be different"
+    },
+    {
+      "code": 6051,
+      "name": "LiquidationAssetNotPositive",
+      "msg": "liqee asset position must be positive"
+    },
+    {
+      "code": 6052,
+      "name": "LiquidationLiabNotNegative",
+      "msg": "liqee liab position must be negative"
+    },
+    {
+      "code": 6053,
+      "name": "LiquidationFeeTooHigh",
+      "msg": "liquidation fee exceeds the group's maximum"
+    },
+    {
+      "code": 6054,
+      "name": "PerpCircuitBreakerHalted",
+      "msg": "perp market order placement is halted by the circuit breaker"
+    },
+    {
+      "code": 6055,
+      "name": "PerpOrderEquityTooLow",
+      "msg": "account equity is below the market's minimum for placing orders"
+    },
+    {
+      "code": 6056,
+      "name": "LiquidationLiqorAssetNetting",
+      "msg": "liquidation would repay the liqor's existing asset borrow"
+    },
+    {
+      "code": 6057,
+      "name": "OracleTypeMismatch",
+      "msg": "oracle account does not match the expected oracle type"
+    },
+    {
+      "code": 6058,
+      "name": "PerpSettleTokenChangeNotAllowed",
+      "msg": "the perp market has been used and can't change its settle token"
+    },
+    {
+      "code": 6059,
+      "name": "PerpOrderMinRestTime",
+      "msg": "the perp order has not rested on the book for the minimum time yet"
+    },
+    {
+      "code": 6060,
+      "name": "PerpEventQueueCongested",
+      "msg": "the perp event queue is too full to place orders, events need to be consumed"
+    },
+    {
+      "code": 6061,
+      "name": "PerpOrderPriceOutOfRange",
+      "msg": "the perp order price is outside the market's allowed order price range"
+    },
+    {
+      "code": 6062,
+      "name": "LiquidationFeeTooLow",
+      "msg": "liquidation fee is below the group's minimum"
+    },
+    {
+      "code": 6063,
+      "name": "PerpMarketEditUnsafe",
+      "msg": "the perp market edit would bring a sample account below maintenance health"
+    },
+    {
+      "code": 6064,
+      "name": "TokenVaultDiscrepancyTooLarge",
+      "msg": "the vault discrepancy exceeds the bank's max_vault_discrepancy"
+    },
+    {
+      "code": 6065,
+      "name": "TokenVaultReconcileNegativeFees",
+      "msg": "reconciling the vault would make the bank's collected fees negative"
+    },
+    {
+      "code": 6066,
+      "name": "FeesTreasuryMismatch",
+      "msg": "the token account is not owned by the group's fees treasury"
+    },
+    {
+      "code": 6067,
+      "name": "NoFeesToSweep",
+      "msg": "the bank has no collected fees to sweep"
     }
   ]
 };
@@ -12,6 +12,13 @@ export class FlashLoanType {
   static swap = { swap: {} };
 }
 
+export class OracleType {
+  static pyth = { pyth: {} };
+  static stub = { stub: {} };
+  static switchboardV1 = { switchboardV1: {} };
+  static switchboardV2 = { switchboardV2: {} };
+}
+
 export class InterestRateParams {
   util0: number;
   rate0: number;